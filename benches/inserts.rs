@@ -1,6 +1,8 @@
 use criterion::{black_box, criterion_group, criterion_main, Criterion};
 
-use kodiak_sets::Sequence;
+use kodiak_sets::{PositionStrategy, Sequence};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
 
 mod helper;
 use helper::next_ascii_char;
@@ -46,6 +48,17 @@ fn vec_with_capacity_insert_at_last_index(n: usize) {
     }
 }
 
+fn seq_with_strategy_insert_at_random_index(n: usize, strategy: PositionStrategy) {
+    let mut seq: Sequence<char> = Sequence::with_strategy(strategy);
+    let mut rng = StdRng::seed_from_u64(42);
+    let mut i: usize = 0;
+
+    while i < n {
+        seq.insert(rng.gen_range(0..=i), next_ascii_char('A', (i % 10) as u8).unwrap());
+        i += 1;
+    }
+}
+
 fn bench_seq_new_vs_with_capacity(c: &mut Criterion) {
     let mut group = c.benchmark_group("bench_new_vs_with_capacity");
     let n = 1_000_000;
@@ -70,5 +83,23 @@ fn bench_seq_vs_vec_new(c: &mut Criterion) {
     });
 }
 
-criterion_group!(benches, bench_seq_new_vs_with_capacity, bench_seq_vs_vec_new);
+fn bench_mediant_vs_stern_brocot(c: &mut Criterion) {
+    let mut group = c.benchmark_group("bench_mediant_vs_stern_brocot");
+    let n = 10_000;
+
+    group.bench_function(format!("seq mediant - insert {} chars at random index", n).as_str(), |b| {
+        b.iter(|| seq_with_strategy_insert_at_random_index(black_box(n), PositionStrategy::Mediant))
+    });
+    group.bench_function(
+        format!("seq stern-brocot - insert {} chars at random index", n).as_str(),
+        |b| b.iter(|| seq_with_strategy_insert_at_random_index(black_box(n), PositionStrategy::SternBrocot)),
+    );
+}
+
+criterion_group!(
+    benches,
+    bench_seq_new_vs_with_capacity,
+    bench_seq_vs_vec_new,
+    bench_mediant_vs_stern_brocot
+);
 criterion_main!(benches);
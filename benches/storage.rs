@@ -0,0 +1,90 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+
+use kodiak_sets::{BTreeSequence, Pos, Sequence, TreeSequence};
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+
+// Positions 1/1, 2/1, ..., n/1 in random order
+fn shuffled_positions(n: usize) -> Vec<Pos> {
+    let mut positions: Vec<Pos> = (1..=n as u64).map(|num| Pos::try_new(num, 1).unwrap()).collect();
+    positions.shuffle(&mut StdRng::seed_from_u64(42));
+    positions
+}
+
+fn seq_insert_at_remove_at(positions: &[Pos]) {
+    let mut seq: Sequence<usize> = Sequence::new();
+
+    for (i, position) in positions.iter().enumerate() {
+        seq.insert_at(*position, i);
+    }
+    for position in positions.iter() {
+        black_box(seq.get_at(position));
+    }
+    for position in positions.iter() {
+        seq.remove_at(position);
+    }
+}
+
+fn btree_seq_insert_at_remove_at(positions: &[Pos]) {
+    let mut seq: BTreeSequence<usize> = BTreeSequence::new();
+
+    for (i, position) in positions.iter().enumerate() {
+        seq.insert_at(*position, i);
+    }
+    for position in positions.iter() {
+        black_box(seq.get_at(position));
+    }
+    for position in positions.iter() {
+        seq.remove_at(position);
+    }
+}
+
+fn seq_insert_get_at_random_index(n: usize) {
+    let mut seq: Sequence<usize> = Sequence::new();
+    let mut rng = StdRng::seed_from_u64(42);
+
+    for i in 0..n {
+        seq.insert(rng.gen_range(0..=i), i);
+        black_box(seq.get(rng.gen_range(0..=i)));
+    }
+}
+
+fn tree_seq_insert_get_at_random_index(n: usize) {
+    let mut seq: TreeSequence<usize> = TreeSequence::new();
+    let mut rng = StdRng::seed_from_u64(42);
+
+    for i in 0..n {
+        seq.insert(rng.gen_range(0..=i), i);
+        black_box(seq.get(rng.gen_range(0..=i)));
+    }
+}
+
+fn bench_seq_vs_btree_seq(c: &mut Criterion) {
+    let mut group = c.benchmark_group("bench_seq_vs_btree_seq");
+    let n = 100_000;
+    let positions = shuffled_positions(n);
+
+    group.bench_function(format!("seq - insert, find and remove {} positions", n).as_str(), |b| {
+        b.iter(|| seq_insert_at_remove_at(black_box(&positions)))
+    });
+    group.bench_function(format!("btree seq - insert, find and remove {} positions", n).as_str(), |b| {
+        b.iter(|| btree_seq_insert_at_remove_at(black_box(&positions)))
+    });
+}
+
+fn bench_seq_vs_tree_seq(c: &mut Criterion) {
+    let mut group = c.benchmark_group("bench_seq_vs_tree_seq");
+    let n = 50_000;
+
+    group.bench_function(format!("seq - insert and get {} elements at random index", n).as_str(), |b| {
+        b.iter(|| seq_insert_get_at_random_index(black_box(n)))
+    });
+    group.bench_function(
+        format!("tree seq - insert and get {} elements at random index", n).as_str(),
+        |b| b.iter(|| tree_seq_insert_get_at_random_index(black_box(n))),
+    );
+}
+
+criterion_group!(benches, bench_seq_vs_btree_seq, bench_seq_vs_tree_seq);
+criterion_main!(benches);
@@ -0,0 +1,357 @@
+//! `BigPos` is a position of arbitrary-precision numerator and denominator, available with feature `bigint`.
+//! Unlike `Pos`, it never runs out of positions in between two others, at the cost of heap allocations.
+
+use std::cmp::Ordering;
+use std::fmt::{Debug, Display, Formatter};
+use std::hash::{Hash, Hasher};
+
+use num_bigint::BigUint;
+use num_integer::Integer;
+#[cfg(feature = "serde-derive")]
+use serde::{Deserialize, Serialize};
+
+use crate::error::PosError;
+use crate::sequence::{Pos, Position, PositionStrategy};
+
+/// `BigPos` defines the ordering of `Node`s in a `Sequence` like [`Pos`], but with `BigUint` numerator and denominator.
+///
+/// Use it as position type of long-lived sequences, i.e. `Sequence<T, BigPos>`,
+/// which must survive any number of inserts without rebalancing.
+///
+/// ```
+/// use kodiak_sets::{BigPos, Sequence};
+///
+/// let mut seq: Sequence<u32, BigPos> = Sequence::with_position();
+/// seq.push(0);
+///
+/// for i in 1..=100 {
+///     seq.insert(0, i);
+/// }
+///
+/// assert_eq!(seq.position_from(0).unwrap().to_string(), "1/101");
+/// ```
+///
+/// With feature `serde-derive`, it is serialized as `(numerator, denominator)` pair and validated when deserialized.
+#[derive(Clone)]
+#[cfg_attr(
+    feature = "serde-derive",
+    derive(Serialize, Deserialize),
+    serde(try_from = "(BigUint, BigUint)", into = "(BigUint, BigUint)")
+)]
+pub struct BigPos {
+    num: BigUint,
+    denom: BigUint,
+}
+
+impl BigPos {
+    /// Creates a `BigPos` from a numerator and a denominator.
+    /// The fraction is stored as given, use [`reduced()`](`BigPos::reduced()`) to get its canonical form.
+    ///
+    /// # Errors
+    /// Returns `PosError::ZeroDenominator` if denominator is 0.
+    #[inline]
+    pub fn try_new(num: impl Into<BigUint>, denom: impl Into<BigUint>) -> Result<Self, PosError> {
+        let denom = denom.into();
+
+        if denom == BigUint::ZERO {
+            Err(PosError::ZeroDenominator)
+        } else {
+            Ok(BigPos { num: num.into(), denom })
+        }
+    }
+
+    /// Returns the numerator.
+    #[inline]
+    #[must_use]
+    pub fn num(&self) -> &BigUint {
+        &self.num
+    }
+
+    /// Returns the denominator, which is always >= 1.
+    #[inline]
+    #[must_use]
+    pub fn denom(&self) -> &BigUint {
+        &self.denom
+    }
+
+    /// Returns the canonical form of `BigPos`, i.e. the fraction reduced to lowest terms.
+    #[inline]
+    #[must_use]
+    pub fn reduced(&self) -> Self {
+        let divisor = self.num.gcd(&self.denom);
+
+        BigPos {
+            num: &self.num / &divisor,
+            denom: &self.denom / &divisor,
+        }
+    }
+
+    /// Returns true if `BigPos` is in its canonical form, see [`reduced()`](`BigPos::reduced()`).
+    #[inline]
+    #[must_use]
+    pub fn is_reduced(&self) -> bool {
+        self.num.gcd(&self.denom) == BigUint::from(1u32)
+    }
+
+    /// Returns the mediant of two positions, i.e. `(a + c) / (b + d)` for `a/b` and `c/d`,
+    /// which lies strictly between both positions.
+    ///
+    /// Returns None if both positions are equal and there is no position in between.
+    /// Unlike [`Pos::checked_mid()`], there is no overflow.
+    #[inline]
+    #[must_use]
+    pub fn checked_mid(first: &Self, second: &Self) -> Option<Self> {
+        if first == second {
+            None
+        } else {
+            Some(BigPos {
+                num: &first.num + &second.num,
+                denom: &first.denom + &second.denom,
+            })
+        }
+    }
+
+    /// Returns the fraction with the smallest denominator strictly between two positions,
+    /// found by descending the Stern–Brocot tree. The result is reduced.
+    ///
+    /// Returns None if both positions are equal.
+    #[must_use]
+    pub fn simplest_between(first: &Self, second: &Self) -> Option<Self> {
+        let (lower, upper) = match first.cmp(second) {
+            Ordering::Less => (first, second),
+            Ordering::Greater => (second, first),
+            Ordering::Equal => return None,
+        };
+
+        Some(Self::simplest_above(lower, Some(upper)))
+    }
+
+    /// Returns the fraction with the smallest denominator strictly between lower and upper,
+    /// with an upper bound of None standing for no bound at all. Requires lower < upper.
+    fn simplest_above(lower: &Self, upper: Option<&Self>) -> Self {
+        // Descends the Stern–Brocot tree one continued fraction term at a time, see `Pos::simplest_between()`.
+        fn descend(lower: (BigUint, BigUint), upper: Option<(BigUint, BigUint)>) -> (BigUint, BigUint) {
+            let (num, denom) = lower;
+            let (floor, rem) = num.div_rem(&denom);
+            let next = &floor + 1u32;
+
+            match upper {
+                None => (next, BigUint::from(1u32)),
+                Some((upper_num, upper_denom)) => {
+                    // Take the next integer if it is in between.
+                    if &next * &upper_denom < upper_num {
+                        return (next, BigUint::from(1u32));
+                    }
+
+                    // Both bounds share the integer part, continue with the reciprocals of the fractional parts.
+                    let lower = (upper_denom.clone(), upper_num - &floor * &upper_denom);
+                    let upper = if rem == BigUint::ZERO { None } else { Some((denom, rem)) };
+                    let (num, denom) = descend(lower, upper);
+
+                    (floor * &num + denom, num)
+                }
+            }
+        }
+
+        let (num, denom) = descend(
+            (lower.num.clone(), lower.denom.clone()),
+            upper.map(|upper| (upper.num.clone(), upper.denom.clone())),
+        );
+
+        BigPos { num, denom }
+    }
+
+    #[inline]
+    fn zero() -> Self {
+        BigPos {
+            num: BigUint::ZERO,
+            denom: BigUint::from(1u32),
+        }
+    }
+}
+
+impl Position for BigPos {
+    /// Returns a position strictly between lower and upper as defined by strategy, see `Pos`.
+    /// A lower bound of None stands for `0/1` (exclusive), an upper bound of None for no bound at all.
+    fn between(lower: Option<&Self>, upper: Option<&Self>, strategy: PositionStrategy) -> Option<Self> {
+        let zero = BigPos::zero();
+
+        match (strategy, lower, upper) {
+            (PositionStrategy::Mediant, None, None) => Some(BigPos::default()),
+            (PositionStrategy::Mediant, Some(lower), None) => Some(BigPos {
+                num: &lower.num + 1u32,
+                denom: lower.denom.clone(),
+            }),
+            (PositionStrategy::Mediant, lower, Some(upper)) => BigPos::checked_mid(lower.unwrap_or(&zero), upper),
+            (PositionStrategy::SternBrocot, lower, upper) => {
+                let lower = lower.unwrap_or(&zero);
+                match upper {
+                    Some(upper) if upper <= lower => None,
+                    _ => Some(BigPos::simplest_above(lower, upper)),
+                }
+            }
+        }
+    }
+
+    /// Returns n ascending positions strictly between lower and upper, with small denominators.
+    /// Positions are spread evenly between both bounds, or are consecutive integers if there is no upper bound.
+    /// The common denominator is found by bisection like for `Pos`, so it is not always the smallest one which fits.
+    fn spread(lower: Option<&Self>, upper: Option<&Self>, n: usize) -> Option<Vec<Self>> {
+        if n == 0 {
+            return Some(Vec::new());
+        }
+
+        let zero = BigPos::zero();
+        let lower = lower.unwrap_or(&zero);
+        if upper.is_some_and(|upper| upper <= lower) {
+            return None;
+        }
+
+        let n = BigUint::from(n);
+        // Returns the first numerator and the number of numerators m, for which m/denom is in between the bounds.
+        let numerators = |denom: &BigUint| -> (BigUint, BigUint) {
+            let first = &lower.num * denom / &lower.denom + 1u32;
+            let count = match upper {
+                None => n.clone(),
+                // upper > lower >= 0, so its numerator is >= 1.
+                Some(upper) => {
+                    let last = (&upper.num * denom - 1u32) / &upper.denom;
+                    if last >= first {
+                        last - &first + 1u32
+                    } else {
+                        BigUint::ZERO
+                    }
+                }
+            };
+
+            (first, count)
+        };
+
+        // Find a small denominator by doubling it first and bisecting afterwards.
+        let mut denom = BigUint::from(1u32);
+        while numerators(&denom).1 < n {
+            denom *= 2u32;
+        }
+        let (mut low, mut high) = (&denom / 2u32 + 1u32, denom);
+        while low < high {
+            let mid = &low + (&high - &low) / 2u32;
+            if numerators(&mid).1 >= n {
+                high = mid;
+            } else {
+                low = mid + 1u32;
+            }
+        }
+        let denom = high;
+        let (first, count) = numerators(&denom);
+
+        let mut positions = Vec::new();
+        let mut i = BigUint::ZERO;
+        while i < n {
+            let num = match upper {
+                None => &first + &i,
+                Some(_) => &first + (&i + 1u32) * (&count + 1u32) / (&n + 1u32) - 1u32,
+            };
+            positions.push(
+                BigPos {
+                    num,
+                    denom: denom.clone(),
+                }
+                .reduced(),
+            );
+            i += 1u32;
+        }
+
+        Some(positions)
+    }
+}
+
+/// `BigPos` are compared as fractions, i.e. `1/2 == 2/4`.
+impl PartialEq for BigPos {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for BigPos {}
+
+impl PartialOrd for BigPos {
+    #[inline]
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for BigPos {
+    #[inline]
+    fn cmp(&self, other: &Self) -> Ordering {
+        (&self.num * &other.denom).cmp(&(&other.num * &self.denom))
+    }
+}
+
+/// Hashes the reduced fraction, so that equal `BigPos` (e.g. `1/2` and `2/4`) have equal hashes.
+impl Hash for BigPos {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        let reduced = self.reduced();
+
+        reduced.num.hash(state);
+        reduced.denom.hash(state);
+    }
+}
+
+/// Creates a `BigPos` from a `(numerator, denominator)` pair, e.g. as loaded from a database.
+impl TryFrom<(BigUint, BigUint)> for BigPos {
+    type Error = PosError;
+
+    #[inline]
+    fn try_from((num, denom): (BigUint, BigUint)) -> Result<Self, Self::Error> {
+        BigPos::try_new(num, denom)
+    }
+}
+
+/// Converts a `BigPos` into its `(numerator, denominator)` pair, e.g. to store it in a database.
+/// The pair is returned as stored, not reduced.
+impl From<BigPos> for (BigUint, BigUint) {
+    #[inline]
+    fn from(pos: BigPos) -> Self {
+        (pos.num, pos.denom)
+    }
+}
+
+/// Converts a `Pos` into a `BigPos` of the same numerator and denominator, e.g. to migrate a sequence.
+impl From<Pos> for BigPos {
+    #[inline]
+    fn from(pos: Pos) -> Self {
+        BigPos {
+            num: BigUint::from(pos.num()),
+            denom: BigUint::from(pos.denom()),
+        }
+    }
+}
+
+/// Formats `BigPos` as fraction, e.g. `3/2`.
+impl Display for BigPos {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}/{}", self.num, self.denom)
+    }
+}
+
+impl Debug for BigPos {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}/{}", self.num, self.denom)
+    }
+}
+
+impl Default for BigPos {
+    #[inline]
+    fn default() -> Self {
+        BigPos {
+            num: BigUint::from(1u32),
+            denom: BigUint::from(1u32),
+        }
+    }
+}
+
+#[cfg(test)]
+#[path = "tests/big_pos_tests.rs"]
+mod big_pos_tests;
@@ -0,0 +1,532 @@
+//! `BTreeSequence` is a `Sequence` backed by a `BTreeMap` keyed by position.
+//! Operations on positions take O(log n), while operations on indices take O(n).
+
+use std::borrow::Borrow;
+use std::collections::btree_map;
+use std::collections::BTreeMap;
+use std::fmt::{Debug, Formatter};
+use std::ops::RangeBounds;
+
+use crate::error::PositionExhausted;
+use crate::sequence::{is_empty_range, Change, Node, NodeId, Pos, Position, PositionStrategy};
+
+/// A sequence with a deterministic and easy to persist ordering, backed by a `BTreeMap`.
+///
+/// Inserting and removing elements at a position takes O(log n) instead of O(n) for [`Sequence`](`crate::Sequence`).
+/// In turn, inserting, removing and getting elements by index takes O(n), as the map has to be walked up to index.
+/// Prefer `BTreeSequence` if elements are mostly addressed by their positions, e.g. as persisted in a database.
+///
+/// Methods of the same name behave like those of `Sequence` and hand out the same positions and ids:
+/// removed elements leave empty nodes behind, whose positions are not handed out again until they are dropped,
+/// elements have stable ids, and changes are recorded in a journal if enabled.
+///
+/// What you can do
+/// * Create a BTreeSequence: [new()](`BTreeSequence::new()`) or with_strategy()
+/// * Create a BTreeSequence of another position type: with_position()
+/// * Determine if it contains elements: is_empty()
+/// * Determine how many elements it contains: len()
+/// * Get the first element: first()
+/// * Get the last element: last()
+/// * Get a reference to an element at a defined index: get() and get_mut()
+/// * Get the node holding an element at a defined index or position: node() and node_at()
+/// * Get a reference to an element at a defined position: get_at() and get_at_mut()
+/// * Insert an element at a defined index: insert() and try_insert()
+/// * Insert an element at a defined position: insert_at()
+/// * Get an element's index from its position and vice versa: index_from() and position_from()
+/// * Append an element to the sequence: push() and try_push()
+/// * Remove an element at a defined index: remove()
+/// * Remove an element at a defined position: remove_at()
+/// * Address an element by its stable id: id_of(), get_by_id(), position_of() and remove_by_id()
+/// * Iterate over the nodes or the nodes in a range of positions: iter() and range()
+/// * Iterate over all nodes including empty ones: iter_with_tombstones()
+/// * Reassign compact positions to all elements: rebalance()
+/// * Drop empty nodes left behind by removed elements: tombstones(), compact() and compact_before()
+/// * Record changes to persist them incrementally: enable_journal(), disable_journal() and take_changes()
+#[derive(Clone, Debug)]
+pub struct BTreeSequence<T, P = Pos> {
+    map: BTreeMap<P, Node<T, P>>,
+    len: usize,
+    strategy: PositionStrategy,
+    removals: u64,
+    next_id: u64,
+    journal: Option<Vec<Change<P>>>,
+}
+
+impl<T> BTreeSequence<T, Pos> {
+    /// Creates an empty sequence with positions of type `Pos`.
+    /// Use [`with_position()`](`BTreeSequence::with_position()`) for other position types.
+    #[inline]
+    #[must_use]
+    pub fn new() -> Self {
+        Self::with_position()
+    }
+
+    /// Creates an empty sequence with positions of type `Pos`,
+    /// choosing the positions of inserted elements as defined by strategy.
+    #[inline]
+    #[must_use]
+    pub fn with_strategy(strategy: PositionStrategy) -> Self {
+        let mut seq = Self::new();
+        seq.strategy = strategy;
+        seq
+    }
+}
+
+impl<T, P: Position> BTreeSequence<T, P> {
+    /// Creates an empty sequence with positions of type `P`, e.g. `BTreeSequence::<char, OrderKey>::with_position()`.
+    #[inline]
+    #[must_use]
+    pub fn with_position() -> Self {
+        Self {
+            map: BTreeMap::new(),
+            len: 0,
+            strategy: PositionStrategy::default(),
+            removals: 0,
+            next_id: 0,
+            journal: None,
+        }
+    }
+
+    /// Returns the strategy used to choose the positions of inserted elements.
+    #[inline]
+    pub fn strategy(&self) -> PositionStrategy {
+        self.strategy
+    }
+
+    /// Sets the strategy used to choose the positions of inserted elements.
+    /// Positions of elements already in the sequence do not change.
+    #[inline]
+    pub fn set_strategy(&mut self, strategy: PositionStrategy) {
+        self.strategy = strategy;
+    }
+
+    /// Returns true if the sequence contains no elements.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the number of elements in the sequence.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns the first element, or None if the sequence is empty.
+    #[inline]
+    #[must_use]
+    pub fn first(&self) -> Option<&T> {
+        self.iter().next().and_then(Node::element_as_ref)
+    }
+
+    /// Returns the last element, or None if the sequence is empty.
+    #[inline]
+    #[must_use]
+    pub fn last(&self) -> Option<&T> {
+        self.iter().next_back().and_then(Node::element_as_ref)
+    }
+
+    /// Returns the element at index, or None if out of bounds.
+    /// Indices count elements only, i.e. skip empty nodes. Takes O(n).
+    #[inline]
+    #[must_use]
+    pub fn get(&self, index: usize) -> Option<&T> {
+        self.node(index).and_then(Node::element_as_ref)
+    }
+
+    /// Returns the element at index mutably, or None if out of bounds. Takes O(n).
+    #[inline]
+    #[must_use]
+    pub fn get_mut(&mut self, index: usize) -> Option<&mut T> {
+        self.map.values_mut().filter_map(Node::element_as_mut).nth(index)
+    }
+
+    /// Returns the node holding the element at index, or None if out of bounds. Takes O(n).
+    #[inline]
+    #[must_use]
+    pub fn node(&self, index: usize) -> Option<&Node<T, P>> {
+        self.iter().nth(index)
+    }
+
+    /// Returns the node holding the element at position, or None if there is none. Takes O(log n).
+    #[inline]
+    #[must_use]
+    pub fn node_at<Q: Borrow<P>>(&self, position: Q) -> Option<&Node<T, P>> {
+        self.map.get(position.borrow()).filter(|node| node.is_some())
+    }
+
+    /// Returns the element at position, or None if there is none. Takes O(log n).
+    #[inline]
+    #[must_use]
+    pub fn get_at<Q: Borrow<P>>(&self, position: Q) -> Option<&T> {
+        self.node_at(position).and_then(Node::element_as_ref)
+    }
+
+    /// Returns the element at position mutably, or None if there is none. Takes O(log n).
+    #[inline]
+    #[must_use]
+    pub fn get_at_mut<Q: Borrow<P>>(&mut self, position: Q) -> Option<&mut T> {
+        self.map.get_mut(position.borrow()).and_then(Node::element_as_mut)
+    }
+
+    /// Returns the index of the element at position, or None if there is none. Takes O(n).
+    pub fn index_from<Q: Borrow<P>>(&self, position: Q) -> Option<usize> {
+        let position = position.borrow();
+
+        self.node_at(position)?;
+        Some(self.range(..position).count())
+    }
+
+    /// Returns the position of the element at index, or None if out of bounds. Takes O(n).
+    #[inline]
+    pub fn position_from(&self, index: usize) -> Option<P> {
+        self.node(index).map(|node| node.position().clone())
+    }
+
+    /// Inserts an element at index, shifting all elements after it to the right.
+    /// Indices count elements only, i.e. skip empty nodes. Appends the element if index >= len.
+    ///
+    /// # Panics
+    /// Panics if there is no position left between the element's neighbours,
+    /// see [`try_insert()`](`BTreeSequence::try_insert()`) for a non-panicking alternative.
+    pub fn insert(&mut self, index: usize, element: T) {
+        if let Err(err) = self.try_insert(index, element) {
+            panic!("{}", err);
+        }
+    }
+
+    /// Inserts an element at index like [`insert()`](`BTreeSequence::insert()`) and returns the element's position.
+    ///
+    /// # Errors
+    /// Returns `PositionExhausted` if there is no position left between the element's neighbours.
+    /// The sequence is left unchanged and the element is dropped.
+    pub fn try_insert(&mut self, index: usize, element: T) -> Result<P, PositionExhausted<P>> {
+        let Some(upper) = self.node(index).map(Node::position) else {
+            return self.try_push(element);
+        };
+
+        // The node before may be empty, its position is not handed out again.
+        let lower = self.map.range::<P, _>(..upper).next_back().map(|(position, _)| position);
+        let pos = P::between(lower, Some(upper), self.strategy)
+            .ok_or_else(|| PositionExhausted::new(lower.cloned(), Some(upper.clone())))?;
+        self.insert_node(pos.clone(), element);
+
+        Ok(pos)
+    }
+
+    /// Inserts an element at position. If there is an element at the position, it is overwritten and keeps its id.
+    /// If not, element is inserted with a new id, also if an empty node is left at the position. Takes O(log n).
+    pub fn insert_at(&mut self, position: P, element: T) {
+        let Some(node) = self.map.get(&position) else {
+            self.insert_node(position, element);
+            return;
+        };
+
+        // Record the node's position, which may differ from an equal position, e.g. `2/4` for `1/2`.
+        let pos = node.position().clone();
+        let id = if node.is_none() {
+            self.len += 1;
+            self.record(Change::Inserted { pos: pos.clone() });
+            Some(self.generate_id())
+        } else {
+            self.record(Change::Updated { pos: pos.clone() });
+            None
+        };
+
+        if let Some(node) = self.map.get_mut(&pos) {
+            node.id = id.unwrap_or(node.id);
+            node.set(element);
+        }
+    }
+
+    /// Appends an element to the back of the sequence.
+    ///
+    /// # Panics
+    /// Panics if there is no position left after the last position,
+    /// see [`try_push()`](`BTreeSequence::try_push()`) for a non-panicking alternative.
+    #[inline]
+    pub fn push(&mut self, element: T) {
+        if let Err(err) = self.try_push(element) {
+            panic!("{}", err);
+        }
+    }
+
+    /// Appends an element to the back of the sequence and returns the element's position.
+    /// The position is after the last node, which may be empty.
+    ///
+    /// # Errors
+    /// Returns `PositionExhausted` if there is no position left after the last position.
+    /// The sequence is left unchanged and the element is dropped.
+    pub fn try_push(&mut self, element: T) -> Result<P, PositionExhausted<P>> {
+        let last = self.map.last_key_value().map(|(position, _)| position);
+        let pos = P::between(last, None, self.strategy).ok_or_else(|| PositionExhausted::new(last.cloned(), None))?;
+        self.insert_node(pos.clone(), element);
+
+        Ok(pos)
+    }
+
+    /// Removes and returns the element at index, or None if out of bounds. Takes O(n).
+    /// Like [`Sequence::remove()`](`crate::Sequence::remove()`), an empty node is left at the element's position.
+    pub fn remove(&mut self, index: usize) -> Option<T> {
+        let position = self.position_from(index)?;
+        self.remove_at(position)
+    }
+
+    /// Removes and returns the element at position, or None if there is none, see [`remove()`](`BTreeSequence::remove()`).
+    /// Takes O(log n).
+    pub fn remove_at<Q: Borrow<P>>(&mut self, position: Q) -> Option<T> {
+        let node = self.map.get_mut(position.borrow()).filter(|node| node.is_some())?;
+        let pos = node.position().clone();
+        let element = node.take(self.removals);
+
+        self.len -= 1;
+        self.removals += 1;
+        self.record(Change::Removed { pos });
+        element
+    }
+
+    /// Returns the id of the element at position, or None if there is none.
+    #[inline]
+    pub fn id_of<Q: Borrow<P>>(&self, position: Q) -> Option<NodeId> {
+        self.node_at(position).map(Node::id)
+    }
+
+    /// Returns the element with id, or None if there is none. Takes O(n).
+    #[inline]
+    pub fn get_by_id(&self, id: NodeId) -> Option<&T> {
+        self.iter().find(|node| node.id() == id).and_then(Node::element_as_ref)
+    }
+
+    /// Returns the position of the element with id, or None if there is none. Takes O(n).
+    #[inline]
+    pub fn position_of(&self, id: NodeId) -> Option<P> {
+        self.iter().find(|node| node.id() == id).map(|node| node.position().clone())
+    }
+
+    /// Removes and returns the element with id, or None if there is none, see [`remove()`](`BTreeSequence::remove()`).
+    /// Takes O(n).
+    pub fn remove_by_id(&mut self, id: NodeId) -> Option<T> {
+        let position = self.position_of(id)?;
+        self.remove_at(position)
+    }
+
+    /// Returns an iterator over the nodes in order. Empty nodes are skipped,
+    /// see [`iter_with_tombstones()`](`BTreeSequence::iter_with_tombstones()`).
+    #[inline]
+    pub fn iter(&self) -> BTreeSequenceIter<'_, T, P> {
+        BTreeSequenceIter(self.map.range::<P, _>(..))
+    }
+
+    /// Returns an iterator over all nodes in order, including the empty nodes left behind by removed elements.
+    #[inline]
+    pub fn iter_with_tombstones(&self) -> btree_map::Values<'_, P, Node<T, P>> {
+        self.map.values()
+    }
+
+    /// Returns an iterator over the nodes in range of positions, in order.
+    /// Takes O(log n) to find the range's start.
+    /// Returns an empty iterator if the range's start is after its end.
+    pub fn range<R: RangeBounds<P>>(&self, range: R) -> BTreeSequenceIter<'_, T, P> {
+        if is_empty_range(&range) {
+            BTreeSequenceIter(btree_map::Range::default())
+        } else {
+            BTreeSequenceIter(self.map.range(range))
+        }
+    }
+
+    /// Reassigns compact positions to all elements and drops all empty nodes, e.g. `1/1, 2/1, 3/1, ...` for `Pos`.
+    /// The order of elements does not change.
+    ///
+    /// Returns the list of changed positions as `(old, new)` pairs, see [`Sequence::rebalance()`](`crate::Sequence::rebalance()`).
+    pub fn rebalance(&mut self) -> Vec<(P, P)> {
+        let positions = P::spread(None, None, self.len).expect("positions are unbounded");
+        let map = std::mem::take(&mut self.map);
+
+        let mut changes = Vec::new();
+        for (mut node, pos) in map.into_values().filter(|node| node.is_some()).zip(positions) {
+            if node.position != pos {
+                let old = std::mem::replace(&mut node.position, pos.clone());
+                changes.push((old, pos.clone()));
+            }
+            self.map.insert(pos, node);
+        }
+        self.record_moves(&changes);
+
+        changes
+    }
+
+    /// Enables the journal, see [`Sequence::enable_journal()`](`crate::Sequence::enable_journal()`).
+    #[inline]
+    pub fn enable_journal(&mut self) {
+        self.journal.get_or_insert_with(Vec::new);
+    }
+
+    /// Stops recording changes and drops the changes recorded so far.
+    #[inline]
+    pub fn disable_journal(&mut self) {
+        self.journal = None;
+    }
+
+    /// Returns true if changes are recorded, see [`enable_journal()`](`BTreeSequence::enable_journal()`).
+    #[inline]
+    pub fn is_journal_enabled(&self) -> bool {
+        self.journal.is_some()
+    }
+
+    /// Returns the changes recorded since the journal was enabled or changes were last taken, in order,
+    /// see [`Sequence::take_changes()`](`crate::Sequence::take_changes()`).
+    #[inline]
+    pub fn take_changes(&mut self) -> Vec<Change<P>> {
+        self.journal.as_mut().map(std::mem::take).unwrap_or_default()
+    }
+
+    /// Returns the number of empty nodes left behind by removed elements.
+    #[inline]
+    pub fn tombstones(&self) -> usize {
+        self.map.len() - self.len
+    }
+
+    /// Returns a watermark to pass to [`compact_before()`](`BTreeSequence::compact_before()`) later on,
+    /// see [`Sequence::watermark()`](`crate::Sequence::watermark()`).
+    #[inline]
+    pub fn watermark(&self) -> u64 {
+        self.removals
+    }
+
+    /// Drops all empty nodes. Positions of the remaining elements do not change.
+    ///
+    /// Returns the number of dropped nodes.
+    pub fn compact(&mut self) -> usize {
+        let tombstones = self.tombstones();
+        self.map.retain(|_, node| node.is_some());
+
+        tombstones
+    }
+
+    /// Drops the empty nodes left behind by removals before watermark was taken, see [`watermark()`](`BTreeSequence::watermark()`).
+    /// Positions of the remaining elements do not change.
+    ///
+    /// Returns the number of dropped nodes.
+    pub fn compact_before(&mut self, watermark: u64) -> usize {
+        let tombstones = self.tombstones();
+        self.map
+            .retain(|_, node| node.is_some() || node.removed.is_some_and(|removed| removed >= watermark));
+
+        tombstones - self.tombstones()
+    }
+
+    /// Inserts element into a new node at position, which must not be taken by a node.
+    fn insert_node(&mut self, position: P, element: T) {
+        let id = self.generate_id();
+        self.map.insert(position.clone(), Node::new(id, position.clone(), element));
+        self.len += 1;
+        self.record(Change::Inserted { pos: position });
+    }
+
+    /// Returns a new id, which has not been handed out by this sequence before.
+    #[inline]
+    fn generate_id(&mut self) -> NodeId {
+        self.next_id += 1;
+        NodeId::from(self.next_id - 1)
+    }
+
+    /// Records change, if the journal is enabled.
+    #[inline]
+    fn record(&mut self, change: Change<P>) {
+        if let Some(journal) = self.journal.as_mut() {
+            journal.push(change);
+        }
+    }
+
+    /// Records `(old, new)` pairs of positions as moves, if the journal is enabled.
+    fn record_moves(&mut self, changes: &[(P, P)]) {
+        for (from, to) in changes {
+            self.record(Change::Moved {
+                from: from.clone(),
+                to: to.clone(),
+            });
+        }
+    }
+}
+
+impl<T, P: Position> Default for BTreeSequence<T, P> {
+    fn default() -> Self {
+        Self::with_position()
+    }
+}
+
+// The journal is not part of a sequence's value.
+impl<T: PartialEq, P: PartialEq> PartialEq for BTreeSequence<T, P> {
+    fn eq(&self, other: &Self) -> bool {
+        self.map == other.map
+            && self.len == other.len
+            && self.strategy == other.strategy
+            && self.removals == other.removals
+            && self.next_id == other.next_id
+    }
+}
+
+//
+// Iterator
+//
+
+/// Iterator over the nodes of a [`BTreeSequence`] in order, skipping empty nodes,
+/// see [`iter()`](`BTreeSequence::iter()`) and [`range()`](`BTreeSequence::range()`).
+pub struct BTreeSequenceIter<'iterator, T: 'iterator, P: 'iterator = Pos>(btree_map::Range<'iterator, P, Node<T, P>>);
+
+impl<'iterator, T: 'iterator, P: 'iterator> Iterator for BTreeSequenceIter<'iterator, T, P> {
+    type Item = &'iterator Node<T, P>;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.by_ref().map(|(_, node)| node).find(|node| node.is_some())
+    }
+}
+
+impl<'iterator, T: 'iterator, P: 'iterator> DoubleEndedIterator for BTreeSequenceIter<'iterator, T, P> {
+    #[inline]
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.0.by_ref().map(|(_, node)| node).rfind(|node| node.is_some())
+    }
+}
+
+impl<'iterator, T: 'iterator, P: 'iterator> Clone for BTreeSequenceIter<'iterator, T, P> {
+    fn clone(&self) -> Self {
+        BTreeSequenceIter(self.0.clone())
+    }
+}
+
+// Formats the remaining nodes.
+impl<'iterator, T: Debug + 'iterator, P: Debug + 'iterator> Debug for BTreeSequenceIter<'iterator, T, P> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_list().entries(self.clone()).finish()
+    }
+}
+
+impl<'iterator, T: 'iterator, P: Position + 'iterator> IntoIterator for &'iterator BTreeSequence<T, P> {
+    type Item = &'iterator Node<T, P>;
+    type IntoIter = BTreeSequenceIter<'iterator, T, P>;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+// IntoIterator for BTreeSequence<T, P>
+// - consumes the `BTreeSequence`
+// - removes all empty nodes
+// - iterates over the remaining nodes in order
+impl<T, P> IntoIterator for BTreeSequence<T, P> {
+    type Item = Node<T, P>;
+    type IntoIter = std::vec::IntoIter<Node<T, P>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        let nodes: Vec<Node<T, P>> = self.map.into_values().filter(|node| node.is_some()).collect();
+        nodes.into_iter()
+    }
+}
+
+#[cfg(test)]
+#[path = "tests/btree_sequence_tests.rs"]
+mod btree_sequence_tests;
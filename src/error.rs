@@ -0,0 +1,182 @@
+//! Errors returned by fallible operations on positions and `Sequence`.
+
+use std::error::Error;
+use std::fmt::{Debug, Display, Formatter};
+
+use crate::sequence::Pos;
+
+/// Error returned when constructing a `Pos` from an invalid numerator / denominator pair.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum PosError {
+    /// The denominator of a `Pos` must be >= 1.
+    ZeroDenominator,
+    /// The bytes are not a sort key created by `Pos::sort_key()`, or its fraction does not fit into `u64`.
+    InvalidSortKey,
+}
+
+impl Display for PosError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PosError::ZeroDenominator => write!(f, "denominator of a position must not be zero"),
+            PosError::InvalidSortKey => write!(f, "invalid sort key of a position"),
+        }
+    }
+}
+
+impl Error for PosError {}
+
+/// Error returned when there is no position left between two neighbouring positions.
+///
+/// Positions are fractions of `u64`, so repeatedly inserting at the same spot of a `Sequence`
+/// eventually exhausts the available positions. Rebalancing the sequence makes room for further inserts.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct PositionExhausted<P = Pos> {
+    lower: Option<P>,
+    upper: Option<P>,
+}
+
+impl<P> PositionExhausted<P> {
+    #[inline]
+    #[must_use]
+    pub(crate) fn new(lower: Option<P>, upper: Option<P>) -> Self {
+        PositionExhausted { lower, upper }
+    }
+
+    /// Returns the position before the requested one, or None if requested at the front.
+    #[inline]
+    #[must_use]
+    pub fn lower(&self) -> Option<&P> {
+        self.lower.as_ref()
+    }
+
+    /// Returns the position after the requested one, or None if requested at the back.
+    #[inline]
+    #[must_use]
+    pub fn upper(&self) -> Option<&P> {
+        self.upper.as_ref()
+    }
+}
+
+impl<P: Display> Display for PositionExhausted<P> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match (&self.lower, &self.upper) {
+            (Some(lower), Some(upper)) => write!(f, "no position left between {} and {}", lower, upper),
+            (Some(lower), None) => write!(f, "no position left after {}", lower),
+            (None, Some(upper)) => write!(f, "no position left before {}", upper),
+            (None, None) => write!(f, "no position left"),
+        }
+    }
+}
+
+impl<P: Debug + Display> Error for PositionExhausted<P> {}
+
+/// Error returned when constructing an `OrderKey` from an invalid string.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum OrderKeyError {
+    /// Keys consist of the digits `0-9`, `A-Z` and `a-z` only, starting with a letter.
+    InvalidChar(char),
+    /// The key is shorter than the length its first letter announces for the integer part.
+    InvalidLength,
+    /// The fractional part of a key must not end with `0`, otherwise there is no key in between it and its prefix.
+    TrailingZero,
+    /// The smallest integer part is reserved to always have a key before any other key.
+    Reserved,
+}
+
+impl Display for OrderKeyError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            OrderKeyError::InvalidChar(c) => write!(f, "invalid character {:?} in order key", c),
+            OrderKeyError::InvalidLength => write!(f, "order key is too short for its integer part"),
+            OrderKeyError::TrailingZero => write!(f, "order key must not end with '0' after its integer part"),
+            OrderKeyError::Reserved => write!(f, "order key is reserved"),
+        }
+    }
+}
+
+impl Error for OrderKeyError {}
+
+/// Error returned when loading a `Sequence` from or saving it to SQLite.
+#[cfg(feature = "sqlite")]
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum SqliteError {
+    /// SQLite failed to execute a statement, or an element could not be decoded.
+    Sqlite(rusqlite::Error),
+    /// A row holds an invalid position, e.g. a zero denominator.
+    InvalidPos(PosError),
+}
+
+#[cfg(feature = "sqlite")]
+impl Display for SqliteError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SqliteError::Sqlite(error) => write!(f, "sqlite: {}", error),
+            SqliteError::InvalidPos(error) => write!(f, "invalid position in table: {}", error),
+        }
+    }
+}
+
+#[cfg(feature = "sqlite")]
+impl Error for SqliteError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            SqliteError::Sqlite(error) => Some(error),
+            SqliteError::InvalidPos(error) => Some(error),
+        }
+    }
+}
+
+#[cfg(feature = "sqlite")]
+impl From<rusqlite::Error> for SqliteError {
+    fn from(error: rusqlite::Error) -> Self {
+        SqliteError::Sqlite(error)
+    }
+}
+
+#[cfg(feature = "sqlite")]
+impl From<PosError> for SqliteError {
+    fn from(error: PosError) -> Self {
+        SqliteError::InvalidPos(error)
+    }
+}
+
+/// Error returned when loading a `Sequence` from or saving it to PostgreSQL.
+#[cfg(feature = "postgres")]
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum PostgresError {
+    /// PostgreSQL failed to execute a statement.
+    Postgres(::postgres::Error),
+    /// A row holds an invalid position, e.g. a negative numerator, or an element which could not be decoded.
+    Decode(Box<dyn Error + Send + Sync>),
+}
+
+#[cfg(feature = "postgres")]
+impl Display for PostgresError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PostgresError::Postgres(error) => write!(f, "postgres: {}", error),
+            PostgresError::Decode(error) => write!(f, "failed to decode row: {}", error),
+        }
+    }
+}
+
+#[cfg(feature = "postgres")]
+impl Error for PostgresError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            PostgresError::Postgres(error) => Some(error),
+            PostgresError::Decode(error) => Some(error.as_ref()),
+        }
+    }
+}
+
+#[cfg(feature = "postgres")]
+impl From<::postgres::Error> for PostgresError {
+    fn from(error: ::postgres::Error) -> Self {
+        PostgresError::Postgres(error)
+    }
+}
@@ -24,13 +24,71 @@ Collection of useful rustdoc options awaiting their implementation.
 
 // Keep crate's module structure completely private, see public re-exports below.
 // (also hides modules from crate documentation)
+#[cfg(feature = "bigint")]
+mod big_pos;
+mod btree_sequence;
+mod error;
+mod order_key;
+#[cfg(feature = "postgres")]
+mod postgres;
 mod sequence;
+#[cfg(test)]
+#[path = "tests/shared_sequence_tests.rs"]
+mod shared_sequence_tests;
+#[cfg(feature = "sqlite")]
+mod sqlite;
+mod store;
+#[cfg(test)]
+#[path = "tests/util.rs"]
+mod test_util;
+mod tree_sequence;
 
 // Re-exports for convenient use within crate.
 // none
 
 // Publicly re-exporting all items valuable to users.
 // (avoids explicitly listing re-exports in crate documentation as there is no alternate path to those items)
+#[cfg(feature = "bigint")]
+pub use big_pos::BigPos;
+pub use btree_sequence::BTreeSequence;
+pub use btree_sequence::BTreeSequenceIter;
+pub use error::OrderKeyError;
+pub use error::PosError;
+pub use error::PositionExhausted;
+#[cfg(feature = "postgres")]
+pub use error::PostgresError;
+#[cfg(feature = "sqlite")]
+pub use error::SqliteError;
+pub use order_key::OrderKey;
+#[cfg(feature = "postgres")]
+pub use postgres::PostgresCodec;
+#[cfg(feature = "postgres")]
+pub use postgres::PostgresStore;
+pub use sequence::Change;
+pub use sequence::Entry;
+pub use sequence::EnumeratePositions;
+pub use sequence::Iter;
+pub use sequence::IterMut;
 pub use sequence::Node;
+pub use sequence::NodeId;
+pub use sequence::OccupiedEntry;
+pub use sequence::Placement;
 pub use sequence::Pos;
+pub use sequence::Position;
+pub use sequence::PositionStrategy;
+pub use sequence::Positions;
+pub use sequence::Range;
 pub use sequence::Sequence;
+pub use sequence::TombstonedEntry;
+pub use sequence::VacantEntry;
+pub use sequence::Values;
+#[cfg(feature = "sqlite")]
+pub use sqlite::SqliteCodec;
+#[cfg(feature = "sqlite")]
+pub use sqlite::SqliteStore;
+pub use store::FileCodec;
+pub use store::FileStore;
+pub use store::MemoryStore;
+pub use store::SequenceStore;
+pub use tree_sequence::TreeSequence;
+pub use tree_sequence::TreeSequenceIter;
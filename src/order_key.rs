@@ -0,0 +1,321 @@
+//! `OrderKey` is a position made of base 62 digits, which sorts like a plain string.
+//! The algorithm follows fractional indexing as described in
+//! <https://observablehq.com/@dgreensp/implementing-fractional-indexing>.
+
+use std::fmt::{Display, Formatter};
+
+use crate::error::OrderKeyError;
+use crate::sequence::{Position, PositionStrategy};
+#[cfg(feature = "serde-derive")]
+use serde::{Deserialize, Serialize};
+
+/// Digits in ascending order of their ASCII values, so that keys compare like their digits.
+const DIGITS: &[u8; 62] = b"0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz";
+const ZERO: u8 = b'0';
+const LAST: u8 = b'z';
+
+/// Key of the first element of an empty sequence.
+const INTEGER_ZERO: &str = "a0";
+
+/// Smallest integer part, reserved so that there is always a key before any other key.
+const INTEGER_MIN: &str = "A00000000000000000000000000";
+
+/// `OrderKey` defines the ordering of `Node`s in a `Sequence` by a string, also known as fractional indexing.
+///
+/// A key consists of an integer part, whose first letter encodes its length, and an optional fractional part.
+/// Appending and prepending elements increments the integer part, which keeps keys short in the common case.
+/// Keys compare byte by byte, so they sort correctly by a plain `ORDER BY key` in any database.
+///
+/// ```
+/// use kodiak_sets::OrderKey;
+///
+/// let first = OrderKey::default();
+/// let second = first.after();
+/// let key = OrderKey::between(&first, &second).unwrap();
+///
+/// assert_eq!(first.as_str(), "a0");
+/// assert_eq!(second.as_str(), "a1");
+/// assert_eq!(key.as_str(), "a0V");
+/// assert!(first < key && key < second);
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(
+    feature = "serde-derive",
+    derive(Serialize, Deserialize),
+    serde(try_from = "String", into = "String")
+)]
+pub struct OrderKey(String);
+
+impl OrderKey {
+    /// Creates an `OrderKey` from a string, e.g. read from a database.
+    ///
+    /// # Errors
+    /// Returns `OrderKeyError` if key is not a valid key, see [`OrderKeyError`](`crate::OrderKeyError`).
+    pub fn try_new(key: &str) -> Result<Self, OrderKeyError> {
+        Self::validate(key)?;
+        Ok(OrderKey(key.to_string()))
+    }
+
+    /// Returns the key as string slice.
+    #[inline]
+    #[must_use]
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// Returns a key in between first and second, in any order, or None if both are equal.
+    #[must_use]
+    pub fn between(first: &Self, second: &Self) -> Option<Self> {
+        match first.cmp(second) {
+            std::cmp::Ordering::Less => Self::generate(Some(first), Some(second)),
+            std::cmp::Ordering::Greater => Self::generate(Some(second), Some(first)),
+            std::cmp::Ordering::Equal => None,
+        }
+    }
+
+    /// Returns a key before this key.
+    #[must_use]
+    pub fn before(&self) -> Self {
+        Self::generate(None, Some(self)).expect("there is always a key before any other key")
+    }
+
+    /// Returns a key after this key.
+    #[must_use]
+    pub fn after(&self) -> Self {
+        Self::generate(Some(self), None).expect("there is always a key after any other key")
+    }
+
+    /// Returns a key in between lower and upper, with None meaning unbounded.
+    /// Returns None if lower is not less than upper.
+    fn generate(lower: Option<&Self>, upper: Option<&Self>) -> Option<Self> {
+        let key = match (lower.map(|key| key.0.as_bytes()), upper.map(|key| key.0.as_bytes())) {
+            (None, None) => INTEGER_ZERO.as_bytes().to_vec(),
+            (None, Some(upper)) => {
+                let (integer, fraction) = upper.split_at(integer_len(upper[0]));
+                if integer == INTEGER_MIN.as_bytes() {
+                    [integer, &midpoint(&[], Some(fraction))].concat()
+                } else if !fraction.is_empty() {
+                    integer.to_vec()
+                } else {
+                    let integer = decrement_integer(integer)?;
+                    if integer == INTEGER_MIN.as_bytes() {
+                        // The smallest integer part is reserved, so only keys with a fractional part may use it.
+                        [integer.as_slice(), &midpoint(&[], None)].concat()
+                    } else {
+                        integer
+                    }
+                }
+            }
+            (Some(lower), None) => {
+                let (integer, fraction) = lower.split_at(integer_len(lower[0]));
+                increment_integer(integer).unwrap_or_else(|| [integer, &midpoint(fraction, None)].concat())
+            }
+            (Some(lower), Some(upper)) => {
+                if lower >= upper {
+                    return None;
+                }
+
+                let (integer_lower, fraction_lower) = lower.split_at(integer_len(lower[0]));
+                let (integer_upper, fraction_upper) = upper.split_at(integer_len(upper[0]));
+                if integer_lower == integer_upper {
+                    [integer_lower, &midpoint(fraction_lower, Some(fraction_upper))].concat()
+                } else {
+                    // The integer part of lower is not the largest one, as there is a larger one in upper.
+                    let integer = increment_integer(integer_lower)?;
+                    if integer.as_slice() < upper {
+                        integer
+                    } else {
+                        [integer_lower, &midpoint(fraction_lower, None)].concat()
+                    }
+                }
+            }
+        };
+
+        // Keys only consist of ASCII digits.
+        String::from_utf8(key).ok().map(OrderKey)
+    }
+
+    fn validate(key: &str) -> Result<(), OrderKeyError> {
+        let mut chars = key.chars();
+        match chars.next() {
+            None => return Err(OrderKeyError::InvalidLength),
+            Some(head) if !head.is_ascii_alphabetic() => return Err(OrderKeyError::InvalidChar(head)),
+            Some(_) => {}
+        }
+        if let Some(c) = chars.find(|c| !c.is_ascii_alphanumeric()) {
+            return Err(OrderKeyError::InvalidChar(c));
+        }
+
+        let key = key.as_bytes();
+        let len = integer_len(key[0]);
+        if key.len() < len {
+            Err(OrderKeyError::InvalidLength)
+        } else if key.len() > len && key[key.len() - 1] == ZERO {
+            Err(OrderKeyError::TrailingZero)
+        } else if key == INTEGER_MIN.as_bytes() {
+            Err(OrderKeyError::Reserved)
+        } else {
+            Ok(())
+        }
+    }
+}
+
+impl Position for OrderKey {
+    #[inline]
+    fn between(lower: Option<&Self>, upper: Option<&Self>, _strategy: PositionStrategy) -> Option<Self> {
+        OrderKey::generate(lower, upper)
+    }
+}
+
+impl Default for OrderKey {
+    #[inline]
+    fn default() -> Self {
+        OrderKey(INTEGER_ZERO.to_string())
+    }
+}
+
+impl Display for OrderKey {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl TryFrom<&str> for OrderKey {
+    type Error = OrderKeyError;
+
+    #[inline]
+    fn try_from(key: &str) -> Result<Self, Self::Error> {
+        OrderKey::try_new(key)
+    }
+}
+
+impl TryFrom<String> for OrderKey {
+    type Error = OrderKeyError;
+
+    #[inline]
+    fn try_from(key: String) -> Result<Self, Self::Error> {
+        Self::validate(&key)?;
+        Ok(OrderKey(key))
+    }
+}
+
+impl From<OrderKey> for String {
+    #[inline]
+    fn from(key: OrderKey) -> Self {
+        key.0
+    }
+}
+
+/// Returns the value of digit, i.e. its index in `DIGITS`.
+#[inline]
+fn digit_value(digit: u8) -> usize {
+    match digit {
+        b'0'..=b'9' => (digit - b'0') as usize,
+        b'A'..=b'Z' => (digit - b'A') as usize + 10,
+        _ => (digit - b'a') as usize + 36,
+    }
+}
+
+/// Returns the length of an integer part, including its head, i.e. `a` to `z` for 2 to 27 and `Z` to `A` for 2 to 27.
+#[inline]
+fn integer_len(head: u8) -> usize {
+    match head {
+        b'a'..=b'z' => (head - b'a') as usize + 2,
+        _ => (b'Z' - head) as usize + 2,
+    }
+}
+
+/// Returns the digits in between lower and upper, with lower padded by zeros and None meaning unbounded.
+/// Neither lower nor upper must end with zero, and lower must be less than upper.
+fn midpoint(lower: &[u8], upper: Option<&[u8]>) -> Vec<u8> {
+    if let Some(upper) = upper {
+        // Keep the common prefix, then look for a midpoint in the remaining digits.
+        let common = upper
+            .iter()
+            .enumerate()
+            .take_while(|(i, &digit)| lower.get(*i).copied().unwrap_or(ZERO) == digit)
+            .count();
+        if common > 0 {
+            let lower = lower.get(common..).unwrap_or_default();
+            return [&upper[..common], &midpoint(lower, Some(&upper[common..]))].concat();
+        }
+    }
+
+    let digit_lower = lower.first().map_or(0, |&digit| digit_value(digit));
+    let digit_upper = upper.map_or(DIGITS.len(), |upper| digit_value(upper[0]));
+    if digit_upper - digit_lower > 1 {
+        vec![DIGITS[(digit_lower + digit_upper).div_ceil(2)]]
+    } else if let Some(upper) = upper.filter(|upper| upper.len() > 1) {
+        // Upper's first digit is followed by non-zero digits, so it is in between on its own.
+        vec![upper[0]]
+    } else {
+        // Digits are adjacent, so keep lower's digit and look for a midpoint after it.
+        let mut digits = vec![DIGITS[digit_lower]];
+        digits.extend(midpoint(lower.get(1..).unwrap_or_default(), None));
+        digits
+    }
+}
+
+/// Returns the next integer part, or None if integer is the largest one.
+fn increment_integer(integer: &[u8]) -> Option<Vec<u8>> {
+    let (head, digits) = integer.split_first()?;
+    let mut digits = digits.to_vec();
+
+    for digit in digits.iter_mut().rev() {
+        if *digit == LAST {
+            *digit = ZERO;
+        } else {
+            *digit = DIGITS[digit_value(*digit) + 1];
+            return Some([&[*head][..], &digits].concat());
+        }
+    }
+
+    // All digits overflowed, so the integer part grows by one digit, or shrinks when changing from negative to positive.
+    match *head {
+        b'Z' => Some(INTEGER_ZERO.as_bytes().to_vec()),
+        b'z' => None,
+        head => {
+            let head = head + 1;
+            if head > b'a' {
+                digits.push(ZERO);
+            } else {
+                digits.pop();
+            }
+            Some([&[head][..], &digits].concat())
+        }
+    }
+}
+
+/// Returns the previous integer part, or None if integer is the smallest one.
+fn decrement_integer(integer: &[u8]) -> Option<Vec<u8>> {
+    let (head, digits) = integer.split_first()?;
+    let mut digits = digits.to_vec();
+
+    for digit in digits.iter_mut().rev() {
+        if *digit == ZERO {
+            *digit = LAST;
+        } else {
+            *digit = DIGITS[digit_value(*digit) - 1];
+            return Some([&[*head][..], &digits].concat());
+        }
+    }
+
+    // All digits underflowed, so the integer part grows by one digit, or shrinks when changing from positive to negative.
+    match *head {
+        b'a' => Some(vec![b'Z', LAST]),
+        b'A' => None,
+        head => {
+            let head = head - 1;
+            if head < b'Z' {
+                digits.push(LAST);
+            } else {
+                digits.pop();
+            }
+            Some([&[head][..], &digits].concat())
+        }
+    }
+}
+
+#[cfg(test)]
+#[path = "tests/order_key_tests.rs"]
+mod order_key_tests;
@@ -0,0 +1,344 @@
+//! `PostgresStore` persists a `Sequence` in a PostgreSQL table, available with feature `postgres`.
+//! Positions are stored as numerator and denominator, elements are stored by an implementation of `PostgresCodec`.
+
+use std::error::Error;
+use std::fmt::{Debug, Formatter};
+use std::ops::{Bound, RangeBounds};
+
+use ::postgres::types::{FromSql, ToSql};
+use ::postgres::Client;
+
+use crate::error::PostgresError;
+use crate::sequence::Pos;
+use crate::store::{row_keys, SequenceStore};
+
+/// Encodes elements of a `Sequence` to values of the payload column and decodes them back.
+///
+/// Implemented for `String` (`TEXT`), `Vec<u8>` (`BYTEA`), `i64` (`BIGINT`), `f64` (`DOUBLE PRECISION`)
+/// and `bool` (`BOOLEAN`). Implement it for your own types, e.g. by encoding them as `JSONB` or `TEXT`.
+pub trait PostgresCodec: Sized {
+    /// SQL type of the payload column, used by [`create_table()`](`PostgresStore::create_table()`).
+    const SQL_TYPE: &'static str;
+
+    /// Rust type of the values in the payload column.
+    type Column: ToSql + Sync + for<'a> FromSql<'a>;
+
+    /// Encodes the element to the value stored in the payload column.
+    fn encode(&self) -> Self::Column;
+
+    /// Decodes an element from a value read from the payload column.
+    fn decode(column: Self::Column) -> Result<Self, Box<dyn Error + Send + Sync>>;
+}
+
+impl PostgresCodec for String {
+    const SQL_TYPE: &'static str = "TEXT";
+    type Column = String;
+
+    fn encode(&self) -> Self::Column {
+        self.clone()
+    }
+
+    fn decode(column: Self::Column) -> Result<Self, Box<dyn Error + Send + Sync>> {
+        Ok(column)
+    }
+}
+
+impl PostgresCodec for Vec<u8> {
+    const SQL_TYPE: &'static str = "BYTEA";
+    type Column = Vec<u8>;
+
+    fn encode(&self) -> Self::Column {
+        self.clone()
+    }
+
+    fn decode(column: Self::Column) -> Result<Self, Box<dyn Error + Send + Sync>> {
+        Ok(column)
+    }
+}
+
+impl PostgresCodec for i64 {
+    const SQL_TYPE: &'static str = "BIGINT";
+    type Column = i64;
+
+    fn encode(&self) -> Self::Column {
+        *self
+    }
+
+    fn decode(column: Self::Column) -> Result<Self, Box<dyn Error + Send + Sync>> {
+        Ok(column)
+    }
+}
+
+impl PostgresCodec for f64 {
+    const SQL_TYPE: &'static str = "DOUBLE PRECISION";
+    type Column = f64;
+
+    fn encode(&self) -> Self::Column {
+        *self
+    }
+
+    fn decode(column: Self::Column) -> Result<Self, Box<dyn Error + Send + Sync>> {
+        Ok(column)
+    }
+}
+
+impl PostgresCodec for bool {
+    const SQL_TYPE: &'static str = "BOOLEAN";
+    type Column = bool;
+
+    fn encode(&self) -> Self::Column {
+        *self
+    }
+
+    fn decode(column: Self::Column) -> Result<Self, Box<dyn Error + Send + Sync>> {
+        Ok(column)
+    }
+}
+
+/// A `SequenceStore` keeping the elements of a `Sequence` in a PostgreSQL table with the columns `numerator`,
+/// `denominator` and `payload`.
+///
+/// Numerators and denominators are stored as `NUMERIC(20, 0)`, which holds any `u64`. Rows are ordered exactly by
+/// [`ORDER_BY`](`PostgresStore::ORDER_BY`), unlike ordering by a floating point quotient, which can't tell
+/// close fractions apart. Likewise, [`load_range()`](`SequenceStore::load_range()`) compares fractions exactly in SQL.
+///
+/// Positions are written in lowest terms, see [`Pos::reduced()`], so that equal positions like `1/2` and `2/4`
+/// map to the same row.
+///
+/// Batches are transactions. A failing upsert or delete rolls back the current batch, as PostgreSQL does not execute
+/// any further statement of a failed transaction, and the batch is repeated as a whole anyway,
+/// see [`Sequence::sync_to()`](`crate::Sequence::sync_to()`).
+///
+/// What you can do
+/// * Create a PostgresStore for a table of a client: [new()](`PostgresStore::new()`)
+/// * Create the table and an index on the exact ordering if they do not exist: create_table()
+/// * Load a sequence from the table: [`Sequence::load_from()`](`crate::Sequence::load_from()`)
+/// * Replace the table's content with a sequence: [`Sequence::save_to()`](`crate::Sequence::save_to()`)
+/// * Write the changes recorded by a sequence's journal to the table: [`Sequence::sync_to()`](`crate::Sequence::sync_to()`)
+/// * Get the client back: client() and into_client()
+/// * Order rows of the table in your own queries: ORDER_BY
+pub struct PostgresStore {
+    client: Client,
+    table: String,
+    batch: bool,
+}
+
+impl PostgresStore {
+    /// Expression ordering the rows of the table exactly by position, e.g. `SELECT payload FROM sequence ORDER BY ...`.
+    ///
+    /// Two distinct fractions of `u64` differ by at least 1 / (2^64 * 2^64), which is more than 10^-40.
+    /// So the integer part of a position multiplied by 10^40 differs for any two distinct positions and is equal
+    /// for equal positions. `div()` computes it in exact `NUMERIC` arithmetic, which PostgreSQL is able to index.
+    pub const ORDER_BY: &'static str = "div(numerator * 1e40, denominator), numerator, denominator";
+
+    /// Creates a store for the given table of the client. The name is quoted, so it may contain any character.
+    #[inline]
+    #[must_use]
+    pub fn new(client: Client, table: &str) -> Self {
+        PostgresStore {
+            client,
+            table: table.to_string(),
+            batch: false,
+        }
+    }
+
+    /// Returns the name of the table.
+    #[inline]
+    #[must_use]
+    pub fn table(&self) -> &str {
+        &self.table
+    }
+
+    /// Returns the client, e.g. to run your own queries on the table.
+    /// Statements executed while a batch is open are part of the batch.
+    #[inline]
+    #[must_use]
+    pub fn client(&mut self) -> &mut Client {
+        &mut self.client
+    }
+
+    /// Consumes the store and returns the client.
+    #[inline]
+    #[must_use]
+    pub fn into_client(self) -> Client {
+        self.client
+    }
+
+    /// Creates the table with a payload column of type `T::SQL_TYPE` and an index on the exact ordering,
+    /// unless they exist.
+    pub fn create_table<T: PostgresCodec>(&mut self) -> Result<(), PostgresError> {
+        self.client.batch_execute(&create_table_sql::<T>(&self.table))?;
+
+        Ok(())
+    }
+
+    /// Executes a statement, rolling back the current batch if it fails.
+    fn execute(&mut self, sql: &str, params: &[&(dyn ToSql + Sync)]) -> Result<(), PostgresError> {
+        match self.client.execute(sql, params) {
+            Ok(_) => Ok(()),
+            Err(error) => {
+                if std::mem::take(&mut self.batch) {
+                    // The statement's error is more helpful than one of the rollback.
+                    let _ = self.client.batch_execute("ROLLBACK");
+                }
+                Err(error.into())
+            }
+        }
+    }
+
+    fn delete_rows(&mut self, position: &Pos) -> Result<(), PostgresError> {
+        let sql = format!(
+            "DELETE FROM {} WHERE numerator = $1::TEXT::NUMERIC AND denominator = $2::TEXT::NUMERIC",
+            self.quoted_table()
+        );
+        for (num, denom) in row_keys(position) {
+            self.execute(&sql, &[&num.to_string(), &denom.to_string()])?;
+        }
+
+        Ok(())
+    }
+
+    fn load_where<T: PostgresCodec>(
+        &mut self,
+        condition: &str,
+        params: &[&(dyn ToSql + Sync)],
+    ) -> Result<Vec<(Pos, T)>, PostgresError> {
+        let rows = self.client.query(
+            &format!(
+                "SELECT numerator::TEXT, denominator::TEXT, payload FROM {} WHERE {}",
+                self.quoted_table(),
+                condition
+            ),
+            params,
+        )?;
+
+        let mut elements = Vec::with_capacity(rows.len());
+        for row in rows {
+            let num = parse_u64(row.try_get(0)?)?;
+            let denom = parse_u64(row.try_get(1)?)?;
+            let element = T::decode(row.try_get(2)?).map_err(PostgresError::Decode)?;
+            let position = Pos::try_new(num, denom).map_err(|error| PostgresError::Decode(Box::new(error)))?;
+            elements.push((position, element));
+        }
+
+        Ok(elements)
+    }
+
+    fn quoted_table(&self) -> String {
+        quote(&self.table)
+    }
+}
+
+// `Client` does not implement `Debug`.
+impl Debug for PostgresStore {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PostgresStore")
+            .field("table", &self.table)
+            .field("batch", &self.batch)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<T: PostgresCodec> SequenceStore<T> for PostgresStore {
+    type Error = PostgresError;
+
+    fn load_all(&mut self) -> Result<Vec<(Pos, T)>, Self::Error> {
+        self.load_where("TRUE", &[])
+    }
+
+    /// Compares the fractions exactly by cross-multiplying them in `NUMERIC` arithmetic.
+    fn load_range<R: RangeBounds<Pos>>(&mut self, range: R) -> Result<Vec<(Pos, T)>, Self::Error> {
+        let bounds = [(range.start_bound(), ">"), (range.end_bound(), "<")];
+        let mut conditions = vec!["TRUE".to_string()];
+        let mut params = Vec::new();
+        for (bound, operator) in bounds {
+            let (position, operator) = match bound {
+                Bound::Included(position) => (position, format!("{}=", operator)),
+                Bound::Excluded(position) => (position, operator.to_string()),
+                Bound::Unbounded => continue,
+            };
+            conditions.push(format!(
+                "numerator * ${}::TEXT::NUMERIC {} ${}::TEXT::NUMERIC * denominator",
+                params.len() + 2,
+                operator,
+                params.len() + 1
+            ));
+            params.extend([position.num().to_string(), position.denom().to_string()]);
+        }
+
+        let params: Vec<&(dyn ToSql + Sync)> = params.iter().map(|param| param as &(dyn ToSql + Sync)).collect();
+        self.load_where(&conditions.join(" AND "), &params)
+    }
+
+    fn upsert(&mut self, position: &Pos, element: &T) -> Result<(), Self::Error> {
+        self.delete_rows(position)?;
+
+        let reduced = position.reduced();
+        let sql = format!(
+            "INSERT INTO {} (numerator, denominator, payload) VALUES ($1::TEXT::NUMERIC, $2::TEXT::NUMERIC, $3)",
+            self.quoted_table()
+        );
+        self.execute(
+            &sql,
+            &[&reduced.num().to_string(), &reduced.denom().to_string(), &element.encode()],
+        )
+    }
+
+    fn delete(&mut self, position: &Pos) -> Result<(), Self::Error> {
+        self.delete_rows(position)
+    }
+
+    fn begin(&mut self) -> Result<(), Self::Error> {
+        if std::mem::take(&mut self.batch) {
+            self.client.batch_execute("ROLLBACK")?;
+        }
+        self.client.batch_execute("BEGIN")?;
+        self.batch = true;
+
+        Ok(())
+    }
+
+    fn commit(&mut self) -> Result<(), Self::Error> {
+        if std::mem::take(&mut self.batch) {
+            self.client.batch_execute("COMMIT")?;
+        }
+
+        Ok(())
+    }
+
+    fn clear(&mut self) -> Result<(), Self::Error> {
+        let sql = format!("DELETE FROM {}", self.quoted_table());
+        self.execute(&sql, &[])
+    }
+}
+
+/// Returns the statements creating the table with a payload column of type `T::SQL_TYPE` and an index on the exact ordering.
+fn create_table_sql<T: PostgresCodec>(table: &str) -> String {
+    format!(
+        "CREATE TABLE IF NOT EXISTS {table} (
+            numerator NUMERIC(20, 0) NOT NULL CHECK (numerator >= 0),
+            denominator NUMERIC(20, 0) NOT NULL CHECK (denominator >= 1),
+            payload {payload},
+            PRIMARY KEY (numerator, denominator)
+        );
+        CREATE INDEX IF NOT EXISTS {index} ON {table} ({order_by});",
+        table = quote(table),
+        payload = T::SQL_TYPE,
+        index = quote(&format!("{}_order_idx", table)),
+        order_by = PostgresStore::ORDER_BY
+    )
+}
+
+/// Quotes an identifier, doubling any quotes it contains.
+fn quote(identifier: &str) -> String {
+    format!("\"{}\"", identifier.replace('"', "\"\""))
+}
+
+/// Parses a numerator or denominator read as text, as `NUMERIC` has no counterpart in Rust.
+fn parse_u64(text: String) -> Result<u64, PostgresError> {
+    text.parse().map_err(|error| PostgresError::Decode(Box::new(error)))
+}
+
+#[cfg(test)]
+#[path = "tests/postgres_tests.rs"]
+mod postgres_tests;
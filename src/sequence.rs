@@ -7,63 +7,107 @@
 
 // Publicly re-exporting all items valuable to users.
 // (avoids explicitly listing re-exports in crate documentation as there is no alternate path to those items)
+use std::borrow::Borrow;
 use std::cmp::Ordering;
-use std::fmt::{Debug, Formatter};
+use std::collections::BTreeSet;
+use std::fmt::{Debug, Display, Formatter};
+use std::hash::{Hash, Hasher};
 use std::ops::{Add, AddAssign};
-use std::ops::{Index, IndexMut};
+use std::ops::{Bound, Index, IndexMut, RangeBounds};
 
 use num_integer::gcd;
+
+use crate::error::{PosError, PositionExhausted};
+use crate::store::SequenceStore;
 #[cfg(feature = "serde-derive")]
 use serde::{Deserialize, Serialize};
 
+//
+// NodeId
+//
+
+/// `NodeId` is the stable identity of an element in a [`Sequence`], generated when the element is inserted.
+///
+/// Unlike the element's position, its id does not change when the element is moved or the sequence is rebalanced,
+/// so that it maps one-to-one to e.g. a primary key in a database. A sequence never hands out the same id twice.
+///
+/// Ids are unique within one sequence only. Both sequences resulting from [`Sequence::split_off_at()`]
+/// continue to hand out ids where the original sequence left off, so do not mix the ids of both, e.g. in one table.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde-derive", derive(Serialize, Deserialize))]
+pub struct NodeId(u64);
+
+impl From<u64> for NodeId {
+    #[inline]
+    fn from(id: u64) -> Self {
+        NodeId(id)
+    }
+}
+
+impl From<NodeId> for u64 {
+    #[inline]
+    fn from(id: NodeId) -> Self {
+        id.0
+    }
+}
+
+/// Formats `NodeId` as plain number, e.g. `42`.
+impl Display for NodeId {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
 //
 // Node
 //
-#[derive(Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq)]
 #[cfg_attr(feature = "serde-derive", derive(Serialize, Deserialize))]
-pub struct Node<T> {
-    position: Pos,
+pub struct Node<T, P = Pos> {
+    #[cfg_attr(feature = "serde-derive", serde(default))]
+    pub(crate) id: NodeId,
+    pub(crate) position: P,
     element: Option<T>,
+    // Stamp of the removal which left this node empty, see `Sequence::watermark()`.
+    #[cfg_attr(feature = "serde-derive", serde(default))]
+    pub(crate) removed: Option<u64>,
 }
 
-impl<T> Node<T> {
+impl<T, P> Node<T, P> {
     #[inline]
     #[must_use]
-    fn new(position: Pos, element: T) -> Self {
+    pub(crate) fn new(id: NodeId, position: P, element: T) -> Self {
         Node {
+            id,
             position,
             element: Some(element),
+            removed: None,
         }
     }
 
     #[inline]
     #[must_use]
-    fn new_empty(position: Pos) -> Self {
-        Node { position, element: None }
-    }
-
-    #[inline]
-    #[must_use]
-    fn position(&self) -> Pos {
-        self.position
-    }
-
-    #[inline]
-    #[must_use]
-    pub fn pos(&self) -> (u64, u64) {
-        (self.position.num, self.position.denom)
+    fn new_empty(id: NodeId, position: P) -> Self {
+        Node {
+            id,
+            position,
+            element: None,
+            removed: None,
+        }
     }
 
+    /// Returns the node's id, see [`NodeId`].
     #[inline]
     #[must_use]
-    pub fn num(&self) -> u64 {
-        self.position.num
+    pub fn id(&self) -> NodeId {
+        self.id
     }
 
+    /// Returns the node's position.
     #[inline]
     #[must_use]
-    pub fn denom(&self) -> u64 {
-        self.position.denom
+    pub fn position(&self) -> &P {
+        &self.position
     }
 
     #[inline]
@@ -97,8 +141,36 @@ impl<T> Node<T> {
     }
 
     #[inline]
-    fn set(&mut self, element: T) {
-        self.element = Some(element)
+    pub(crate) fn set(&mut self, element: T) {
+        self.element = Some(element);
+        self.removed = None;
+    }
+
+    /// Takes the element out of the node, leaving an empty node at the same position stamped with removed.
+    #[inline]
+    pub(crate) fn take(&mut self, removed: u64) -> Option<T> {
+        self.removed = Some(removed);
+        self.element.take()
+    }
+}
+
+impl<T> Node<T, Pos> {
+    #[inline]
+    #[must_use]
+    pub fn pos(&self) -> (u64, u64) {
+        (self.position.num, self.position.denom)
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn num(&self) -> u64 {
+        self.position.num
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn denom(&self) -> u64 {
+        self.position.denom
     }
 }
 
@@ -109,16 +181,82 @@ mod node_tests;
 //
 // Position
 //
+
+/// `Position` is implemented by all types that define the ordering of `Node`s in a `Sequence`,
+/// i.e. [`Pos`] and [`OrderKey`](`crate::OrderKey`).
+///
+/// There must always be a position before and after any other position,
+/// while the positions in between two others may run out, e.g. due to limited precision.
+pub trait Position: Clone + Ord + Debug + Display {
+    /// Returns a position in between lower and upper, or None if there is none left.
+    /// A missing lower (upper) bound asks for a position before upper (after lower),
+    /// both missing for the position of the first element of an empty sequence.
+    ///
+    /// Types which have a single way to choose a position ignore strategy.
+    fn between(lower: Option<&Self>, upper: Option<&Self>, strategy: PositionStrategy) -> Option<Self>;
+
+    /// Returns n ascending positions in between lower and upper, or None if there are not enough positions left.
+    /// Without an upper bound, this never returns None.
+    ///
+    /// The default implementation bisects the range recursively, so positions are spread evenly.
+    fn spread(lower: Option<&Self>, upper: Option<&Self>, n: usize) -> Option<Vec<Self>> {
+        if n == 0 {
+            return Some(Vec::new());
+        }
+
+        let mid = Self::between(lower, upper, PositionStrategy::default())?;
+        let mut positions = Self::spread(lower, Some(&mid), n / 2)?;
+        let after = Self::spread(Some(&mid), upper, n - n / 2 - 1)?;
+        positions.push(mid);
+        positions.extend(after);
+
+        Some(positions)
+    }
+}
+
+/// Returns true if range contains no position for sure, i.e. its start is after its end,
+/// or both are equal and not both included. `BTreeMap::range()` panics on some of these ranges.
+pub(crate) fn is_empty_range<P: Ord, R: RangeBounds<P>>(range: &R) -> bool {
+    match (range.start_bound(), range.end_bound()) {
+        (Bound::Included(start), Bound::Included(end)) => start > end,
+        (Bound::Included(start) | Bound::Excluded(start), Bound::Included(end) | Bound::Excluded(end)) => start >= end,
+        _ => false,
+    }
+}
+
 const DENOM_MIN: u64 = 1;
 
+// Length bytes of terms in sort keys start at 1, so neither they nor their inversions collide with the closing bytes 0 and 255.
+const SORT_KEY_TERM: u8 = 1;
+
 /// `Pos` defines the ordering of `Node`s in a `Sequence`.
+///
+/// With feature `serde-derive`, it is serialized as struct of `num` and `denom` and validated when deserialized.
 #[derive(Copy, Clone)]
-#[cfg_attr(feature = "serde-derive", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde-derive", derive(Serialize, Deserialize), serde(try_from = "PosRepr"))]
 pub struct Pos {
     num: u64,
     denom: u64,
 }
 
+// Serialized shape of `Pos`, converted into `Pos` by `try_new()` to reject a denominator of 0.
+#[cfg(feature = "serde-derive")]
+#[derive(Deserialize)]
+struct PosRepr {
+    num: u64,
+    denom: u64,
+}
+
+#[cfg(feature = "serde-derive")]
+impl TryFrom<PosRepr> for Pos {
+    type Error = PosError;
+
+    #[inline]
+    fn try_from(repr: PosRepr) -> Result<Self, Self::Error> {
+        Pos::try_new(repr.num, repr.denom)
+    }
+}
+
 trait Min {
     const MIN: Pos;
 }
@@ -127,20 +265,161 @@ impl Min for Pos {
     const MIN: Pos = Pos { num: u64::MIN, denom: 1 };
 }
 
-trait Max {
-    const MAX: Pos;
-}
-
-impl Max for Pos {
-    const MAX: Pos = Pos { num: u64::MAX, denom: 1 };
+/// `PositionStrategy` defines how a `Sequence` chooses the position of an element inserted between two others.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde-derive", derive(Serialize, Deserialize))]
+pub enum PositionStrategy {
+    /// Takes the mediant of both neighbours, see [`checked_mid()`](`Pos::checked_mid()`).
+    /// Cheap to compute, but denominators grow quickly unless the neighbours are adjacent fractions.
+    #[default]
+    Mediant,
+    /// Takes the fraction with the smallest denominator in between both neighbours,
+    /// see [`simplest_between()`](`Pos::simplest_between()`). Keeps numerators and denominators small for longer.
+    SternBrocot,
 }
 
 impl Pos {
+    /// Creates a `Pos` from a numerator and a denominator.
+    /// The fraction is stored as given, use [`reduced()`](`Pos::reduced()`) to get its canonical form.
+    ///
+    /// # Errors
+    /// Returns `PosError::ZeroDenominator` if denominator is 0.
+    #[inline]
+    pub fn try_new(num: u64, denom: u64) -> Result<Self, PosError> {
+        if denom < DENOM_MIN {
+            Err(PosError::ZeroDenominator)
+        } else {
+            Ok(Pos { num, denom })
+        }
+    }
+
+    /// Returns the numerator.
+    #[inline]
+    #[must_use]
+    pub fn num(&self) -> u64 {
+        self.num
+    }
+
+    /// Returns the denominator, which is always >= 1.
+    #[inline]
+    #[must_use]
+    pub fn denom(&self) -> u64 {
+        self.denom
+    }
+
+    /// Returns the canonical form of `Pos`, i.e. the fraction reduced to lowest terms.
+    /// Equal `Pos` have the same reduced form, e.g. `2/4` and `3/6` are both reduced to `1/2`.
+    #[inline]
+    #[must_use]
+    pub fn reduced(&self) -> Self {
+        let divisor = gcd(self.num, self.denom);
+
+        Pos {
+            num: self.num / divisor,
+            denom: self.denom / divisor,
+        }
+    }
+
+    /// Returns true if `Pos` is in its canonical form, see [`reduced()`](`Pos::reduced()`).
+    #[inline]
+    #[must_use]
+    pub fn is_reduced(&self) -> bool {
+        gcd(self.num, self.denom) == 1
+    }
+
+    /// Returns a key which sorts like the position when compared byte by byte, e.g. by `memcmp` or as `BLOB`.
+    /// Equal positions have equal keys, e.g. `2/4` and `1/2`, so keys are suitable for unique indices, too.
+    ///
+    /// The key encodes the continued fraction `[a0; a1, ..., an]` of the position. Each term is written as a length byte
+    /// followed by its big endian bytes, with the bytes of every odd term inverted, as a larger odd term makes the fraction
+    /// smaller. A final byte stands for the infinite term closing the continued fraction.
+    #[must_use]
+    pub fn sort_key(&self) -> Vec<u8> {
+        let mut key = Vec::new();
+        let (mut num, mut denom) = (self.num, self.denom);
+        let mut odd = false;
+
+        while denom != 0 {
+            let term = num / denom;
+            (num, denom) = (denom, num % denom);
+
+            let len = 8 - term.leading_zeros() as usize / 8;
+            let start = key.len();
+            key.push(SORT_KEY_TERM + len as u8);
+            key.extend_from_slice(&term.to_be_bytes()[8 - len..]);
+            if odd {
+                key[start..].iter_mut().for_each(|byte| *byte = !*byte);
+            }
+            odd = !odd;
+        }
+        key.push(if odd { u8::MIN } else { u8::MAX });
+
+        key
+    }
+
+    /// Creates a `Pos` from a key created by [`sort_key()`](`Pos::sort_key()`). The `Pos` is reduced.
+    ///
+    /// # Errors
+    /// Returns `PosError::InvalidSortKey` if the bytes are not a key created by `sort_key()`.
+    pub fn from_sort_key(key: &[u8]) -> Result<Self, PosError> {
+        let mut terms = Vec::new();
+        let mut rest = key;
+
+        loop {
+            let odd = terms.len() % 2 == 1;
+            let decode = |byte: u8| if odd { !byte } else { byte };
+
+            let (&first, tail) = rest.split_first().ok_or(PosError::InvalidSortKey)?;
+            if first == if odd { u8::MIN } else { u8::MAX } {
+                if !tail.is_empty() {
+                    return Err(PosError::InvalidSortKey);
+                }
+                break;
+            }
+
+            let len = decode(first).wrapping_sub(SORT_KEY_TERM) as usize;
+            if len > 8 || tail.len() < len {
+                return Err(PosError::InvalidSortKey);
+            }
+            let mut bytes = [0; 8];
+            tail[..len]
+                .iter()
+                .zip(&mut bytes[8 - len..])
+                .for_each(|(byte, to)| *to = decode(*byte));
+            let term = u64::from_be_bytes(bytes);
+
+            // Terms are written with as few bytes as possible, and only the first term may be 0.
+            if (len > 0 && bytes[8 - len] == 0) || (term == 0 && !terms.is_empty()) {
+                return Err(PosError::InvalidSortKey);
+            }
+            terms.push(term);
+            rest = &tail[len..];
+        }
+
+        // A last term of 1 is folded into the term before, e.g. [0; 1, 1] is written as [0; 2].
+        if terms.is_empty() || (terms.len() > 1 && terms.last() == Some(&1)) {
+            return Err(PosError::InvalidSortKey);
+        }
+
+        // Evaluates the continued fraction from its last term, i.e. a + 1 / (num / denom) = (a * num + denom) / num.
+        let mut terms = terms.into_iter().rev();
+        let (mut num, mut denom) = (terms.next().unwrap_or_default(), 1u64);
+        for term in terms {
+            let next = term
+                .checked_mul(num)
+                .and_then(|n| n.checked_add(denom))
+                .ok_or(PosError::InvalidSortKey)?;
+            (num, denom) = (next, num);
+        }
+
+        Ok(Pos { num, denom })
+    }
+
     /// Creates a valid `Pos`, i.e. the denominator >= 1
     /// If denominator is set to 0, the `Pos` will have a denominator of 1.
     #[inline]
     #[must_use]
-    fn new(num: u64, denom: u64) -> Self {
+    pub(crate) fn new(num: u64, denom: u64) -> Self {
         if denom < DENOM_MIN {
             Pos { num, denom: DENOM_MIN }
         } else {
@@ -155,30 +434,186 @@ impl Pos {
         Self { num: 1, denom: 0 }
     }
 
+    /// Returns the mediant of two positions, i.e. `(a + c) / (b + d)` for `a/b` and `c/d`,
+    /// which lies strictly between both positions.
+    ///
+    /// Returns None if numerator or denominator overflow, or if both positions are equal
+    /// and there is no position in between.
+    #[inline]
+    #[must_use]
+    pub fn checked_mid(first: Self, second: Self) -> Option<Self> {
+        if first == second {
+            None
+        } else {
+            first.checked_add(second)
+        }
+    }
+
+    /// Returns the fraction with the smallest denominator strictly between two positions,
+    /// found by descending the Stern–Brocot tree. The result is reduced.
+    ///
+    /// Returns None if both positions are equal, or if numerator or denominator of the result overflow.
+    #[must_use]
+    pub fn simplest_between(first: Self, second: Self) -> Option<Self> {
+        let (lower, upper) = match first.cmp(&second) {
+            Ordering::Less => (first, second),
+            Ordering::Greater => (second, first),
+            Ordering::Equal => return None,
+        };
+
+        Self::simplest_above(lower, Some(upper))
+    }
+
+    /// Returns the fraction with the smallest denominator strictly between lower and upper,
+    /// with an upper bound of None standing for no bound at all. Requires lower < upper.
+    fn simplest_above(lower: Self, upper: Option<Self>) -> Option<Self> {
+        // Descends the Stern–Brocot tree one continued fraction term at a time,
+        // so that the number of steps is logarithmic in the denominators.
+        fn descend(lower: (u128, u128), upper: Option<(u128, u128)>) -> (u128, u128) {
+            let (num, denom) = lower;
+            let floor = num / denom;
+
+            match upper {
+                None => (floor + 1, 1),
+                Some((upper_num, upper_denom)) => {
+                    // Take the next integer if it is in between.
+                    if (floor + 1).checked_mul(upper_denom).is_some_and(|next| next < upper_num) {
+                        return (floor + 1, 1);
+                    }
+
+                    // Both bounds share the integer part, continue with the reciprocals of the fractional parts.
+                    let lower = (upper_denom, upper_num - floor * upper_denom);
+                    let upper = match num - floor * denom {
+                        0 => None,
+                        rem => Some((denom, rem)),
+                    };
+                    let (num, denom) = descend(lower, upper);
+
+                    (floor * num + denom, num)
+                }
+            }
+        }
+
+        let (num, denom) = descend(
+            (lower.num as u128, lower.denom as u128),
+            upper.map(|upper| (upper.num as u128, upper.denom as u128)),
+        );
+
+        Some(Pos {
+            num: u64::try_from(num).ok()?,
+            denom: u64::try_from(denom).ok()?,
+        })
+    }
+
+    /// Returns a position strictly between lower and upper as defined by strategy.
+    /// A lower bound of None stands for `Pos::MIN` (exclusive), an upper bound of None for no bound at all.
+    ///
+    /// Returns None if there is no position left in between.
+    fn between(lower: Option<Self>, upper: Option<Self>, strategy: PositionStrategy) -> Option<Self> {
+        match (strategy, lower, upper) {
+            (PositionStrategy::Mediant, None, None) => Some(Pos::new(1, 1)),
+            (PositionStrategy::Mediant, Some(lower), None) => lower.checked_add(Pos::n1d0()),
+            (PositionStrategy::Mediant, lower, Some(upper)) => Pos::checked_mid(lower.unwrap_or(Pos::MIN), upper),
+            (PositionStrategy::SternBrocot, lower, upper) => {
+                let lower = lower.unwrap_or(Pos::MIN);
+                match upper {
+                    Some(upper) if upper <= lower => None,
+                    _ => Pos::simplest_above(lower, upper),
+                }
+            }
+        }
+    }
+
+    /// Returns n ascending positions strictly between lower and upper, with small denominators.
+    /// A lower bound of None stands for `Pos::MIN` (exclusive), an upper bound of None for no bound at all.
+    ///
+    /// Positions are spread evenly between both bounds, or are consecutive integers if there is no upper bound.
+    /// The common denominator is found by bisection, but the number of fractions in between both bounds does not
+    /// strictly grow with the denominator, so it is not always the smallest one which fits n positions.
+    /// Returns None if there are less than n positions of `u64` numerators and denominators in between.
+    fn spread(lower: Option<Self>, upper: Option<Self>, n: usize) -> Option<Vec<Self>> {
+        let n = n as u128;
+        if n == 0 {
+            return Some(Vec::new());
+        }
+
+        let lower = lower.unwrap_or(Pos::MIN);
+        // Returns the first numerator and the number of numerators m, for which m/denom is in between the bounds.
+        let numerators = |denom: u64| -> (u128, u128) {
+            let first = lower.num as u128 * denom as u128 / lower.denom as u128 + 1;
+            let last = match upper {
+                None => u64::MAX as u128,
+                Some(upper) => match upper.num as u128 * denom as u128 {
+                    0 => 0,
+                    num => ((num - 1) / upper.denom as u128).min(u64::MAX as u128),
+                },
+            };
+
+            (first, (last + 1).saturating_sub(first))
+        };
+
+        // Find a small denominator by doubling it first and bisecting afterwards.
+        let mut denom: u64 = 1;
+        while numerators(denom).1 < n {
+            denom = match denom {
+                u64::MAX => return None,
+                _ => denom.saturating_mul(2),
+            };
+        }
+        let (mut low, mut high) = (denom / 2 + 1, denom);
+        while low < high {
+            let mid = low + (high - low) / 2;
+            if numerators(mid).1 >= n {
+                high = mid;
+            } else {
+                low = mid + 1;
+            }
+        }
+        let denom = high;
+        let (first, count) = numerators(denom);
+
+        let positions = (0..n)
+            .map(|i| match upper {
+                None => first + i,
+                Some(_) => first + (i + 1) * (count + 1) / (n + 1) - 1,
+            })
+            .map(|num| Pos::new(num as u64, denom).reduced())
+            .collect();
+
+        Some(positions)
+    }
+
+    /// Adds numerators and denominators separately like `Add`, returns None on overflow.
     #[inline]
     #[must_use]
-    fn mid(first: Self, second: Self) -> Self {
-        first + second
+    fn checked_add(self, rhs: Self) -> Option<Self> {
+        Some(Self {
+            num: self.num.checked_add(rhs.num)?,
+            denom: self.denom.checked_add(rhs.denom)?,
+        })
     }
 }
 
 /// `Pos` are added to each other by adding their numerators and denominators separately.
 /// So, `Pos` does not follow the rules for adding fractions.
+///
+/// # Panics
+/// Panics if numerator or denominator overflow, in release builds too.
+/// Use [`checked_mid()`](`Pos::checked_mid()`) to get the mediant of two positions without panicking.
 impl Add for Pos {
     type Output = Self;
 
     #[inline]
-    #[must_use]
     fn add(self, rhs: Self) -> Self::Output {
-        Self {
-            num: self.num + rhs.num,
-            denom: self.denom + rhs.denom,
-        }
+        self.checked_add(rhs).expect("numerator or denominator overflow")
     }
 }
 
 /// `Pos` are added to each other by adding their numerators and denominators separately.
 /// So, `Pos` does not follow the rules for adding fractions.
+///
+/// # Panics
+/// Panics if numerator or denominator overflow, like `Add`.
 impl AddAssign for Pos {
     #[inline]
     fn add_assign(&mut self, rhs: Self) {
@@ -186,46 +621,69 @@ impl AddAssign for Pos {
     }
 }
 
+/// `Pos` are compared as fractions, i.e. `1/2 == 2/4`.
+///
+/// Comparison is exact: numerators and denominators are cross-multiplied in 128-bit arithmetic,
+/// which cannot overflow for `u64` operands.
 impl PartialEq for Pos {
     #[inline]
     fn eq(&self, other: &Self) -> bool {
-        if self.num == other.num && self.denom == other.denom {
-            true
-        } else {
-            let self_divisor = gcd(self.num, self.denom);
-            let other_divisor = gcd(other.num, other.denom);
-
-            // If greatest common divisor (gcd) is not 1 we have to reduce the
-            // Position first by dividing numerator and denominator by the gcd.
-            match (self_divisor, other_divisor) {
-                (1, 1) => false,
-                #[rustfmt::skip]
-                (_, 1) => { // marked for tarpaulin
-                    let f1 = Self::new(self.num / self_divisor, self.denom / self_divisor);
-                    f1 == *other
-                }
-                #[rustfmt::skip]
-                (1, _) => { // marked for tarpaulin
-                    let f2 = Self::new(other.num / other_divisor, other.denom / other_divisor);
-                    f2 == *self
-                }
-                #[rustfmt::skip]
-                (_, _) => { // marked for tarpaulin
-                    let f1 = Self::new(self.num / self_divisor, self.denom / self_divisor);
-                    let f2 = Self::new(other.num / other_divisor, other.denom / other_divisor);
-                    f1 == f2
-                }
-            }
-        }
+        self.cmp(other) == Ordering::Equal
     }
 }
 
+impl Eq for Pos {}
+
 impl PartialOrd for Pos {
+    #[inline]
     fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        let f1 = self.num as f64 / self.denom as f64;
-        let f2 = other.num as f64 / self.denom as f64;
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Pos {
+    #[inline]
+    fn cmp(&self, other: &Self) -> Ordering {
+        let lhs = self.num as u128 * other.denom as u128;
+        let rhs = other.num as u128 * self.denom as u128;
+
+        lhs.cmp(&rhs)
+    }
+}
+
+/// Hashes the reduced fraction, so that equal `Pos` (e.g. `1/2` and `2/4`) have equal hashes.
+impl Hash for Pos {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        let reduced = self.reduced();
+
+        reduced.num.hash(state);
+        reduced.denom.hash(state);
+    }
+}
+
+/// Creates a `Pos` from a `(numerator, denominator)` pair, e.g. as loaded from a database.
+impl TryFrom<(u64, u64)> for Pos {
+    type Error = PosError;
+
+    #[inline]
+    fn try_from((num, denom): (u64, u64)) -> Result<Self, Self::Error> {
+        Pos::try_new(num, denom)
+    }
+}
+
+/// Converts a `Pos` into its `(numerator, denominator)` pair, e.g. to store it in a database.
+/// The pair is returned as stored, not reduced.
+impl From<Pos> for (u64, u64) {
+    #[inline]
+    fn from(pos: Pos) -> Self {
+        (pos.num, pos.denom)
+    }
+}
 
-        f1.partial_cmp(&f2)
+/// Formats `Pos` as fraction, e.g. `3/2`.
+impl Display for Pos {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}/{}", self.num, self.denom)
     }
 }
 
@@ -237,12 +695,23 @@ impl Debug for Pos {
 
 impl Default for Pos {
     #[inline]
-    #[must_use]
     fn default() -> Self {
         Self::new(1, DENOM_MIN)
     }
 }
 
+impl Position for Pos {
+    #[inline]
+    fn between(lower: Option<&Self>, upper: Option<&Self>, strategy: PositionStrategy) -> Option<Self> {
+        Pos::between(lower.copied(), upper.copied(), strategy)
+    }
+
+    #[inline]
+    fn spread(lower: Option<&Self>, upper: Option<&Self>, n: usize) -> Option<Vec<Self>> {
+        Pos::spread(lower.copied(), upper.copied(), n)
+    }
+}
+
 #[cfg(test)]
 #[path = "tests/position_tests.rs"]
 mod position_tests;
@@ -251,49 +720,247 @@ mod position_tests;
 // Sequence
 //
 
+/// `Placement` defines where to move an element relative to another one,
+/// see [`move_to_pos()`](`Sequence::move_to_pos()`) and [`move_by_id()`](`Sequence::move_by_id()`).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Placement<P = Pos> {
+    /// Right before the element at the given position.
+    Before(P),
+    /// Right after the element at the given position.
+    After(P),
+}
+
+/// `Change` records a mutation of a [`Sequence`], see [`take_changes()`](`Sequence::take_changes()`).
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde-derive", derive(Serialize, Deserialize))]
+pub enum Change<P = Pos> {
+    /// An element has been inserted at pos.
+    Inserted { pos: P },
+    /// The element at pos has been replaced.
+    Updated { pos: P },
+    /// The element at pos has been removed.
+    Removed { pos: P },
+    /// An element has been moved from one position to another.
+    Moved { from: P, to: P },
+}
+
+impl<P> Placement<P> {
+    /// Returns the position of the element to move relative to.
+    #[inline]
+    fn anchor(&self) -> &P {
+        match self {
+            Placement::Before(anchor) | Placement::After(anchor) => anchor,
+        }
+    }
+
+    /// Returns the same placement relative to another anchor.
+    #[inline]
+    fn with_anchor<Q>(&self, anchor: Q) -> Placement<Q> {
+        match self {
+            Placement::Before(_) => Placement::Before(anchor),
+            Placement::After(_) => Placement::After(anchor),
+        }
+    }
+}
+
+impl<P> Change<P> {
+    /// Returns the positions affected by the change, i.e. both positions of a move.
+    pub fn positions(&self) -> impl Iterator<Item = &P> {
+        let (first, second) = match self {
+            Change::Inserted { pos } | Change::Updated { pos } | Change::Removed { pos } => (pos, None),
+            Change::Moved { from, to } => (from, Some(to)),
+        };
+
+        std::iter::once(first).chain(second)
+    }
+}
+
 /// A `Sequence` with a deterministic and easy to persist ordering.
 ///
 /// What you can do
-/// * Create a Sequence: [new()](`Sequence::new()`) or with_capacity()
+/// * Create a Sequence: [new()](`Sequence::new()`), with_capacity() or with_strategy()
+/// * Create a Sequence of another position type: with_position() or with_capacity_and_position()
 /// * Determine the capacity: capacity()
 /// * Determine if it contains elements: is_empty()
 /// * Determine how many elements it contains: len()
 /// * Get the first element: first()
 /// * Get the last element: last()
-/// * Get an immutable reference to an element: get()
-/// * Get a mutable reference to an element: get_mut()
-/// * Insert an element at a defined index: insert()
+/// * Get an immutable reference to an element: get() and `seq[index]`
+/// * Get a mutable reference to an element: get_mut() and `seq[index]`
+/// * Get the node holding an element at a defined index or position: node() and node_at()
+/// * Get a reference to an element at a defined position: get_at() and get_at_mut()
+/// * Look up positions: contains_pos(), predecessor(), successor() and position_between()
+/// * Insert an element at a defined index: insert() and try_insert()
 /// * Insert an element at a defined positions: insert_at()
-/// * Get an element's position from its index: position_from() and pos_from()
-/// * Append an element to the sequence: push()
+/// * Get an element's position from its index: position_from()
+/// * Inspect and modify the element at a defined position in place: entry()
+/// * Append an element to the sequence: push() and try_push()
+/// * Insert or append many elements at once: insert_many(), try_insert_many(), collect() and extend()
 /// * Remove an element at a defined index: remove()
 /// * Remove an element at a defined position: remove_at()
-#[derive(Debug, PartialEq)]
-#[cfg_attr(feature = "serde-derive", derive(Serialize, Deserialize))]
-pub struct Sequence<T> {
-    nodes: Vec<Node<T>>,
+/// * Move an element, changing its position only: move_to() and move_to_pos()
+/// * Iterate over the elements: iter(), iter_mut(), values(), positions() and enumerate_positions()
+/// * Iterate over all nodes including empty ones: iter_with_tombstones()
+/// * Get the elements in a range of positions or indices: range() and range_by_index()
+/// * Split off or drain the elements in a range of positions: split_off_at() and drain_range()
+/// * Address an element by its stable id: id_of(), get_by_id(), position_of(), remove_by_id() and move_by_id()
+/// * Reassign compact positions to all or some elements: rebalance() and rebalance_range()
+/// * Reorder or filter elements, changing as few positions as possible: sort_by(), sort_by_key(), reverse(),
+///   retain() and dedup_by_key()
+/// * Drop empty nodes left behind by removed elements: tombstones(), compact() and compact_before()
+/// * Manage the capacity: reserve() and shrink_to_fit()
+/// * Record changes to persist them incrementally: enable_journal(), disable_journal() and take_changes()
+/// * Load from, save to and sync to a [`SequenceStore`](`crate::SequenceStore`): load_from(), load_range_from(), save_to()
+///   and sync_to()
+///
+/// With feature `serde-derive`, a deserialized sequence is validated, i.e. its nodes must be strictly ascending
+/// by position and hold as many elements as its length says, and its elements must have distinct ids.
+/// Sequences serialized before ids were introduced get fresh ids in order.
+#[derive(Debug)]
+#[cfg_attr(
+    feature = "serde-derive",
+    derive(Serialize, Deserialize),
+    serde(
+        try_from = "SequenceRepr<T, P>",
+        bound(deserialize = "T: Deserialize<'de>, P: Position + Deserialize<'de>")
+    )
+)]
+pub struct Sequence<T, P = Pos> {
+    nodes: Vec<Node<T, P>>,
     len: usize,
+    strategy: PositionStrategy,
+    removals: u64,
+    next_id: u64,
+    #[cfg_attr(feature = "serde-derive", serde(skip))]
+    journal: Option<Vec<Change<P>>>,
 }
 
-impl<T> Sequence<T> {
-    #[inline]
-    #[must_use]
-    pub fn new() -> Self {
-        Self {
-            nodes: Vec::new(),
-            len: 0,
-        }
-    }
+// Serialized shape of `Sequence`, converted into `Sequence` once lookups by binary search can rely on its nodes.
+#[cfg(feature = "serde-derive")]
+#[derive(Deserialize)]
+struct SequenceRepr<T, P> {
+    nodes: Vec<Node<T, P>>,
+    len: usize,
+    #[serde(default)]
+    strategy: PositionStrategy,
+    #[serde(default)]
+    removals: u64,
+    // Missing if serialized before ids were introduced.
+    #[serde(default)]
+    next_id: Option<u64>,
+}
 
-    #[inline]
+#[cfg(feature = "serde-derive")]
+impl<T, P: Position> TryFrom<SequenceRepr<T, P>> for Sequence<T, P> {
+    type Error = &'static str;
+
+    fn try_from(mut repr: SequenceRepr<T, P>) -> Result<Self, Self::Error> {
+        if !repr.nodes.windows(2).all(|pair| pair[0].position < pair[1].position) {
+            return Err("nodes of a sequence must be strictly ascending by position");
+        }
+        if repr.nodes.iter().filter(|node| node.is_some()).count() != repr.len {
+            return Err("length of a sequence must match the number of its elements");
+        }
+
+        let next_id = match repr.next_id {
+            Some(next_id) => {
+                if !repr.nodes.iter().all(|node| node.id.0 < next_id) {
+                    return Err("ids of a sequence must be less than its next id");
+                }
+                // Empty nodes keep the id of their removed or moved element, so only elements must not share ids.
+                let mut ids = BTreeSet::new();
+                if !repr
+                    .nodes
+                    .iter()
+                    .filter(|node| node.is_some())
+                    .all(|node| ids.insert(node.id))
+                {
+                    return Err("elements of a sequence must have distinct ids");
+                }
+                next_id
+            }
+            None => {
+                for (id, node) in repr.nodes.iter_mut().enumerate() {
+                    node.id = NodeId(id as u64);
+                }
+                repr.nodes.len() as u64
+            }
+        };
+
+        Ok(Sequence {
+            nodes: repr.nodes,
+            len: repr.len,
+            strategy: repr.strategy,
+            removals: repr.removals,
+            next_id,
+            journal: None,
+        })
+    }
+}
+
+impl<T> Sequence<T, Pos> {
+    /// Creates an empty sequence with positions of type `Pos`.
+    /// Use [`with_position()`](`Sequence::with_position()`) for other position types.
+    #[inline]
+    #[must_use]
+    pub fn new() -> Self {
+        Self::with_position()
+    }
+
+    /// Creates an empty sequence with positions of type `Pos`, which holds at least capacity nodes without reallocating.
+    #[inline]
     #[must_use]
     pub fn with_capacity(capacity: usize) -> Self {
+        Self::with_capacity_and_position(capacity)
+    }
+
+    /// Creates an empty sequence with positions of type `Pos`,
+    /// choosing the positions of inserted elements as defined by strategy.
+    #[inline]
+    #[must_use]
+    pub fn with_strategy(strategy: PositionStrategy) -> Self {
+        let mut seq = Self::new();
+        seq.strategy = strategy;
+        seq
+    }
+}
+
+impl<T, P: Position> Sequence<T, P> {
+    /// Creates an empty sequence with positions of type `P`, e.g. `Sequence::<char, OrderKey>::with_position()`.
+    /// Set the strategy used to choose positions by [`set_strategy()`](`Sequence::set_strategy()`).
+    #[inline]
+    #[must_use]
+    pub fn with_position() -> Self {
+        Self::with_capacity_and_position(0)
+    }
+
+    /// Creates an empty sequence with positions of type `P`, which holds at least capacity nodes without reallocating.
+    #[inline]
+    #[must_use]
+    pub fn with_capacity_and_position(capacity: usize) -> Self {
         Self {
             nodes: Vec::with_capacity(capacity),
             len: 0,
+            strategy: PositionStrategy::default(),
+            removals: 0,
+            next_id: 0,
+            journal: None,
         }
     }
 
+    /// Returns the strategy used to choose the positions of inserted elements.
+    #[inline]
+    pub fn strategy(&self) -> PositionStrategy {
+        self.strategy
+    }
+
+    /// Sets the strategy used to choose the positions of inserted elements.
+    /// Positions of elements already in the sequence do not change.
+    #[inline]
+    pub fn set_strategy(&mut self, strategy: PositionStrategy) {
+        self.strategy = strategy;
+    }
+
     /// Returns the total number of elements the sequence can
     /// hold without reallocating.
     #[inline]
@@ -356,109 +1023,242 @@ impl<T> Sequence<T> {
         }
     }
 
+    /// Returns the element at index, or None if out of bounds.
+    /// Indices count elements only, i.e. skip empty nodes, like [`len()`](`Sequence::len()`) and `seq[index]`.
     #[inline]
     #[must_use]
-    /// Returns `Some<T>`
-    /// Returns None when out of bounds
     pub fn get(&self, index: usize) -> Option<&T> {
-        if index >= self.len {
-            return None;
-        }
+        self.node(index).and_then(|node| node.element_as_ref())
+    }
 
-        for node in self.nodes[index..].iter() {
-            if node.is_some() {
-                return node.element_as_ref();
-            }
-        }
+    /// Returns the element at index mutably, or None if out of bounds, see [`get()`](`Sequence::get()`).
+    #[inline]
+    #[must_use]
+    pub fn get_mut(&mut self, index: usize) -> Option<&mut T> {
+        let slot = self.slot(index)?;
+        self.nodes[slot].element_as_mut()
+    }
 
-        #[cfg(not(tarpaulin_include))]
-        {
-            None
-        }
+    /// Returns the node holding the element at index, or None if out of bounds, see [`get()`](`Sequence::get()`).
+    #[inline]
+    #[must_use]
+    pub fn node(&self, index: usize) -> Option<&Node<T, P>> {
+        self.slot(index).map(|slot| &self.nodes[slot])
+    }
+
+    /// Returns the node holding the element at position, or None if there is none.
+    ///
+    /// Nodes are kept ordered by their position, so this is a binary search of O(log n).
+    #[must_use]
+    pub fn node_at<Q: Borrow<P>>(&self, position: Q) -> Option<&Node<T, P>> {
+        let slot = self.search(position.borrow()).ok()?;
+        Some(&self.nodes[slot]).filter(|node| node.is_some())
     }
 
+    /// Returns the element at position, or None if there is none. Takes O(log n).
     #[inline]
     #[must_use]
-    /// Returns `Some<T>`
-    /// Returns None when out of bounds
-    pub fn get_mut(&mut self, index: usize) -> Option<&mut T> {
-        if index >= self.len {
-            return None;
-        }
+    pub fn get_at<Q: Borrow<P>>(&self, position: Q) -> Option<&T> {
+        self.node_at(position).and_then(|node| node.element_as_ref())
+    }
 
-        for node in self.nodes[index..].iter_mut() {
-            if node.is_some() {
-                return node.element_as_mut();
-            }
+    /// Returns the element at position mutably, or None if there is none. Takes O(log n).
+    #[must_use]
+    pub fn get_at_mut<Q: Borrow<P>>(&mut self, position: Q) -> Option<&mut T> {
+        let slot = self.search(position.borrow()).ok()?;
+        self.nodes[slot].element_as_mut()
+    }
+
+    /// Returns true if there is an element at position. Takes O(log n).
+    #[inline]
+    pub fn contains_pos<Q: Borrow<P>>(&self, position: Q) -> bool {
+        self.node_at(position).is_some()
+    }
+
+    /// Returns the node holding the last element before position, or None if there is none.
+    /// There does not need to be an element at position. Empty nodes are skipped.
+    pub fn predecessor<Q: Borrow<P>>(&self, position: Q) -> Option<&Node<T, P>> {
+        let (slot, _) = self.slot_bounds(position.borrow()..);
+        self.nodes[..slot].iter().rfind(|node| node.is_some())
+    }
+
+    /// Returns the node holding the first element after position, or None if there is none.
+    /// There does not need to be an element at position. Empty nodes are skipped.
+    pub fn successor<Q: Borrow<P>>(&self, position: Q) -> Option<&Node<T, P>> {
+        let (_, slot) = self.slot_bounds(..=position.borrow());
+        self.nodes[slot..].iter().find(|node| node.is_some())
+    }
+
+    /// Returns a free position between lower and upper, e.g. to insert an element with [`insert_at()`](`Sequence::insert_at()`)
+    /// in between the elements at positions received from a client. Takes O(log n).
+    ///
+    /// The position is chosen right after lower as defined by the sequence's strategy,
+    /// so that it is neither taken by an element nor by an empty node.
+    ///
+    /// # Errors
+    /// Returns `PositionExhausted` if there is no position left right after lower, or if upper is not after lower.
+    pub fn position_between<Q: Borrow<P>>(&self, lower: Q, upper: Q) -> Result<P, PositionExhausted<P>> {
+        let (lower, upper) = (lower.borrow(), upper.borrow());
+        if lower >= upper {
+            return Err(PositionExhausted::new(Some(lower.clone()), Some(upper.clone())));
         }
 
-        #[cfg(not(tarpaulin_include))]
-        {
-            None
+        // Any node in between lower and upper limits the free positions right after lower.
+        let (_, slot) = self.slot_bounds(..=lower);
+        let upper = match self.nodes.get(slot) {
+            Some(node) if node.position() < upper => node.position(),
+            _ => upper,
+        };
+
+        P::between(Some(lower), Some(upper), self.strategy)
+            .ok_or_else(|| PositionExhausted::new(Some(lower.clone()), Some(upper.clone())))
+    }
+
+    /// Returns the node at slot, counting empty nodes, or None if out of bounds. This is what `seq[slot]` used to return.
+    #[deprecated(note = "slots count empty nodes, use `node()` which counts elements only, or `iter_with_tombstones()`")]
+    #[inline]
+    #[must_use]
+    pub fn node_at_slot(&self, slot: usize) -> Option<&Node<T, P>> {
+        self.nodes.get(slot)
+    }
+
+    /// Returns the index of the element at position, or None if there is none.
+    /// Indices count elements only, i.e. skip empty nodes, so this takes O(n).
+    pub fn index_from<Q: Borrow<P>>(&self, position: Q) -> Option<usize> {
+        match self.search(position.borrow()) {
+            Ok(slot) if self.nodes[slot].is_some() => Some(self.nodes[..slot].iter().filter(|node| node.is_some()).count()),
+            _ => None,
         }
     }
 
-    /// Iterates the sequence searching for position.
-    /// Returns None if position is not in sequence.
-    /// Returns Some(index) if position is in sequence.
-    ///
-    /// # Panics
-    /// This function might panic if the iterator has more than usize::MAX non-matching elements.
-    // todo: legacy!
-    // todo: cover panic!
-    pub fn index_from(&self, position: Pos) -> Option<usize> {
-        self.nodes.iter().position(|node| node.position() == position)
+    /// Binary searches the nodes for position, see `slice::binary_search()`.
+    #[inline]
+    fn search(&self, position: &P) -> Result<usize, usize> {
+        self.nodes.binary_search_by(|node| node.position().cmp(position))
     }
 
     /// Inserts an element at index, shifting all elements after it to the right.
-    /// Appends the element to the vector if index > len.
-    /// Inserts an element into an existing Node or by creating a new Node.
-    /// Remember: Index starts at 0.
+    /// Indices count elements only, i.e. skip empty nodes. Appends the element if index >= len.
     ///
-    /// if node is empty (i.e. node.element == None) set node.element to Some(element).
-    /// if node is not empty (i.e. node.element == Some) insert element into self.nodes.
+    /// The element is inserted into a new node. Empty nodes keep their positions, see [`remove()`](`Sequence::remove()`).
     ///
     /// # Panics
-    /// Unlike `std::vec::Vec`, does not panic.
+    /// Unlike `std::vec::Vec`, does not panic if index > len.
+    /// Panics if there is no position left between the element's neighbours,
+    /// see [`try_insert()`](`Sequence::try_insert()`) for a non-panicking alternative.
     pub fn insert(&mut self, index: usize, element: T) {
-        if index >= self.nodes.len() {
-            // Append element (self.len is incremented by push()).
-            self.push(element);
-        } else {
-            if self.nodes[index].is_some() {
-                let pos = if index == 0 {
-                    // unwrap() is safe because the prior if covered the self.len() == 0 case.
-                    Pos::mid(Pos::MIN, self.nodes.first().map(|node| node.position()).unwrap())
-                } else {
-                    Pos::mid(
-                        self.nodes.get(index - 1).map(|node| node.position()).unwrap(),
-                        self.nodes.get(index).map(|node| node.position()).unwrap(),
-                    )
-                };
-
-                let node = Node::new(pos, element);
-                self.nodes.insert(index, node);
-            } else {
-                self.nodes[index].set(element);
-            }
+        if let Err(err) = self.try_insert(index, element) {
+            panic!("{}", err);
+        }
+    }
+
+    /// Inserts an element at index like [`insert()`](`Sequence::insert()`) and returns the element's position.
+    ///
+    /// # Errors
+    /// Returns `PositionExhausted` if there is no position left between the element's neighbours,
+    /// e.g. after many inserts at the same index. The sequence is left unchanged and the element is dropped.
+    /// Rebalancing the sequence makes room for further inserts.
+    pub fn try_insert(&mut self, index: usize, element: T) -> Result<P, PositionExhausted<P>> {
+        let Some(slot) = self.slot(index) else {
+            // Append element (self.len is incremented by try_push()).
+            return self.try_push(element);
+        };
+
+        // The node before may be empty, its position is not handed out again.
+        let lower = slot.checked_sub(1).map(|index| self.nodes[index].position());
+        let upper = self.nodes[slot].position();
+
+        let pos = P::between(lower, Some(upper), self.strategy)
+            .ok_or_else(|| PositionExhausted::new(lower.cloned(), Some(upper.clone())))?;
+        let id = self.generate_id();
+        self.nodes.insert(slot, Node::new(id, pos.clone(), element));
+
+        self.len += 1;
+        self.record(Change::Inserted { pos: pos.clone() });
+        Ok(pos)
+    }
 
-            self.len += 1;
+    /// Inserts all elements in order at index, shifting all elements after them to the right.
+    /// Indices count elements only, i.e. skip empty nodes. Appends the elements if index >= len.
+    ///
+    /// The positions are spread evenly between the neighbouring elements in one pass,
+    /// which keeps them much shorter than inserting the elements one by one.
+    ///
+    /// # Panics
+    /// Panics if there are not enough positions left between the neighbouring elements,
+    /// see [`try_insert_many()`](`Sequence::try_insert_many()`) for a non-panicking alternative.
+    pub fn insert_many<I: IntoIterator<Item = T>>(&mut self, index: usize, elements: I) {
+        if let Err(err) = self.try_insert_many(index, elements) {
+            panic!("{}", err);
         }
     }
 
-    /// Inserts an element at position. If there is an element at the position, it is overwritten.
-    /// If not, element is inserted and all following elements after shifted to the right.
-    // todo: use binary_search which required Ord for Position
-    pub fn insert_at(&mut self, position: Pos, element: T) {
-        match self.index_from(position) {
-            None => {
-                let index = self.nodes.partition_point(|node| node.position() < position);
-                self.insert(index, element);
+    /// Inserts all elements in order at index like [`insert_many()`](`Sequence::insert_many()`) and returns their positions.
+    ///
+    /// # Errors
+    /// Returns `PositionExhausted` if there are not enough positions left between the neighbouring elements.
+    /// The sequence is left unchanged and the elements are dropped.
+    pub fn try_insert_many<I: IntoIterator<Item = T>>(
+        &mut self,
+        index: usize,
+        elements: I,
+    ) -> Result<Vec<P>, PositionExhausted<P>> {
+        let elements: Vec<T> = elements.into_iter().collect();
+
+        let slot = self.slot(index).unwrap_or(self.nodes.len());
+        let lower = slot.checked_sub(1).map(|index| self.nodes[index].position());
+        let upper = self.nodes.get(slot).map(|node| node.position());
+        let positions =
+            P::spread(lower, upper, elements.len()).ok_or_else(|| PositionExhausted::new(lower.cloned(), upper.cloned()))?;
+
+        let mut nodes = Vec::with_capacity(elements.len());
+        for (position, element) in positions.iter().cloned().zip(elements) {
+            nodes.push(Node::new(self.generate_id(), position, element));
+        }
+        self.len += nodes.len();
+        self.nodes.splice(slot..slot, nodes);
+        for pos in &positions {
+            self.record(Change::Inserted { pos: pos.clone() });
+        }
+
+        Ok(positions)
+    }
+
+    /// Gets the entry at position for in-place manipulation, e.g. to insert an element only if there is none.
+    /// Nodes are kept ordered by their position, so this is a binary search of O(log n).
+    pub fn entry(&mut self, position: P) -> Entry<'_, T, P> {
+        match self.search(&position) {
+            Ok(slot) if self.nodes[slot].is_some() => Entry::Occupied(OccupiedEntry { seq: self, slot }),
+            Ok(slot) => Entry::Tombstoned(TombstonedEntry { seq: self, slot }),
+            Err(slot) => Entry::Vacant(VacantEntry {
+                seq: self,
+                slot,
+                position,
+            }),
+        }
+    }
+
+    /// Inserts an element at position. If there is an element at the position, it is overwritten and keeps its id.
+    /// If not, element is inserted with a new id and all following elements after shifted to the right.
+    /// See [`entry()`](`Sequence::entry()`) to inspect the position first.
+    pub fn insert_at(&mut self, position: P, element: T) {
+        match self.search(&position) {
+            Err(index) => {
+                let id = self.generate_id();
+                self.nodes.insert(index, Node::new(id, position.clone(), element));
+                self.len += 1;
+                self.record(Change::Inserted { pos: position });
             }
-            Some(index) => {
-                // If node does not contain an element, increase len before setting the element.
+            Ok(index) => {
+                // If node does not contain an element, increase len and assign a new id before setting the element.
+                // Record the node's position, which may differ from an equal position, e.g. `2/4` for `1/2`.
+                let pos = self.nodes[index].position().clone();
                 if self.nodes[index].is_none() {
                     self.len += 1;
+                    self.nodes[index].id = self.generate_id();
+                    self.record(Change::Inserted { pos });
+                } else {
+                    self.record(Change::Updated { pos });
                 }
 
                 // Replace the prior element.
@@ -467,210 +1267,1384 @@ impl<T> Sequence<T> {
         }
     }
 
-    // todo: legacy
-    pub fn position_from(&self, index: usize) -> Option<Pos> {
-        if index >= self.nodes.len() {
-            None
-        } else {
-            Some(self.nodes[index].position())
-        }
-    }
-
-    #[allow(unused)]
-    fn pos_from(&self, index: usize) -> Option<(u64, u64)> {
-        if index >= self.nodes.len() {
-            None
-        } else {
-            Some(self.nodes[index].pos())
-        }
+    /// Returns the position of the element at index, or None if out of bounds, see [`get()`](`Sequence::get()`).
+    #[inline]
+    pub fn position_from(&self, index: usize) -> Option<P> {
+        self.node(index).map(|node| node.position().clone())
     }
 
     /// Appends an element to the back of the sequence.
+    ///
+    /// # Panics
+    /// Panics if there is no position left after the last position,
+    /// see [`try_push()`](`Sequence::try_push()`) for a non-panicking alternative.
     #[inline]
     pub fn push(&mut self, element: T) {
-        let pos = match self.last_position() {
-            None => Pos::new(1, 1),
-            Some(pos) => pos + Pos::n1d0(),
-        };
-        let node = Node::new(pos, element);
+        if let Err(err) = self.try_push(element) {
+            panic!("{}", err);
+        }
+    }
+
+    /// Appends an element to the back of the sequence and returns the element's position.
+    ///
+    /// # Errors
+    /// Returns `PositionExhausted` if the last position's numerator can't be incremented any further.
+    /// The sequence is left unchanged and the element is dropped.
+    pub fn try_push(&mut self, element: T) -> Result<P, PositionExhausted<P>> {
+        let last = self.last_position();
+        let pos = P::between(last, None, self.strategy).ok_or_else(|| PositionExhausted::new(last.cloned(), None))?;
+        let node = Node::new(self.generate_id(), pos.clone(), element);
 
         self.nodes.push(node);
         self.len += 1;
+        self.record(Change::Inserted { pos: pos.clone() });
+        Ok(pos)
     }
 
-    /// Removes and returns the element at index.
-    /// Unlike `std::vec::Vec`, does not shift elements after it to the left
+    /// Removes and returns the element at index, or None if out of bounds.
+    /// Indices count elements only, i.e. skip empty nodes.
+    /// Unlike `std::vec::Vec`, does not shift nodes after it to the left
     /// because it just replaces the element with None.
-    /// So, this has performance of O(1).
+    /// The empty node keeps its position, so nodes stay sorted by position and the sequence does not hand out
+    /// the position again until the empty node is dropped, see [`compact()`](`Sequence::compact()`).
+    /// [`insert_at()`](`Sequence::insert_at()`) still inserts at the position if asked to.
     ///
     /// # Panics
     /// Unlike `std::vec::Vec`, does not panic.
     pub fn remove(&mut self, index: usize) -> Option<T> {
-        if index >= self.len {
-            return None;
+        let slot = self.slot(index)?;
+        self.take(slot)
+    }
+
+    /// Takes the element out of the node at slot, leaving a stamped empty node behind. The node must not be empty.
+    #[inline]
+    fn take(&mut self, slot: usize) -> Option<T> {
+        self.len -= 1;
+        self.removals += 1;
+        self.record(Change::Removed {
+            pos: self.nodes[slot].position().clone(),
+        });
+        self.nodes[slot].take(self.removals - 1)
+    }
+
+    /// Removes and returns the element at position, or None if there is none, see [`remove()`](`Sequence::remove()`).
+    /// Nodes are kept ordered by their position, so this is a binary search of O(log n).
+    ///
+    /// # Panics
+    /// Unlike `std::vec::Vec`, does not panic.
+    pub fn remove_at<Q: Borrow<P>>(&mut self, position: Q) -> Option<T> {
+        match self.search(position.borrow()) {
+            Ok(slot) if self.nodes[slot].is_some() => self.take(slot),
+            _ => None,
         }
+    }
 
-        for (i, node) in self.nodes[index..].iter().enumerate() {
-            if node.is_some() {
-                self.len -= 1;
-
-                // Push an empty node to the end of Vec self.nodes
-                // using the position of the node to be removed.
-                // Then swap the empty node with the node to remove.
-                // Finally, remove the node.
-                let node = Node::new_empty(self.nodes[index + i].position());
-                self.nodes.push(node);
-                return self.nodes.swap_remove(index + i).element();
-            }
+    /// Moves the element at index from to index to, shifting the elements in between, and returns its new position.
+    /// Indices count elements only, i.e. skip empty nodes. Moves the element to the back if to >= len.
+    ///
+    /// Only the moved element's position changes, so that a single row has to be updated in a database.
+    /// If the element's position is already in between its new neighbours, it is kept. Otherwise, an empty node is left
+    /// at the old position like [`remove()`](`Sequence::remove()`) does, so that the position is not handed out again.
+    /// Returns Ok(None) if there is no element at index from.
+    ///
+    /// # Errors
+    /// Returns `PositionExhausted` if there is no position left between the element's new neighbours.
+    /// The sequence is left unchanged.
+    pub fn move_to(&mut self, from: usize, to: usize) -> Result<Option<P>, PositionExhausted<P>> {
+        let Some(from_slot) = self.slot(from) else {
+            return Ok(None);
+        };
+        let node = self.nodes.remove(from_slot);
+
+        // Insert in front of the element at index to, which has been shifted by the removal if behind the node.
+        let slot = self.slot(to).unwrap_or(self.nodes.len());
+        self.place(node, from_slot, slot).map(Some)
+    }
+
+    /// Moves the element at position right before or after the element at another position and returns its new position.
+    ///
+    /// Only the moved element's position changes, see [`move_to()`](`Sequence::move_to()`).
+    /// Returns Ok(None) if there is no element at either position.
+    ///
+    /// # Errors
+    /// Returns `PositionExhausted` if there is no position left between the element's new neighbours.
+    /// The sequence is left unchanged.
+    pub fn move_to_pos<Q: Borrow<P>>(&mut self, position: Q, placement: Placement<P>) -> Result<Option<P>, PositionExhausted<P>> {
+        let (Ok(from_slot), Ok(anchor_slot)) = (self.search(position.borrow()), self.search(placement.anchor())) else {
+            return Ok(None);
+        };
+        if self.nodes[from_slot].is_none() || self.nodes[anchor_slot].is_none() {
+            return Ok(None);
+        }
+        if from_slot == anchor_slot {
+            return Ok(Some(self.nodes[from_slot].position().clone()));
         }
+        let node = self.nodes.remove(from_slot);
 
-        #[cfg(not(tarpaulin_include))]
-        {
+        let anchor_slot = if anchor_slot > from_slot {
+            anchor_slot - 1
+        } else {
+            anchor_slot
+        };
+        let slot = match placement {
+            Placement::Before(_) => anchor_slot,
+            Placement::After(_) => anchor_slot + 1,
+        };
+        self.place(node, from_slot, slot).map(Some)
+    }
+
+    /// Inserts a node taken from slot from_slot at slot, assigning a new position between its new neighbours if required.
+    /// Restores the node at from_slot if there is no position left.
+    ///
+    /// If the position changes, a stamped empty node is left at the old position like [`take()`](`Sequence::take()`) does,
+    /// so that the old position is not handed out again.
+    fn place(&mut self, mut node: Node<T, P>, from_slot: usize, slot: usize) -> Result<P, PositionExhausted<P>> {
+        let lower = slot.checked_sub(1).map(|index| self.nodes[index].position());
+        let upper = self.nodes.get(slot).map(|node| node.position());
+
+        let fits = lower.is_none_or(|lower| lower < &node.position) && upper.is_none_or(|upper| &node.position < upper);
+        let from = if fits {
             None
+        } else {
+            match P::between(lower, upper, self.strategy) {
+                Some(pos) => Some(std::mem::replace(&mut node.position, pos)),
+                None => {
+                    let err = PositionExhausted::new(lower.cloned(), upper.cloned());
+                    self.nodes.insert(from_slot, node);
+                    return Err(err);
+                }
+            }
+        };
+
+        let (id, pos) = (node.id, node.position.clone());
+        self.nodes.insert(slot, node);
+
+        if let Some(from) = from {
+            let mut empty = Node::new_empty(id, from.clone());
+            empty.removed = Some(self.removals);
+            self.removals += 1;
+
+            let slot = self.search(&from).unwrap_or_else(|slot| slot);
+            self.nodes.insert(slot, empty);
+            self.record(Change::Moved { from, to: pos.clone() });
         }
+
+        Ok(pos)
     }
 
-    /// Removes and returns the element at position.
-    /// Unlike `std::vec::Vec`, does not shift elements after it to the left
-    /// because it just replaces the element with None.
-    /// Because we have to search the `Position`, it has performance of O(n/2).
-    ///
-    /// # Panics
-    /// Unlike `std::vec::Vec`, does not panic.
-    pub fn remove_at(&mut self, position: Pos) -> Option<T> {
-        match self.index_from(position) {
-            None => None,
-            Some(index) => self.remove(index),
+    /// Returns the id of the element at position, or None if there is none.
+    pub fn id_of<Q: Borrow<P>>(&self, position: Q) -> Option<NodeId> {
+        match self.search(position.borrow()) {
+            Ok(index) if self.nodes[index].is_some() => Some(self.nodes[index].id),
+            _ => None,
         }
     }
 
-    #[inline]
-    #[must_use]
-    fn last_position(&self) -> Option<Pos> {
-        self.nodes.last().map(|node| node.position())
+    /// Returns the element with id, or None if there is none. Takes O(n).
+    pub fn get_by_id(&self, id: NodeId) -> Option<&T> {
+        self.slot_of(id).and_then(|slot| self.nodes[slot].element_as_ref())
     }
 
-    #[cfg(not(tarpaulin_include))]
-    #[allow(dead_code)]
-    fn shrink(&mut self) {
-        unimplemented!()
+    /// Returns the position of the element with id, or None if there is none. Takes O(n).
+    pub fn position_of(&self, id: NodeId) -> Option<P> {
+        self.slot_of(id).map(|slot| self.nodes[slot].position().clone())
     }
-}
 
-impl<T> Default for Sequence<T> {
-    fn default() -> Self {
-        Self::new()
+    /// Removes and returns the element with id, or None if there is none, see [`remove()`](`Sequence::remove()`).
+    /// Takes O(n).
+    pub fn remove_by_id(&mut self, id: NodeId) -> Option<T> {
+        let slot = self.slot_of(id)?;
+        self.take(slot)
     }
-}
 
-impl<T> Index<usize> for Sequence<T> {
-    type Output = Node<T>;
+    /// Moves the element with id right before or after the element with another id and returns its new position.
+    /// The moved element keeps its id.
+    ///
+    /// Only the moved element's position changes, see [`move_to()`](`Sequence::move_to()`).
+    /// Returns Ok(None) if there is no element with either id. Takes O(n).
+    ///
+    /// # Errors
+    /// Returns `PositionExhausted` if there is no position left between the element's new neighbours.
+    /// The sequence is left unchanged.
+    pub fn move_by_id(&mut self, id: NodeId, placement: Placement<NodeId>) -> Result<Option<P>, PositionExhausted<P>> {
+        let (Some(position), Some(anchor)) = (self.position_of(id), self.position_of(*placement.anchor())) else {
+            return Ok(None);
+        };
+        self.move_to_pos(position, placement.with_anchor(anchor))
+    }
 
+    /// Returns the index of the node holding the element with id. Empty nodes keep the id of their removed element,
+    /// so they are skipped.
     #[inline]
-    fn index(&self, index: usize) -> &Self::Output {
-        &self.nodes[index]
+    fn slot_of(&self, id: NodeId) -> Option<usize> {
+        self.nodes.iter().position(|node| node.id == id && node.is_some())
     }
-}
 
-impl<T> IndexMut<usize> for Sequence<T> {
+    /// Returns a new id, which has not been handed out by this sequence before.
     #[inline]
-    fn index_mut(&mut self, index: usize) -> &mut Self::Output {
-        &mut self.nodes[index]
+    fn generate_id(&mut self) -> NodeId {
+        self.next_id += 1;
+        NodeId(self.next_id - 1)
     }
-}
 
-impl<T: Clone> Clone for Sequence<T> {
-    fn clone(&self) -> Self {
-        let mut seq: Sequence<T> = Sequence::new();
+    /// Returns an iterator over the nodes in order. Empty nodes are skipped, see [`iter_with_tombstones()`](`Sequence::iter_with_tombstones()`).
+    /// Nodes give access to the position and id of each element, use [`values()`](`Sequence::values()`) for the elements only.
+    #[inline]
+    pub fn iter(&self) -> Iter<'_, T, P> {
+        self.into_iter()
+    }
 
-        for node in self {
-            let node = match node.element_as_ref() {
-                None => Node::new_empty(node.position()),
-                Some(element) => Node::new(node.position(), element.clone()),
-            };
-            seq.nodes.push(node);
-        }
-        seq.len = self.len;
-        seq
+    /// Returns an iterator over the elements in order, allowing to modify them.
+    ///
+    /// Unlike [`iter()`](`Sequence::iter()`), this yields elements instead of nodes: neither positions nor ids can be
+    /// changed in place, as nodes must stay ordered by position. See [`move_to_pos()`](`Sequence::move_to_pos()`)
+    /// to change the position of an element.
+    #[inline]
+    pub fn iter_mut(&mut self) -> IterMut<'_, T, P> {
+        self.into_iter()
     }
-}
 
-//
-// Consuming Iterator
-//
+    /// Returns an iterator over the elements in order.
+    #[inline]
+    pub fn values(&self) -> Values<'_, T, P> {
+        Values(self.iter())
+    }
 
-// IntoIterator for Sequence<T>
-// - consumes the `Sequence`
-// - removes all empty Nodes from `Sequence`
-// - iterates over remaining Nodes
-impl<T> IntoIterator for Sequence<T> {
-    type Item = Node<T>;
-    type IntoIter = std::vec::IntoIter<Node<T>>;
+    /// Returns an iterator over the positions of the elements in order.
+    #[inline]
+    pub fn positions(&self) -> Positions<'_, T, P> {
+        Positions(self.iter())
+    }
 
+    /// Returns an iterator over the indices and positions of the elements in order, e.g. to map indices to positions.
     #[inline]
-    fn into_iter(mut self) -> Self::IntoIter {
-        self.nodes.retain(|node| node.element_as_ref().is_some());
-        self.nodes.into_iter()
+    pub fn enumerate_positions(&self) -> EnumeratePositions<'_, T, P> {
+        EnumeratePositions {
+            positions: self.positions(),
+            front: 0,
+        }
     }
-}
 
-//
-// Immutable Iterator
-//
+    /// Returns an iterator over all nodes in order, including the empty nodes left behind by removed elements,
+    /// e.g. to synchronize removals with a database, see [`tombstones()`](`Sequence::tombstones()`).
+    #[inline]
+    pub fn iter_with_tombstones(&self) -> std::slice::Iter<'_, Node<T, P>> {
+        self.nodes.iter()
+    }
 
-// Iterator / IntoIterator over a `Sequence` represented by a slice of immutable `Option<&Node>`
-// - allows to use a Sequence in a for loop
-pub struct SequenceIterator<'iterator, T: 'iterator>(Option<&'iterator [Node<T>]>);
+    /// Returns an iterator over the nodes with positions in range, in order. Empty nodes are skipped.
+    /// Takes O(log n) to find the range's bounds, so that e.g. a page of elements can be retrieved by the positions around it.
+    /// Returns an empty iterator if the range's start is after its end.
+    pub fn range<R: RangeBounds<P>>(&self, range: R) -> Range<'_, T, P> {
+        let (start, end) = self.slot_bounds(range);
+        Range(self.nodes[start..end].iter())
+    }
 
-impl<'iterator, T: 'iterator> Iterator for SequenceIterator<'iterator, T> {
-    type Item = &'iterator Node<T>;
+    /// Returns an iterator over the nodes in range of indices, in order. Indices count elements only, i.e. skip empty nodes.
+    /// Indices out of bounds are limited to the sequence's length.
+    pub fn range_by_index<R: RangeBounds<usize>>(&self, range: R) -> Range<'_, T, P> {
+        let (start, end) = self.index_bounds(range);
+        if start >= end {
+            return Range([].iter());
+        }
 
-    fn next(self: &'_ mut SequenceIterator<'iterator, T>) -> Option<Self::Item> {
-        self.0.and_then(|v| {
-            let (head, tail) = v.split_first()?;
-            self.0 = Some(tail);
-            Some(head)
-        })
+        let start = self.slot(start).unwrap_or(self.nodes.len());
+        let end = self.slot(end).unwrap_or(self.nodes.len());
+        Range(self.nodes[start..end].iter())
     }
-}
 
-impl<'iterator, T: 'iterator> IntoIterator for &'iterator Sequence<T> {
-    type Item = &'iterator Node<T>;
-    type IntoIter = SequenceIterator<'iterator, T>;
+    /// Splits the sequence in two at position. Returns a new sequence with all nodes at positions >= position,
+    /// while the nodes before position are left. Positions and ids of the elements do not change.
+    ///
+    /// Both sequences hand out new ids starting from the same id, so ids are not unique across them, see [`NodeId`].
+    #[must_use]
+    pub fn split_off_at<Q: Borrow<P>>(&mut self, position: Q) -> Self {
+        let (start, _) = self.slot_bounds(position.borrow()..);
+        let nodes = self.nodes.split_off(start);
+        let len = nodes.iter().filter(|node| node.is_some()).count();
+        self.len -= len;
+
+        // The elements split off are removed from this sequence and inserted into the other one.
+        let mut other = Self {
+            nodes,
+            len,
+            strategy: self.strategy,
+            removals: self.removals,
+            next_id: self.next_id,
+            journal: self.journal.as_ref().map(|_| Vec::new()),
+        };
+        for pos in other.positions().cloned().collect::<Vec<P>>() {
+            self.record(Change::Removed { pos: pos.clone() });
+            other.record(Change::Inserted { pos });
+        }
 
-    #[inline]
-    fn into_iter(self) -> Self::IntoIter {
-        SequenceIterator(Some(self.nodes.as_slice()))
+        other
     }
-}
 
-//
-// Mutable Iterator
-//
+    /// Removes the elements with positions in range and returns them as nodes, in order.
+    /// Like [`remove()`](`Sequence::remove()`), leaves empty nodes behind, so that the positions are not handed out again.
+    pub fn drain_range<R: RangeBounds<P>>(&mut self, range: R) -> Vec<Node<T, P>> {
+        let (start, end) = self.slot_bounds(range);
 
-// Iterator / IntoIterator over a `Sequence` represented by a slice of mutable `Option<&mut Node>`
-// - allows to use a Sequence in a for loop
-pub struct SequenceIteratorMut<'iterator, T: 'iterator>(Option<&'iterator mut [Node<T>]>);
+        (start..end).filter_map(|slot| self.take_node(slot)).collect()
+    }
 
-impl<'iterator, T: 'iterator> Iterator for SequenceIteratorMut<'iterator, T> {
-    type Item = &'iterator mut Node<T>;
+    /// Removes the elements for which f returns false. Returns the list of changes, i.e. `Change::Removed` for each
+    /// removed position in order, which are recorded in the journal as well, see [`take_changes()`](`Sequence::take_changes()`).
+    /// Like [`remove()`](`Sequence::remove()`), leaves empty nodes behind. Positions of the remaining elements do not change.
+    pub fn retain<F: FnMut(&T) -> bool>(&mut self, mut f: F) -> Vec<Change<P>> {
+        let mut changes = Vec::new();
+        for slot in 0..self.nodes.len() {
+            if self.nodes[slot].element_as_ref().is_some_and(|element| !f(element)) {
+                changes.push(self.take_change(slot));
+            }
+        }
 
-    fn next(self: &'_ mut SequenceIteratorMut<'iterator, T>) -> Option<Self::Item> {
-        self.0.take().and_then(|v| {
-            let (head, tail) = v.split_first_mut()?;
-            self.0 = Some(tail);
-            Some(head)
-        })
+        changes
     }
-}
 
-impl<'iterator, T: 'iterator> IntoIterator for &'iterator mut Sequence<T> {
-    type Item = &'iterator mut Node<T>;
-    type IntoIter = SequenceIteratorMut<'iterator, T>;
+    /// Removes all but the first of consecutive elements that resolve to the same key. Returns the list of changes,
+    /// see [`retain()`](`Sequence::retain()`).
+    /// Like [`remove()`](`Sequence::remove()`), leaves empty nodes behind. Positions of the remaining elements do not change.
+    pub fn dedup_by_key<K: PartialEq, F: FnMut(&mut T) -> K>(&mut self, mut key: F) -> Vec<Change<P>> {
+        let mut changes = Vec::new();
+        let mut previous = None;
+        for slot in 0..self.nodes.len() {
+            let Some(element) = self.nodes[slot].element_as_mut() else {
+                continue;
+            };
 
-    #[inline]
-    fn into_iter(self) -> Self::IntoIter {
-        SequenceIteratorMut(Some(self.nodes.as_mut_slice()))
+            let current = Some(key(element));
+            if current == previous {
+                changes.push(self.take_change(slot));
+            } else {
+                previous = current;
+            }
+        }
+
+        changes
+    }
+
+    /// Sorts the elements with a comparator function. The sort is stable, i.e. does not reorder equal elements.
+    ///
+    /// Positions are reassigned minimally: the largest possible set of elements, i.e. a longest increasing subsequence
+    /// of the current positions in the sorted order, keeps its positions, while all other elements get new positions
+    /// in between. Returns the list of changed positions as `(old, new)` pairs, see [`rebalance()`](`Sequence::rebalance()`).
+    ///
+    /// # Errors
+    /// Returns `PositionExhausted` if there are not enough positions left in between the elements keeping their positions.
+    /// The sequence is left unchanged.
+    pub fn sort_by<F: FnMut(&T, &T) -> Ordering>(&mut self, mut compare: F) -> Result<Vec<(P, P)>, PositionExhausted<P>> {
+        let elements: Vec<&T> = self.values().collect();
+        let mut order: Vec<usize> = (0..elements.len()).collect();
+        order.sort_by(|&a, &b| compare(elements[a], elements[b]));
+
+        self.reorder(order)
+    }
+
+    /// Sorts the elements with a key extraction function, see [`sort_by()`](`Sequence::sort_by()`).
+    ///
+    /// # Errors
+    /// Returns `PositionExhausted` if there are not enough positions left in between the elements keeping their positions.
+    /// The sequence is left unchanged.
+    pub fn sort_by_key<K: Ord, F: FnMut(&T) -> K>(&mut self, mut key: F) -> Result<Vec<(P, P)>, PositionExhausted<P>> {
+        self.sort_by(|a, b| key(a).cmp(&key(b)))
+    }
+
+    /// Reverses the order of the elements, see [`sort_by()`](`Sequence::sort_by()`). All but one element change their positions.
+    ///
+    /// # Errors
+    /// Returns `PositionExhausted` if there are not enough positions left before or after the element keeping its position.
+    /// The sequence is left unchanged.
+    pub fn reverse(&mut self) -> Result<Vec<(P, P)>, PositionExhausted<P>> {
+        self.reorder((0..self.len).rev().collect())
+    }
+
+    /// Reorders the elements, so that the element at index order\[i\] ends up at index i, reassigning positions minimally.
+    fn reorder(&mut self, order: Vec<usize>) -> Result<Vec<(P, P)>, PositionExhausted<P>> {
+        let live: Vec<&P> = self.positions().collect();
+        let old: Vec<&P> = order.iter().map(|&index| live[index]).collect();
+        let tombstones: Vec<&P> = self
+            .nodes
+            .iter()
+            .filter(|node| node.is_none())
+            .map(|node| node.position())
+            .collect();
+        let keep = longest_increasing_subsequence(&old);
+
+        // Spread the positions of each run of moved elements between the elements keeping their positions around it,
+        // but before the next empty node, so that positions are not handed out twice.
+        let mut positions: Vec<P> = Vec::with_capacity(old.len());
+        while positions.len() < old.len() {
+            let start = positions.len();
+            if keep[start] {
+                positions.push(old[start].clone());
+                continue;
+            }
+
+            let end = (start..old.len()).find(|&index| keep[index]).unwrap_or(old.len());
+            let lower = positions.last().cloned();
+            let tombstone = match &lower {
+                Some(lower) => tombstones.get(tombstones.partition_point(|tombstone| *tombstone <= lower)),
+                None => tombstones.first(),
+            };
+            let upper = match (old.get(end), tombstone) {
+                (Some(kept), Some(tombstone)) => Some(kept.min(tombstone)),
+                (kept, tombstone) => kept.or(tombstone),
+            }
+            .copied();
+
+            let spread = P::spread(lower.as_ref(), upper, end - start)
+                .ok_or_else(|| PositionExhausted::new(lower.clone(), upper.cloned()))?;
+            positions.extend(spread);
+        }
+
+        let changes: Vec<(P, P)> = old
+            .iter()
+            .zip(&positions)
+            .filter(|(old, new)| *old != new)
+            .map(|(old, new)| ((*old).clone(), new.clone()))
+            .collect();
+
+        // Move the elements in order and merge them with the empty nodes, which keep their positions.
+        let mut live = Vec::with_capacity(self.len);
+        let mut tombstones = Vec::with_capacity(self.tombstones());
+        for node in self.nodes.drain(..) {
+            if node.is_some() {
+                live.push(Some(node));
+            } else {
+                tombstones.push(node);
+            }
+        }
+
+        let mut tombstones = tombstones.into_iter().peekable();
+        for (index, position) in order.into_iter().zip(positions) {
+            let mut node = live[index].take().expect("every element is moved once");
+            node.position = position;
+
+            while let Some(tombstone) = tombstones.next_if(|tombstone| tombstone.position < node.position) {
+                self.nodes.push(tombstone);
+            }
+            self.nodes.push(node);
+        }
+        self.nodes.extend(tombstones);
+        self.record_moves(&changes);
+
+        Ok(changes)
+    }
+
+    /// Takes the element out of the node at slot like [`take()`](`Sequence::take()`) and returns it as a new node,
+    /// or None if the node is empty.
+    fn take_node(&mut self, slot: usize) -> Option<Node<T, P>> {
+        if self.nodes[slot].is_none() {
+            return None;
+        }
+
+        let (id, position) = (self.nodes[slot].id, self.nodes[slot].position().clone());
+        self.take(slot).map(|element| Node::new(id, position, element))
+    }
+
+    /// Drops the element of the node at slot like [`take()`](`Sequence::take()`) and returns the recorded change.
+    /// The node must not be empty.
+    fn take_change(&mut self, slot: usize) -> Change<P> {
+        self.take(slot);
+        Change::Removed {
+            pos: self.nodes[slot].position().clone(),
+        }
+    }
+
+    /// Reassigns compact positions to all elements and drops all empty nodes, e.g. `1/1, 2/1, 3/1, ...` for `Pos`.
+    /// The order of elements does not change.
+    ///
+    /// Returns the list of changed positions as `(old, new)` pairs, e.g. to update the positions persisted in a database.
+    /// Elements already at their new position are not part of the list.
+    ///
+    /// Rebalancing is a full compaction like [`compact()`](`Sequence::compact()`): empty nodes are dropped regardless of
+    /// any watermark, as the new positions may reuse theirs. So synchronize removals with a database before rebalancing.
+    pub fn rebalance(&mut self) -> Vec<(P, P)> {
+        self.nodes.retain(|node| node.is_some());
+
+        let positions = P::spread(None, None, self.nodes.len()).expect("positions are unbounded");
+        let changes = Self::reposition(&mut self.nodes, positions);
+        self.record_moves(&changes);
+
+        changes
+    }
+
+    /// Reassigns compact positions to the elements in range, i.e. from the range's start index up to its end index,
+    /// and drops the empty nodes in between. The new positions are spread evenly between the positions of the neighbouring
+    /// nodes, which keep their positions, so that only the elements in range need to be updated in a database.
+    /// Like [`rebalance()`](`Sequence::rebalance()`), empty nodes in between are dropped regardless of any watermark.
+    /// Indices out of bounds are limited to the sequence's length.
+    ///
+    /// Returns the list of changed positions as `(old, new)` pairs, see [`rebalance()`](`Sequence::rebalance()`).
+    ///
+    /// # Errors
+    /// Returns `PositionExhausted` if there are not enough positions left between the neighbouring nodes.
+    /// The sequence is left unchanged.
+    pub fn rebalance_range<R: RangeBounds<usize>>(&mut self, range: R) -> Result<Vec<(P, P)>, PositionExhausted<P>> {
+        let (start, end) = self.index_bounds(range);
+
+        // Find the range of nodes spanned by the elements in range.
+        let (first, last) = match (self.slot(start), end.checked_sub(1).and_then(|last| self.slot(last))) {
+            (Some(first), Some(last)) if start < end => (first, last),
+            _ => return Ok(Vec::new()),
+        };
+        let lower = first.checked_sub(1).map(|index| self.nodes[index].position());
+        let upper = self.nodes.get(last + 1).map(|node| node.position());
+
+        let positions =
+            P::spread(lower, upper, end - start).ok_or_else(|| PositionExhausted::new(lower.cloned(), upper.cloned()))?;
+
+        let mut nodes: Vec<Node<T, P>> = self.nodes.drain(first..=last).filter(|node| node.is_some()).collect();
+        let changes = Self::reposition(&mut nodes, positions);
+        self.nodes.splice(first..first, nodes);
+        self.record_moves(&changes);
+
+        Ok(changes)
+    }
+
+    /// Assigns positions to nodes in order and returns the changed positions as `(old, new)` pairs.
+    fn reposition(nodes: &mut [Node<T, P>], positions: Vec<P>) -> Vec<(P, P)> {
+        let mut changes = Vec::new();
+        for (node, pos) in nodes.iter_mut().zip(positions) {
+            if node.position != pos {
+                let old = std::mem::replace(&mut node.position, pos.clone());
+                changes.push((old, pos));
+            }
+        }
+
+        changes
+    }
+
+    /// Returns the start and end index of range, limited to the sequence's length.
+    fn index_bounds<R: RangeBounds<usize>>(&self, range: R) -> (usize, usize) {
+        let start = match range.start_bound() {
+            Bound::Included(&start) => start,
+            Bound::Excluded(&start) => start.saturating_add(1),
+            Bound::Unbounded => 0,
+        };
+        let end = match range.end_bound() {
+            Bound::Included(&end) => end.saturating_add(1),
+            Bound::Excluded(&end) => end,
+            Bound::Unbounded => self.len,
+        }
+        .min(self.len);
+
+        (start, end)
+    }
+
+    /// Returns the start and end index of the nodes with positions in range. The end is never before the start.
+    fn slot_bounds<R: RangeBounds<P>>(&self, range: R) -> (usize, usize) {
+        let start = match range.start_bound() {
+            Bound::Included(start) => self.nodes.partition_point(|node| node.position() < start),
+            Bound::Excluded(start) => self.nodes.partition_point(|node| node.position() <= start),
+            Bound::Unbounded => 0,
+        };
+        let end = match range.end_bound() {
+            Bound::Included(end) => self.nodes.partition_point(|node| node.position() <= end),
+            Bound::Excluded(end) => self.nodes.partition_point(|node| node.position() < end),
+            Bound::Unbounded => self.nodes.len(),
+        };
+
+        (start, end.max(start))
+    }
+
+    /// Returns the index of the node holding the element at index, i.e. skipping all empty nodes.
+    #[inline]
+    fn slot(&self, index: usize) -> Option<usize> {
+        self.nodes
+            .iter()
+            .enumerate()
+            .filter(|(_, node)| node.is_some())
+            .nth(index)
+            .map(|(slot, _)| slot)
+    }
+
+    #[inline]
+    #[must_use]
+    fn last_position(&self) -> Option<&P> {
+        self.nodes.last().map(|node| node.position())
+    }
+
+    /// Starts recording the changes of elements and their positions, e.g. to persist them incrementally in a database,
+    /// see [`take_changes()`](`Sequence::take_changes()`). Does nothing if changes are already recorded.
+    #[inline]
+    pub fn enable_journal(&mut self) {
+        self.journal.get_or_insert_with(Vec::new);
+    }
+
+    /// Stops recording changes and drops the changes recorded so far.
+    #[inline]
+    pub fn disable_journal(&mut self) {
+        self.journal = None;
+    }
+
+    /// Returns true if changes are recorded, see [`enable_journal()`](`Sequence::enable_journal()`).
+    #[inline]
+    pub fn is_journal_enabled(&self) -> bool {
+        self.journal.is_some()
+    }
+
+    /// Returns the changes recorded since the journal was enabled or changes were last taken, in order.
+    /// Returns an empty list if the journal is disabled.
+    ///
+    /// Every insertion, removal and change of position is recorded, while modifications of elements through mutable
+    /// references, e.g. from [`get_mut()`](`Sequence::get_mut()`), are not. Elements replaced at their position,
+    /// e.g. by [`insert_at()`](`Sequence::insert_at()`), are recorded as updated.
+    #[inline]
+    pub fn take_changes(&mut self) -> Vec<Change<P>> {
+        self.journal.as_mut().map(std::mem::take).unwrap_or_default()
+    }
+
+    /// Loads all elements of a store into a new sequence.
+    ///
+    /// The journal of the sequence is enabled, so changes made from here on can be written back
+    /// by [`sync_to()`](`Sequence::sync_to()`).
+    pub fn load_from<S: SequenceStore<T, P>>(store: &mut S) -> Result<Self, S::Error> {
+        Ok(Self::from_stored(store.load_all()?))
+    }
+
+    /// Loads the elements of a store with positions in range into a new sequence, e.g. a page of a large sequence.
+    ///
+    /// Elements outside of range are not known to the sequence, so positions in front of the first or after the last
+    /// element loaded might be taken in the store. Insert elements in between those loaded to avoid replacing others
+    /// on [`sync_to()`](`Sequence::sync_to()`).
+    pub fn load_range_from<S: SequenceStore<T, P>, R: RangeBounds<P>>(store: &mut S, range: R) -> Result<Self, S::Error> {
+        Ok(Self::from_stored(store.load_range(range)?))
+    }
+
+    fn from_stored(mut elements: Vec<(P, T)>) -> Self {
+        elements.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+        let mut seq = Self::with_capacity_and_position(elements.len());
+        for (position, element) in elements {
+            seq.insert_at(position, element);
+        }
+        seq.enable_journal();
+
+        seq
+    }
+
+    /// Replaces all elements of a store with the elements of the sequence in one batch.
+    /// Returns the number of elements written.
+    ///
+    /// Changes recorded by the journal are kept, as they are not needed to save the sequence as a whole.
+    pub fn save_to<S: SequenceStore<T, P>>(&self, store: &mut S) -> Result<usize, S::Error> {
+        store.begin()?;
+        store.clear()?;
+        for (position, element) in self.positions().zip(self.values()) {
+            store.upsert(position, element)?;
+        }
+        store.commit()?;
+
+        Ok(self.len)
+    }
+
+    /// Writes the changes recorded by the journal to a store in one batch. Returns the number of positions
+    /// upserted or deleted.
+    ///
+    /// Changed positions holding an element are upserted with the element the sequence holds now, all others are
+    /// deleted. Modifications of elements through mutable references are not recorded, see
+    /// [`take_changes()`](`Sequence::take_changes()`). If the store fails, the changes are put back into the journal
+    /// to be synced later. Nothing is written if the journal is disabled.
+    pub fn sync_to<S: SequenceStore<T, P>>(&mut self, store: &mut S) -> Result<usize, S::Error> {
+        let changes = self.take_changes();
+        let positions: BTreeSet<P> = changes.iter().flat_map(Change::positions).cloned().collect();
+        if positions.is_empty() {
+            return Ok(0);
+        }
+
+        match self.write_to(store, &positions) {
+            Ok(()) => Ok(positions.len()),
+            Err(error) => {
+                self.restore_changes(changes);
+                Err(error)
+            }
+        }
+    }
+
+    fn write_to<S: SequenceStore<T, P>>(&self, store: &mut S, positions: &BTreeSet<P>) -> Result<(), S::Error> {
+        store.begin()?;
+        for position in positions {
+            match self.get_at(position) {
+                Some(element) => store.upsert(position, element)?,
+                None => store.delete(position)?,
+            }
+        }
+        store.commit()
+    }
+
+    /// Puts changes taken before back in front of the journal, e.g. if persisting them failed.
+    /// Does nothing if the journal is disabled.
+    pub(crate) fn restore_changes(&mut self, mut changes: Vec<Change<P>>) {
+        if let Some(journal) = self.journal.as_mut() {
+            changes.append(journal);
+            *journal = changes;
+        }
+    }
+
+    /// Records change, if the journal is enabled.
+    #[inline]
+    fn record(&mut self, change: Change<P>) {
+        if let Some(journal) = self.journal.as_mut() {
+            journal.push(change);
+        }
+    }
+
+    /// Records `(old, new)` pairs of positions as moves, if the journal is enabled.
+    fn record_moves(&mut self, changes: &[(P, P)]) {
+        for (from, to) in changes {
+            self.record(Change::Moved {
+                from: from.clone(),
+                to: to.clone(),
+            });
+        }
+    }
+
+    /// Returns the number of empty nodes left behind by removed elements.
+    /// Empty nodes keep their positions until an element is inserted at their position or they are dropped,
+    /// see [`insert_at()`](`Sequence::insert_at()`) and [`compact()`](`Sequence::compact()`).
+    #[inline]
+    pub fn tombstones(&self) -> usize {
+        self.nodes.len() - self.len
+    }
+
+    /// Returns a watermark to pass to [`compact_before()`](`Sequence::compact_before()`) later on,
+    /// so that only empty nodes left behind by removals up to now are dropped.
+    /// [`rebalance()`](`Sequence::rebalance()`) and [`rebalance_range()`](`Sequence::rebalance_range()`)
+    /// ignore watermarks and drop the empty nodes they span.
+    #[inline]
+    pub fn watermark(&self) -> u64 {
+        self.removals
+    }
+
+    /// Drops all empty nodes. Positions of the remaining elements do not change.
+    ///
+    /// Returns the number of dropped nodes.
+    pub fn compact(&mut self) -> usize {
+        let tombstones = self.tombstones();
+        self.nodes.retain(|node| node.is_some());
+
+        tombstones
+    }
+
+    /// Drops the empty nodes left behind by removals before watermark was taken, see [`watermark()`](`Sequence::watermark()`).
+    /// Newer empty nodes are kept, e.g. while their positions might still be referenced by a not yet synchronized database.
+    /// Positions of the remaining elements do not change.
+    ///
+    /// Returns the number of dropped nodes.
+    pub fn compact_before(&mut self, watermark: u64) -> usize {
+        let tombstones = self.tombstones();
+        self.nodes
+            .retain(|node| node.is_some() || node.removed.is_some_and(|removed| removed >= watermark));
+
+        tombstones - self.tombstones()
+    }
+
+    /// Shrinks the capacity of the sequence as much as possible, see `Vec::shrink_to_fit()`.
+    /// Use [`compact()`](`Sequence::compact()`) before to drop empty nodes.
+    #[inline]
+    pub fn shrink_to_fit(&mut self) {
+        self.nodes.shrink_to_fit();
+    }
+
+    /// Reserves capacity for at least additional more elements, see `Vec::reserve()`.
+    ///
+    /// # Panics
+    /// Panics if the new capacity exceeds `isize::MAX` bytes.
+    #[inline]
+    pub fn reserve(&mut self, additional: usize) {
+        self.nodes.reserve(additional);
+    }
+}
+
+impl<T, P: Position> Default for Sequence<T, P> {
+    fn default() -> Self {
+        Self::with_position()
+    }
+}
+
+/// Creates a sequence with compact positions, e.g. `1/1, 2/1, 3/1, ...` for `Pos`.
+impl<T, P: Position> FromIterator<T> for Sequence<T, P> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut seq = Sequence::with_position();
+        seq.extend(iter);
+        seq
+    }
+}
+
+/// Appends all elements to the back of the sequence, see [`insert_many()`](`Sequence::insert_many()`).
+///
+/// # Panics
+/// Panics if there are not enough positions left after the last position.
+impl<T, P: Position> Extend<T> for Sequence<T, P> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        self.insert_many(self.len, iter);
+    }
+}
+
+/// Returns the element at index, counting elements only like [`get()`](`Sequence::get()`).
+///
+/// # Panics
+/// Panics if index is out of bounds.
+impl<T, P: Position> Index<usize> for Sequence<T, P> {
+    type Output = T;
+
+    #[inline]
+    fn index(&self, index: usize) -> &Self::Output {
+        let len = self.len;
+        self.get(index)
+            .unwrap_or_else(|| panic!("index out of bounds: the len is {} but the index is {}", len, index))
+    }
+}
+
+impl<T, P: Position> IndexMut<usize> for Sequence<T, P> {
+    #[inline]
+    fn index_mut(&mut self, index: usize) -> &mut Self::Output {
+        let len = self.len;
+        self.get_mut(index)
+            .unwrap_or_else(|| panic!("index out of bounds: the len is {} but the index is {}", len, index))
+    }
+}
+
+// The journal is not part of a sequence's value.
+impl<T: PartialEq, P: PartialEq> PartialEq for Sequence<T, P> {
+    fn eq(&self, other: &Self) -> bool {
+        self.nodes == other.nodes
+            && self.len == other.len
+            && self.strategy == other.strategy
+            && self.removals == other.removals
+            && self.next_id == other.next_id
+    }
+}
+
+impl<T: Clone, P: Position> Clone for Sequence<T, P> {
+    fn clone(&self) -> Self {
+        let mut seq: Sequence<T, P> = Sequence::with_position();
+        seq.strategy = self.strategy;
+
+        for node in self.iter_with_tombstones() {
+            let node = match node.element_as_ref() {
+                None => Node {
+                    removed: node.removed,
+                    ..Node::new_empty(node.id, node.position().clone())
+                },
+                Some(element) => Node::new(node.id, node.position().clone(), element.clone()),
+            };
+            seq.nodes.push(node);
+        }
+        seq.len = self.len;
+        seq.removals = self.removals;
+        seq.next_id = self.next_id;
+        seq.journal = self.journal.clone();
+        seq
+    }
+}
+
+/// Returns for each item whether it is part of a longest strictly increasing subsequence of items. Takes O(n log n).
+fn longest_increasing_subsequence<P: Ord>(items: &[&P]) -> Vec<bool> {
+    // tails[i] is the index of the smallest item ending an increasing subsequence of length i + 1.
+    let mut tails: Vec<usize> = Vec::new();
+    let mut predecessors: Vec<Option<usize>> = vec![None; items.len()];
+
+    for (index, item) in items.iter().enumerate() {
+        let len = tails.partition_point(|&tail| items[tail] < *item);
+        predecessors[index] = len.checked_sub(1).map(|len| tails[len]);
+        if len == tails.len() {
+            tails.push(index);
+        } else {
+            tails[len] = index;
+        }
+    }
+
+    let mut keep = vec![false; items.len()];
+    let mut next = tails.last().copied();
+    while let Some(index) = next {
+        keep[index] = true;
+        next = predecessors[index];
+    }
+
+    keep
+}
+
+//
+// Entry
+//
+
+/// A view into the node at a position of a [`Sequence`], see [`entry()`](`Sequence::entry()`).
+#[derive(Debug)]
+pub enum Entry<'seq, T, P = Pos> {
+    /// There is an element at the position.
+    Occupied(OccupiedEntry<'seq, T, P>),
+    /// There is no node at the position.
+    Vacant(VacantEntry<'seq, T, P>),
+    /// There is an empty node left behind by a removed element at the position.
+    Tombstoned(TombstonedEntry<'seq, T, P>),
+}
+
+impl<'seq, T, P: Position> Entry<'seq, T, P> {
+    /// Returns the entry's position.
+    pub fn position(&self) -> &P {
+        match self {
+            Entry::Occupied(entry) => entry.position(),
+            Entry::Vacant(entry) => entry.position(),
+            Entry::Tombstoned(entry) => entry.position(),
+        }
+    }
+
+    /// Inserts default if there is no element at the position and returns the element.
+    /// An empty node at the position is revived, see [`TombstonedEntry::revive()`].
+    pub fn or_insert(self, default: T) -> &'seq mut T {
+        self.or_insert_with(|| default)
+    }
+
+    /// Inserts the result of default if there is no element at the position and returns the element,
+    /// see [`or_insert()`](`Entry::or_insert()`).
+    pub fn or_insert_with<F: FnOnce() -> T>(self, default: F) -> &'seq mut T {
+        match self {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => entry.insert(default()),
+            Entry::Tombstoned(entry) => entry.revive(default()),
+        }
+    }
+
+    /// Inserts the default value if there is no element at the position and returns the element,
+    /// see [`or_insert()`](`Entry::or_insert()`).
+    pub fn or_default(self) -> &'seq mut T
+    where
+        T: Default,
+    {
+        self.or_insert_with(T::default)
+    }
+
+    /// Modifies the element at the position, if any, and returns the entry.
+    pub fn and_modify<F: FnOnce(&mut T)>(mut self, f: F) -> Self {
+        if let Entry::Occupied(entry) = &mut self {
+            f(entry.get_mut());
+        }
+        self
+    }
+}
+
+/// A view into the node holding an element at a position of a [`Sequence`], see [`Entry`].
+#[derive(Debug)]
+pub struct OccupiedEntry<'seq, T, P = Pos> {
+    seq: &'seq mut Sequence<T, P>,
+    slot: usize,
+}
+
+impl<'seq, T, P: Position> OccupiedEntry<'seq, T, P> {
+    /// Returns the entry's position.
+    pub fn position(&self) -> &P {
+        self.seq.nodes[self.slot].position()
+    }
+
+    /// Returns the id of the element.
+    pub fn id(&self) -> NodeId {
+        self.seq.nodes[self.slot].id
+    }
+
+    /// Returns the element.
+    pub fn get(&self) -> &T {
+        self.seq.nodes[self.slot]
+            .element_as_ref()
+            .expect("occupied node holds an element")
+    }
+
+    /// Returns the element mutably.
+    pub fn get_mut(&mut self) -> &mut T {
+        self.seq.nodes[self.slot]
+            .element_as_mut()
+            .expect("occupied node holds an element")
+    }
+
+    /// Converts the entry into the element, bound to the lifetime of the sequence.
+    pub fn into_mut(self) -> &'seq mut T {
+        self.seq.nodes[self.slot]
+            .element_as_mut()
+            .expect("occupied node holds an element")
+    }
+
+    /// Replaces the element and returns the previous one. The element keeps its id.
+    pub fn insert(&mut self, element: T) -> T {
+        self.seq.record(Change::Updated {
+            pos: self.position().clone(),
+        });
+        std::mem::replace(self.get_mut(), element)
+    }
+
+    /// Removes and returns the element, leaving an empty node behind, see [`Sequence::remove()`].
+    pub fn remove(self) -> T {
+        self.seq.take(self.slot).expect("occupied node holds an element")
+    }
+}
+
+/// A view into a position of a [`Sequence`] without a node, see [`Entry`].
+#[derive(Debug)]
+pub struct VacantEntry<'seq, T, P = Pos> {
+    seq: &'seq mut Sequence<T, P>,
+    slot: usize,
+    position: P,
+}
+
+impl<'seq, T, P: Position> VacantEntry<'seq, T, P> {
+    /// Returns the entry's position.
+    pub fn position(&self) -> &P {
+        &self.position
+    }
+
+    /// Inserts the element with a new id at the position and returns it.
+    pub fn insert(self, element: T) -> &'seq mut T {
+        let id = self.seq.generate_id();
+        self.seq.record(Change::Inserted {
+            pos: self.position.clone(),
+        });
+        self.seq.nodes.insert(self.slot, Node::new(id, self.position, element));
+        self.seq.len += 1;
+
+        self.seq.nodes[self.slot]
+            .element_as_mut()
+            .expect("inserted node holds an element")
+    }
+}
+
+/// A view into an empty node left behind by a removed element at a position of a [`Sequence`], see [`Entry`].
+#[derive(Debug)]
+pub struct TombstonedEntry<'seq, T, P = Pos> {
+    seq: &'seq mut Sequence<T, P>,
+    slot: usize,
+}
+
+impl<'seq, T, P: Position> TombstonedEntry<'seq, T, P> {
+    /// Returns the entry's position.
+    pub fn position(&self) -> &P {
+        self.seq.nodes[self.slot].position()
+    }
+
+    /// Returns the id of the removed element.
+    pub fn id(&self) -> NodeId {
+        self.seq.nodes[self.slot].id
+    }
+
+    /// Inserts the element into the empty node and returns it.
+    /// Unlike [`Sequence::insert_at()`], the node keeps its id, so the element takes over the removed element's identity,
+    /// e.g. when replaying a removal and insertion of the same element.
+    pub fn revive(self, element: T) -> &'seq mut T {
+        self.seq.record(Change::Inserted {
+            pos: self.position().clone(),
+        });
+        let node = &mut self.seq.nodes[self.slot];
+        node.set(element);
+        self.seq.len += 1;
+
+        node.element_as_mut().expect("revived node holds an element")
+    }
+}
+
+//
+// Consuming Iterator
+//
+
+// IntoIterator for Sequence<T, P>
+// - consumes the `Sequence`
+// - removes all empty Nodes from `Sequence`
+// - iterates over remaining Nodes
+impl<T, P> IntoIterator for Sequence<T, P> {
+    type Item = Node<T, P>;
+    type IntoIter = std::vec::IntoIter<Node<T, P>>;
+
+    #[inline]
+    fn into_iter(mut self) -> Self::IntoIter {
+        self.nodes.retain(|node| node.element_as_ref().is_some());
+        self.nodes.into_iter()
+    }
+}
+
+//
+// Immutable Iterator
+//
+
+/// Iterator over the nodes of a [`Sequence`] in order, skipping empty nodes, see [`iter()`](`Sequence::iter()`).
+pub struct Iter<'iterator, T: 'iterator, P: 'iterator = Pos> {
+    nodes: std::slice::Iter<'iterator, Node<T, P>>,
+    len: usize,
+}
+
+impl<'iterator, T: 'iterator, P: 'iterator> Iterator for Iter<'iterator, T, P> {
+    type Item = &'iterator Node<T, P>;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        let node = self.nodes.find(|node| node.is_some())?;
+        self.len -= 1;
+        Some(node)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<'iterator, T: 'iterator, P: 'iterator> DoubleEndedIterator for Iter<'iterator, T, P> {
+    #[inline]
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let node = self.nodes.rfind(|node| node.is_some())?;
+        self.len -= 1;
+        Some(node)
+    }
+}
+
+impl<'iterator, T: 'iterator, P: 'iterator> ExactSizeIterator for Iter<'iterator, T, P> {}
+
+impl<'iterator, T: 'iterator, P: 'iterator> Clone for Iter<'iterator, T, P> {
+    fn clone(&self) -> Self {
+        Iter {
+            nodes: self.nodes.clone(),
+            len: self.len,
+        }
+    }
+}
+
+// Formats the remaining nodes, like the iterators of `BTreeMap`.
+impl<'iterator, T: Debug + 'iterator, P: Debug + 'iterator> Debug for Iter<'iterator, T, P> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_list().entries(self.clone()).finish()
+    }
+}
+
+impl<'iterator, T: 'iterator, P: 'iterator> IntoIterator for &'iterator Sequence<T, P> {
+    type Item = &'iterator Node<T, P>;
+    type IntoIter = Iter<'iterator, T, P>;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        Iter {
+            nodes: self.nodes.iter(),
+            len: self.len,
+        }
+    }
+}
+
+/// Iterator over the elements of a [`Sequence`] in order, see [`values()`](`Sequence::values()`).
+pub struct Values<'iterator, T: 'iterator, P: 'iterator = Pos>(Iter<'iterator, T, P>);
+
+impl<'iterator, T: 'iterator, P: 'iterator> Iterator for Values<'iterator, T, P> {
+    type Item = &'iterator T;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next()?.element_as_ref()
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+}
+
+impl<'iterator, T: 'iterator, P: 'iterator> DoubleEndedIterator for Values<'iterator, T, P> {
+    #[inline]
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.0.next_back()?.element_as_ref()
+    }
+}
+
+impl<'iterator, T: 'iterator, P: 'iterator> ExactSizeIterator for Values<'iterator, T, P> {}
+
+impl<'iterator, T: 'iterator, P: 'iterator> Clone for Values<'iterator, T, P> {
+    fn clone(&self) -> Self {
+        Values(self.0.clone())
+    }
+}
+
+impl<'iterator, T: Debug + 'iterator, P: 'iterator> Debug for Values<'iterator, T, P> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_list().entries(self.clone()).finish()
+    }
+}
+
+/// Iterator over the positions of the elements of a [`Sequence`] in order, see [`positions()`](`Sequence::positions()`).
+pub struct Positions<'iterator, T: 'iterator, P: 'iterator = Pos>(Iter<'iterator, T, P>);
+
+impl<'iterator, T: 'iterator, P: 'iterator> Iterator for Positions<'iterator, T, P> {
+    type Item = &'iterator P;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next().map(|node| node.position())
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+}
+
+impl<'iterator, T: 'iterator, P: 'iterator> DoubleEndedIterator for Positions<'iterator, T, P> {
+    #[inline]
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.0.next_back().map(|node| node.position())
+    }
+}
+
+impl<'iterator, T: 'iterator, P: 'iterator> ExactSizeIterator for Positions<'iterator, T, P> {}
+
+impl<'iterator, T: 'iterator, P: 'iterator> Clone for Positions<'iterator, T, P> {
+    fn clone(&self) -> Self {
+        Positions(self.0.clone())
+    }
+}
+
+impl<'iterator, T: 'iterator, P: Debug + 'iterator> Debug for Positions<'iterator, T, P> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_list().entries(self.clone()).finish()
+    }
+}
+
+/// Iterator over the indices and positions of the elements of a [`Sequence`] in order,
+/// see [`enumerate_positions()`](`Sequence::enumerate_positions()`).
+pub struct EnumeratePositions<'iterator, T: 'iterator, P: 'iterator = Pos> {
+    positions: Positions<'iterator, T, P>,
+    front: usize,
+}
+
+impl<'iterator, T: 'iterator, P: 'iterator> Iterator for EnumeratePositions<'iterator, T, P> {
+    type Item = (usize, &'iterator P);
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        let position = self.positions.next()?;
+        self.front += 1;
+        Some((self.front - 1, position))
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.positions.size_hint()
+    }
+}
+
+impl<'iterator, T: 'iterator, P: 'iterator> DoubleEndedIterator for EnumeratePositions<'iterator, T, P> {
+    #[inline]
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let position = self.positions.next_back()?;
+        // The remaining positions are at indices front..front + len.
+        Some((self.front + self.positions.len(), position))
+    }
+}
+
+impl<'iterator, T: 'iterator, P: 'iterator> ExactSizeIterator for EnumeratePositions<'iterator, T, P> {}
+
+impl<'iterator, T: 'iterator, P: 'iterator> Clone for EnumeratePositions<'iterator, T, P> {
+    fn clone(&self) -> Self {
+        EnumeratePositions {
+            positions: self.positions.clone(),
+            front: self.front,
+        }
+    }
+}
+
+impl<'iterator, T: 'iterator, P: Debug + 'iterator> Debug for EnumeratePositions<'iterator, T, P> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_list().entries(self.clone()).finish()
+    }
+}
+
+//
+// Range Iterator
+//
+
+/// Iterator over the nodes in a range of a [`Sequence`], skipping empty nodes,
+/// see [`range()`](`Sequence::range()`) and [`range_by_index()`](`Sequence::range_by_index()`).
+pub struct Range<'iterator, T: 'iterator, P: 'iterator = Pos>(std::slice::Iter<'iterator, Node<T, P>>);
+
+impl<'iterator, T: 'iterator, P: 'iterator> Iterator for Range<'iterator, T, P> {
+    type Item = &'iterator Node<T, P>;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.find(|node| node.is_some())
+    }
+}
+
+impl<'iterator, T: 'iterator, P: 'iterator> DoubleEndedIterator for Range<'iterator, T, P> {
+    #[inline]
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.0.rfind(|node| node.is_some())
+    }
+}
+
+impl<'iterator, T: 'iterator, P: 'iterator> Clone for Range<'iterator, T, P> {
+    fn clone(&self) -> Self {
+        Range(self.0.clone())
+    }
+}
+
+// Formats the remaining nodes, like the iterators of `BTreeMap`.
+impl<'iterator, T: Debug + 'iterator, P: Debug + 'iterator> Debug for Range<'iterator, T, P> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_list().entries(self.clone()).finish()
+    }
+}
+
+//
+// Mutable Iterator
+//
+
+/// Iterator over the elements of a [`Sequence`] in order, allowing to modify them,
+/// see [`iter_mut()`](`Sequence::iter_mut()`).
+pub struct IterMut<'iterator, T: 'iterator, P: 'iterator = Pos> {
+    nodes: std::slice::IterMut<'iterator, Node<T, P>>,
+    len: usize,
+}
+
+impl<'iterator, T: 'iterator, P: 'iterator> Iterator for IterMut<'iterator, T, P> {
+    type Item = &'iterator mut T;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        let element = self.nodes.find_map(|node| node.element.as_mut())?;
+        self.len -= 1;
+        Some(element)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<'iterator, T: 'iterator, P: 'iterator> DoubleEndedIterator for IterMut<'iterator, T, P> {
+    #[inline]
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let element = self.nodes.by_ref().rev().find_map(|node| node.element.as_mut())?;
+        self.len -= 1;
+        Some(element)
+    }
+}
+
+impl<'iterator, T: 'iterator, P: 'iterator> ExactSizeIterator for IterMut<'iterator, T, P> {}
+
+// Formats the remaining elements, like the iterators of `BTreeMap`.
+impl<'iterator, T: Debug + 'iterator, P: 'iterator> Debug for IterMut<'iterator, T, P> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let elements = self.nodes.as_slice().iter().filter_map(|node| node.element_as_ref());
+        f.debug_list().entries(elements).finish()
+    }
+}
+
+impl<'iterator, T: 'iterator, P: 'iterator> IntoIterator for &'iterator mut Sequence<T, P> {
+    type Item = &'iterator mut T;
+    type IntoIter = IterMut<'iterator, T, P>;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        IterMut {
+            nodes: self.nodes.iter_mut(),
+            len: self.len,
+        }
     }
 }
 
@@ -0,0 +1,253 @@
+//! `SqliteStore` persists a `Sequence` in a SQLite table, available with feature `sqlite`.
+//! Positions are stored as numerator and denominator, elements are stored by an implementation of `SqliteCodec`.
+
+use std::ops::RangeBounds;
+
+use rusqlite::types::{FromSql, FromSqlResult, Value, ValueRef};
+use rusqlite::{params, Connection, Params};
+
+use crate::error::SqliteError;
+use crate::sequence::Pos;
+use crate::store::{row_keys, SequenceStore};
+
+/// Encodes elements of a `Sequence` to values of the payload column and decodes them back.
+///
+/// Implemented for `String`, `Vec<u8>`, `i64`, `f64` and `bool`. Implement it for your own types,
+/// e.g. by encoding them as JSON text or as blob.
+pub trait SqliteCodec: Sized {
+    /// Encodes the element to the value stored in the payload column.
+    fn encode(&self) -> Value;
+
+    /// Decodes an element from a value read from the payload column.
+    fn decode(value: ValueRef<'_>) -> FromSqlResult<Self>;
+}
+
+impl SqliteCodec for String {
+    fn encode(&self) -> Value {
+        Value::Text(self.clone())
+    }
+
+    fn decode(value: ValueRef<'_>) -> FromSqlResult<Self> {
+        String::column_result(value)
+    }
+}
+
+impl SqliteCodec for Vec<u8> {
+    fn encode(&self) -> Value {
+        Value::Blob(self.clone())
+    }
+
+    fn decode(value: ValueRef<'_>) -> FromSqlResult<Self> {
+        Vec::column_result(value)
+    }
+}
+
+impl SqliteCodec for i64 {
+    fn encode(&self) -> Value {
+        Value::Integer(*self)
+    }
+
+    fn decode(value: ValueRef<'_>) -> FromSqlResult<Self> {
+        i64::column_result(value)
+    }
+}
+
+impl SqliteCodec for f64 {
+    fn encode(&self) -> Value {
+        Value::Real(*self)
+    }
+
+    fn decode(value: ValueRef<'_>) -> FromSqlResult<Self> {
+        f64::column_result(value)
+    }
+}
+
+impl SqliteCodec for bool {
+    fn encode(&self) -> Value {
+        Value::Integer(i64::from(*self))
+    }
+
+    fn decode(value: ValueRef<'_>) -> FromSqlResult<Self> {
+        bool::column_result(value)
+    }
+}
+
+/// A `SequenceStore` keeping the elements of a `Sequence` in a SQLite table with the columns `numerator`,
+/// `denominator` and `payload`.
+///
+/// Positions are `u64` fractions, while SQLite stores signed 64 bit integers. Numerators and denominators are stored
+/// bit for bit, so values above `i64::MAX` read as negative numbers in SQL. As floating point numbers can't tell
+/// close fractions apart, [`Sequence::load_from()`](`crate::Sequence::load_from()`) orders the rows by their exact fraction
+/// after loading instead of in SQL, and [`load_range()`](`SequenceStore::load_range()`) filters them after loading, too.
+///
+/// Positions are written in lowest terms, see [`Pos::reduced()`], so that equal positions like `1/2` and `2/4`
+/// map to the same row.
+///
+/// Batches are transactions. A failing upsert or delete rolls back the current batch, as the batch is repeated
+/// as a whole anyway, see [`Sequence::sync_to()`](`crate::Sequence::sync_to()`).
+///
+/// What you can do
+/// * Create a SqliteStore for a table of a connection: [new()](`SqliteStore::new()`)
+/// * Create the table if it does not exist: create_table()
+/// * Load a sequence from the table: [`Sequence::load_from()`](`crate::Sequence::load_from()`)
+/// * Replace the table's content with a sequence: [`Sequence::save_to()`](`crate::Sequence::save_to()`)
+/// * Write the changes recorded by a sequence's journal to the table: [`Sequence::sync_to()`](`crate::Sequence::sync_to()`)
+/// * Get the connection back: connection() and into_connection()
+#[derive(Debug)]
+pub struct SqliteStore {
+    conn: Connection,
+    table: String,
+}
+
+impl SqliteStore {
+    /// Creates a store for the given table of the connection. The name is quoted, so it may contain any character.
+    #[inline]
+    #[must_use]
+    pub fn new(conn: Connection, table: &str) -> Self {
+        SqliteStore {
+            conn,
+            table: table.to_string(),
+        }
+    }
+
+    /// Returns the name of the table.
+    #[inline]
+    #[must_use]
+    pub fn table(&self) -> &str {
+        &self.table
+    }
+
+    /// Returns the connection, e.g. to run your own queries on the table.
+    #[inline]
+    #[must_use]
+    pub fn connection(&self) -> &Connection {
+        &self.conn
+    }
+
+    /// Consumes the store and returns the connection.
+    #[inline]
+    #[must_use]
+    pub fn into_connection(self) -> Connection {
+        self.conn
+    }
+
+    /// Creates the table, unless it exists.
+    ///
+    /// The payload column has no declared type, so it stores any value an implementation of `SqliteCodec` encodes to.
+    pub fn create_table(&self) -> Result<(), SqliteError> {
+        self.conn.execute(
+            &format!(
+                "CREATE TABLE IF NOT EXISTS {} (
+                    numerator INTEGER NOT NULL,
+                    denominator INTEGER NOT NULL,
+                    payload,
+                    PRIMARY KEY (numerator, denominator)
+                )",
+                self.quoted_table()
+            ),
+            [],
+        )?;
+
+        Ok(())
+    }
+
+    /// Executes a statement, rolling back the current batch if it fails.
+    fn execute<P: Params>(&self, sql: &str, params: P) -> Result<(), SqliteError> {
+        let result = self.conn.prepare_cached(sql).and_then(|mut stmt| stmt.execute(params));
+        if result.is_err() && !self.conn.is_autocommit() {
+            // The statement's error is more helpful than one of the rollback.
+            let _ = self.conn.execute_batch("ROLLBACK");
+        }
+
+        result.map(|_| ()).map_err(SqliteError::from)
+    }
+
+    fn delete_rows(&self, position: &Pos) -> Result<(), SqliteError> {
+        let sql = format!(
+            "DELETE FROM {} WHERE numerator = ?1 AND denominator = ?2",
+            self.quoted_table()
+        );
+        for (num, denom) in row_keys(position) {
+            self.execute(&sql, params![num as i64, denom as i64])?;
+        }
+
+        Ok(())
+    }
+
+    fn quoted_table(&self) -> String {
+        format!("\"{}\"", self.table.replace('"', "\"\""))
+    }
+}
+
+impl<T: SqliteCodec> SequenceStore<T> for SqliteStore {
+    type Error = SqliteError;
+
+    fn load_all(&mut self) -> Result<Vec<(Pos, T)>, Self::Error> {
+        let mut stmt = self.conn.prepare_cached(&format!(
+            "SELECT numerator, denominator, payload FROM {}",
+            self.quoted_table()
+        ))?;
+        let mut rows = stmt.query([])?;
+
+        let mut elements = Vec::new();
+        while let Some(row) = rows.next()? {
+            let num: i64 = row.get(0)?;
+            let denom: i64 = row.get(1)?;
+            let value = row.get_ref(2)?;
+            let element = T::decode(value)
+                .map_err(|error| rusqlite::Error::FromSqlConversionFailure(2, value.data_type(), Box::new(error)))?;
+            elements.push((Pos::try_new(num as u64, denom as u64)?, element));
+        }
+
+        Ok(elements)
+    }
+
+    fn load_range<R: RangeBounds<Pos>>(&mut self, range: R) -> Result<Vec<(Pos, T)>, Self::Error> {
+        let mut elements = self.load_all()?;
+        elements.retain(|(position, _)| range.contains(position));
+
+        Ok(elements)
+    }
+
+    fn upsert(&mut self, position: &Pos, element: &T) -> Result<(), Self::Error> {
+        self.delete_rows(position)?;
+
+        let reduced = position.reduced();
+        self.execute(
+            &format!(
+                "INSERT INTO {} (numerator, denominator, payload) VALUES (?1, ?2, ?3)",
+                self.quoted_table()
+            ),
+            params![reduced.num() as i64, reduced.denom() as i64, element.encode()],
+        )
+    }
+
+    fn delete(&mut self, position: &Pos) -> Result<(), Self::Error> {
+        self.delete_rows(position)
+    }
+
+    fn begin(&mut self) -> Result<(), Self::Error> {
+        if !self.conn.is_autocommit() {
+            self.conn.execute_batch("ROLLBACK")?;
+        }
+        self.conn.execute_batch("BEGIN")?;
+
+        Ok(())
+    }
+
+    fn commit(&mut self) -> Result<(), Self::Error> {
+        if !self.conn.is_autocommit() {
+            self.conn.execute_batch("COMMIT")?;
+        }
+
+        Ok(())
+    }
+
+    fn clear(&mut self) -> Result<(), Self::Error> {
+        self.execute(&format!("DELETE FROM {}", self.quoted_table()), [])
+    }
+}
+
+#[cfg(test)]
+#[path = "tests/sqlite_tests.rs"]
+mod sqlite_tests;
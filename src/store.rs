@@ -0,0 +1,430 @@
+//! `SequenceStore` abstracts the storage a `Sequence` is loaded from and synced to, see
+//! [`Sequence::load_from()`](`crate::Sequence::load_from()`) and [`Sequence::sync_to()`](`crate::Sequence::sync_to()`).
+//! `MemoryStore` is the reference implementation, `FileStore` keeps the elements in a file.
+
+use std::collections::BTreeMap;
+#[cfg(any(feature = "sqlite", feature = "postgres"))]
+use std::collections::BTreeSet;
+use std::convert::Infallible;
+use std::error::Error;
+use std::fs;
+use std::io;
+use std::ops::RangeBounds;
+use std::path::{Path, PathBuf};
+
+use crate::sequence::{is_empty_range, Pos, Position};
+
+/// Storage of the elements of a `Sequence` keyed by their positions, e.g. a table of a database.
+///
+/// The ordering is owned by `Sequence`, so a store only needs to keep one element per position, compared by
+/// `Ord`. [`sync_to()`](`crate::Sequence::sync_to()`) upserts and deletes the changed positions within one batch,
+/// i.e. in between [`begin()`](`SequenceStore::begin()`) and [`commit()`](`SequenceStore::commit()`).
+/// Upserting and deleting the same position again has no further effect, so a failed batch is simply repeated.
+pub trait SequenceStore<T, P = Pos> {
+    /// Error returned by failing operations on the store.
+    type Error;
+
+    /// Returns all elements with their positions, in any order.
+    fn load_all(&mut self) -> Result<Vec<(P, T)>, Self::Error>;
+
+    /// Returns the elements with positions in range, with their positions, in any order.
+    /// Returns no elements if the range's start is after its end.
+    fn load_range<R: RangeBounds<P>>(&mut self, range: R) -> Result<Vec<(P, T)>, Self::Error>;
+
+    /// Stores the element at position, replacing the element stored at the position before.
+    fn upsert(&mut self, position: &P, element: &T) -> Result<(), Self::Error>;
+
+    /// Removes the element at position, if there is any.
+    fn delete(&mut self, position: &P) -> Result<(), Self::Error>;
+
+    /// Starts a batch of upserts and deletes, which takes effect on [`commit()`](`SequenceStore::commit()`).
+    /// Starting a batch drops any batch which has not been committed.
+    fn begin(&mut self) -> Result<(), Self::Error>;
+
+    /// Applies the upserts and deletes of the current batch.
+    fn commit(&mut self) -> Result<(), Self::Error>;
+
+    /// Removes all elements, e.g. before [`save_to()`](`crate::Sequence::save_to()`) stores a whole sequence.
+    /// The default implementation deletes each position returned by [`load_all()`](`SequenceStore::load_all()`),
+    /// so implement it if the store can do better.
+    fn clear(&mut self) -> Result<(), Self::Error> {
+        for (position, _) in self.load_all()? {
+            self.delete(&position)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Returns the numerator / denominator pairs a row of a database table at position may be keyed by.
+///
+/// Rows are keyed by numerator and denominator, while a `Sequence` compares positions by value. Rows are written
+/// in lowest terms, but rows written otherwise are found by the numerator and denominator they were loaded with.
+#[cfg(any(feature = "sqlite", feature = "postgres"))]
+pub(crate) fn row_keys(position: &Pos) -> BTreeSet<(u64, u64)> {
+    BTreeSet::from([(position.num(), position.denom()), position.reduced().into()])
+}
+
+/// A `SequenceStore` in memory, the reference implementation of the trait.
+///
+/// Upserts and deletes outside of a batch take effect at once, while those of a batch are held back until it is committed.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MemoryStore<T, P = Pos> {
+    elements: BTreeMap<P, T>,
+    batch: Option<Vec<(P, Option<T>)>>,
+}
+
+impl<T, P: Position> MemoryStore<T, P> {
+    /// Creates an empty store.
+    #[inline]
+    #[must_use]
+    pub fn new() -> Self {
+        MemoryStore {
+            elements: BTreeMap::new(),
+            batch: None,
+        }
+    }
+
+    /// Returns the number of stored elements, ignoring an uncommitted batch.
+    #[inline]
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.elements.len()
+    }
+
+    /// Returns true if no element is stored, ignoring an uncommitted batch.
+    #[inline]
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.elements.is_empty()
+    }
+
+    /// Returns a reference to the element stored at position, ignoring an uncommitted batch.
+    #[inline]
+    #[must_use]
+    pub fn get(&self, position: &P) -> Option<&T> {
+        self.elements.get(position)
+    }
+
+    /// Returns an iterator over the stored positions and elements, in order.
+    #[inline]
+    pub fn iter(&self) -> impl Iterator<Item = (&P, &T)> {
+        self.elements.iter()
+    }
+
+    fn apply(&mut self, position: P, element: Option<T>) {
+        match &mut self.batch {
+            Some(batch) => batch.push((position, element)),
+            None => match element {
+                Some(element) => {
+                    self.elements.insert(position, element);
+                }
+                None => {
+                    self.elements.remove(&position);
+                }
+            },
+        }
+    }
+}
+
+impl<T, P: Position> Default for MemoryStore<T, P> {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Clone, P: Position> SequenceStore<T, P> for MemoryStore<T, P> {
+    type Error = Infallible;
+
+    fn load_all(&mut self) -> Result<Vec<(P, T)>, Self::Error> {
+        Ok(self
+            .elements
+            .iter()
+            .map(|(position, element)| (position.clone(), element.clone()))
+            .collect())
+    }
+
+    fn load_range<R: RangeBounds<P>>(&mut self, range: R) -> Result<Vec<(P, T)>, Self::Error> {
+        if is_empty_range(&range) {
+            return Ok(Vec::new());
+        }
+
+        Ok(self
+            .elements
+            .range(range)
+            .map(|(position, element)| (position.clone(), element.clone()))
+            .collect())
+    }
+
+    fn upsert(&mut self, position: &P, element: &T) -> Result<(), Self::Error> {
+        self.apply(position.clone(), Some(element.clone()));
+        Ok(())
+    }
+
+    fn delete(&mut self, position: &P) -> Result<(), Self::Error> {
+        self.apply(position.clone(), None);
+        Ok(())
+    }
+
+    fn begin(&mut self) -> Result<(), Self::Error> {
+        self.batch = Some(Vec::new());
+        Ok(())
+    }
+
+    fn commit(&mut self) -> Result<(), Self::Error> {
+        for (position, element) in self.batch.take().unwrap_or_default() {
+            self.apply(position, element);
+        }
+        Ok(())
+    }
+}
+
+/// Encodes elements of a `Sequence` to bytes stored in a file by `FileStore` and decodes them back.
+///
+/// Implemented for `String`, `Vec<u8>`, `i64`, `f64` and `bool`. Implement it for your own types,
+/// e.g. by serializing them.
+pub trait FileCodec: Sized {
+    /// Encodes the element to bytes.
+    fn encode(&self) -> Vec<u8>;
+
+    /// Decodes an element from bytes.
+    fn decode(bytes: &[u8]) -> Result<Self, Box<dyn Error + Send + Sync>>;
+}
+
+impl FileCodec for String {
+    fn encode(&self) -> Vec<u8> {
+        self.as_bytes().to_vec()
+    }
+
+    fn decode(bytes: &[u8]) -> Result<Self, Box<dyn Error + Send + Sync>> {
+        Ok(String::from_utf8(bytes.to_vec())?)
+    }
+}
+
+impl FileCodec for Vec<u8> {
+    fn encode(&self) -> Vec<u8> {
+        self.clone()
+    }
+
+    fn decode(bytes: &[u8]) -> Result<Self, Box<dyn Error + Send + Sync>> {
+        Ok(bytes.to_vec())
+    }
+}
+
+impl FileCodec for i64 {
+    fn encode(&self) -> Vec<u8> {
+        self.to_be_bytes().to_vec()
+    }
+
+    fn decode(bytes: &[u8]) -> Result<Self, Box<dyn Error + Send + Sync>> {
+        Ok(i64::from_be_bytes(bytes.try_into()?))
+    }
+}
+
+impl FileCodec for f64 {
+    fn encode(&self) -> Vec<u8> {
+        self.to_be_bytes().to_vec()
+    }
+
+    fn decode(bytes: &[u8]) -> Result<Self, Box<dyn Error + Send + Sync>> {
+        Ok(f64::from_be_bytes(bytes.try_into()?))
+    }
+}
+
+impl FileCodec for bool {
+    fn encode(&self) -> Vec<u8> {
+        vec![u8::from(*self)]
+    }
+
+    fn decode(bytes: &[u8]) -> Result<Self, Box<dyn Error + Send + Sync>> {
+        match bytes {
+            [0] => Ok(false),
+            [1] => Ok(true),
+            _ => Err("invalid bool".into()),
+        }
+    }
+}
+
+// Files start with this header, followed by one record per element: numerator and denominator as u64,
+// the length of the encoded element as u64, all big endian, and the encoded element.
+const FILE_HEADER: &[u8] = b"kodiak-sets/sequence/1\n";
+
+/// A `SequenceStore` keeping the elements in a file.
+///
+/// The store holds all elements in a `MemoryStore` and rewrites the file whenever an upsert or delete outside of a batch,
+/// or a batch is committed. The elements held in memory only change once the file is written, so they match the file
+/// even if writing fails. The file is replaced atomically by writing a temporary file next to it and renaming it,
+/// so it holds either the elements before or after a commit. Prefer batches, as every write takes O(n).
+#[derive(Clone, Debug, PartialEq)]
+pub struct FileStore<T> {
+    path: PathBuf,
+    memory: MemoryStore<T>,
+}
+
+impl<T: FileCodec> FileStore<T> {
+    /// Opens the store kept in the file at path, or creates an empty store if there is no file yet.
+    /// The file is created by the first write.
+    ///
+    /// # Errors
+    /// Returns an error if the file cannot be read, or if it is not a file written by `FileStore<T>`,
+    /// with kind `InvalidData` in the latter case.
+    pub fn open<Q: AsRef<Path>>(path: Q) -> io::Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let elements = match fs::read(&path) {
+            Ok(bytes) => Self::decode(&bytes)?,
+            Err(error) if error.kind() == io::ErrorKind::NotFound => BTreeMap::new(),
+            Err(error) => return Err(error),
+        };
+
+        Ok(FileStore {
+            path,
+            memory: MemoryStore { elements, batch: None },
+        })
+    }
+
+    /// Returns the path of the file.
+    #[inline]
+    #[must_use]
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Returns the number of stored elements, ignoring an uncommitted batch.
+    #[inline]
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.memory.len()
+    }
+
+    /// Returns true if no element is stored, ignoring an uncommitted batch.
+    #[inline]
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.memory.is_empty()
+    }
+
+    /// Returns a reference to the element stored at position, ignoring an uncommitted batch.
+    #[inline]
+    #[must_use]
+    pub fn get(&self, position: &Pos) -> Option<&T> {
+        self.memory.get(position)
+    }
+
+    /// Writes the elements of memory to the file.
+    fn write(&self, memory: &MemoryStore<T>) -> io::Result<()> {
+        let mut bytes = FILE_HEADER.to_vec();
+        for (position, element) in memory.iter() {
+            let element = element.encode();
+            bytes.extend_from_slice(&position.num().to_be_bytes());
+            bytes.extend_from_slice(&position.denom().to_be_bytes());
+            bytes.extend_from_slice(&(element.len() as u64).to_be_bytes());
+            bytes.extend_from_slice(&element);
+        }
+
+        let mut tmp = self.path.clone().into_os_string();
+        tmp.push(".tmp");
+        fs::write(&tmp, bytes)?;
+        fs::rename(&tmp, &self.path)
+    }
+
+    fn decode(bytes: &[u8]) -> io::Result<BTreeMap<Pos, T>> {
+        let invalid = |error: Box<dyn Error + Send + Sync>| io::Error::new(io::ErrorKind::InvalidData, error);
+        let truncated = || invalid("truncated record".into());
+
+        let mut rest = bytes
+            .strip_prefix(FILE_HEADER)
+            .ok_or_else(|| invalid("missing header".into()))?;
+        let next_u64 = |rest: &mut &[u8]| -> io::Result<u64> {
+            let (bytes, tail) = rest.split_first_chunk::<8>().ok_or_else(truncated)?;
+            *rest = tail;
+            Ok(u64::from_be_bytes(*bytes))
+        };
+
+        let mut elements = BTreeMap::new();
+        while !rest.is_empty() {
+            let num = next_u64(&mut rest)?;
+            let denom = next_u64(&mut rest)?;
+            let len = next_u64(&mut rest)?;
+            let (element, tail) = usize::try_from(len)
+                .ok()
+                .and_then(|len| rest.split_at_checked(len))
+                .ok_or_else(truncated)?;
+            rest = tail;
+
+            let position = Pos::try_new(num, denom).map_err(|error| invalid(Box::new(error)))?;
+            elements.insert(position, T::decode(element).map_err(invalid)?);
+        }
+
+        Ok(elements)
+    }
+}
+
+impl<T: FileCodec + Clone> FileStore<T> {
+    /// Applies f to the elements, or to a copy of them which is written to the file unless a batch is open.
+    /// The copy replaces the elements once the file is written, so that they never get ahead of the file.
+    fn update<F: FnOnce(&mut MemoryStore<T>)>(&mut self, f: F) -> io::Result<()> {
+        if self.memory.batch.is_some() {
+            f(&mut self.memory);
+            return Ok(());
+        }
+
+        let mut memory = self.memory.clone();
+        f(&mut memory);
+        self.write(&memory)?;
+        self.memory = memory;
+
+        Ok(())
+    }
+}
+
+// Operations on the `MemoryStore` can't fail, so only writing the file might.
+impl<T: FileCodec + Clone> SequenceStore<T> for FileStore<T> {
+    type Error = io::Error;
+
+    fn load_all(&mut self) -> Result<Vec<(Pos, T)>, Self::Error> {
+        let Ok(elements) = self.memory.load_all();
+        Ok(elements)
+    }
+
+    fn load_range<R: RangeBounds<Pos>>(&mut self, range: R) -> Result<Vec<(Pos, T)>, Self::Error> {
+        let Ok(elements) = self.memory.load_range(range);
+        Ok(elements)
+    }
+
+    fn upsert(&mut self, position: &Pos, element: &T) -> Result<(), Self::Error> {
+        self.update(|memory| {
+            let Ok(()) = memory.upsert(position, element);
+        })
+    }
+
+    fn delete(&mut self, position: &Pos) -> Result<(), Self::Error> {
+        self.update(|memory| {
+            let Ok(()) = memory.delete(position);
+        })
+    }
+
+    fn begin(&mut self) -> Result<(), Self::Error> {
+        let Ok(()) = self.memory.begin();
+        Ok(())
+    }
+
+    /// Writes the elements with the batch applied to the file first, and applies the batch to the elements afterwards.
+    /// If writing fails, the batch stays open, so committing it can be repeated.
+    fn commit(&mut self) -> Result<(), Self::Error> {
+        if self.memory.batch.is_none() {
+            return Ok(());
+        }
+
+        let mut memory = self.memory.clone();
+        let Ok(()) = memory.commit();
+        self.write(&memory)?;
+        self.memory = memory;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+#[path = "tests/store_tests.rs"]
+mod store_tests;
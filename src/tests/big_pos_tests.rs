@@ -0,0 +1,204 @@
+#[cfg(test)]
+#[allow(clippy::bool_assert_comparison)]
+mod tests {
+    use std::collections::hash_map::DefaultHasher;
+    use std::hash::{Hash, Hasher};
+
+    use num_bigint::BigUint;
+
+    use crate::big_pos::BigPos;
+    use crate::error::PosError;
+    use crate::sequence::{Pos, Position, PositionStrategy, Sequence};
+
+    // Helpers to setup test
+    fn big(num: u64, denom: u64) -> BigPos {
+        BigPos::try_new(num, denom).unwrap()
+    }
+
+    fn hash_of(pos: &BigPos) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        pos.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    #[test]
+    fn test_try_new() {
+        let pos = big(3, 2);
+
+        assert_eq!(pos.num(), &BigUint::from(3u32));
+        assert_eq!(pos.denom(), &BigUint::from(2u32));
+        assert_eq!(BigPos::try_new(1u32, 0u32), Err(PosError::ZeroDenominator));
+    }
+
+    #[test]
+    fn test_try_from_into() {
+        let pair = (BigUint::from(2u32), BigUint::from(4u32));
+        let pos = BigPos::try_from(pair.clone()).unwrap();
+
+        assert_eq!(<(BigUint, BigUint)>::from(pos), pair);
+        assert_eq!(
+            BigPos::try_from((BigUint::from(1u32), BigUint::ZERO)),
+            Err(PosError::ZeroDenominator)
+        );
+    }
+
+    #[cfg(feature = "serde-derive")]
+    #[test]
+    fn test_deserialize_validates() {
+        use serde::de::value::{Error, SeqDeserializer};
+        use serde::Deserialize;
+
+        // BigUint is deserialized from its u32 digits, least significant first.
+        let deserialize = |pair: [Vec<u32>; 2]| BigPos::deserialize(SeqDeserializer::<_, Error>::new(pair.into_iter()));
+
+        assert_eq!(deserialize([vec![2], vec![4]]).unwrap().to_string(), "2/4");
+        assert_eq!(deserialize([vec![1], vec![]]).is_err(), true);
+    }
+
+    #[test]
+    fn test_from_pos() {
+        let pos = BigPos::from(Pos::try_new(u64::MAX, 3).unwrap());
+
+        assert_eq!(pos.num(), &BigUint::from(u64::MAX));
+        assert_eq!(pos.denom(), &BigUint::from(3u32));
+    }
+
+    #[test]
+    fn test_ord_eq_hash() {
+        assert_eq!(big(1, 2), big(2, 4));
+        assert_eq!(hash_of(&big(1, 2)), hash_of(&big(2, 4)));
+        assert_eq!(big(1, 3) < big(1, 2), true);
+        assert_eq!(big(u64::MAX - 1, u64::MAX) < big(u64::MAX, u64::MAX), true);
+        assert_eq!(big(0, 5), big(0, 1));
+    }
+
+    #[test]
+    fn test_reduced() {
+        assert_eq!(big(6, 4).reduced().num(), &BigUint::from(3u32));
+        assert_eq!(big(6, 4).reduced().denom(), &BigUint::from(2u32));
+        assert_eq!(big(6, 4).is_reduced(), false);
+        assert_eq!(big(3, 2).is_reduced(), true);
+    }
+
+    #[test]
+    fn test_display_debug() {
+        assert_eq!(format!("{}", big(3, 2)), "3/2");
+        assert_eq!(format!("{:?}", big(3, 2)), "3/2");
+        assert_eq!(format!("{}", BigPos::default()), "1/1");
+    }
+
+    #[test]
+    fn test_checked_mid() {
+        assert_eq!(BigPos::checked_mid(&big(1, 1), &big(2, 1)), Some(big(3, 2)));
+        assert_eq!(BigPos::checked_mid(&big(1, 2), &big(2, 4)), None);
+
+        // Does not overflow unlike `Pos::checked_mid()`.
+        let mid = BigPos::checked_mid(&big(u64::MAX - 1, 1), &big(u64::MAX, 1)).unwrap();
+        assert_eq!(mid.num(), &(BigUint::from(u64::MAX) * 2u32 - 1u32));
+        assert_eq!(mid.denom(), &BigUint::from(2u32));
+    }
+
+    #[test]
+    fn test_simplest_between() {
+        assert_eq!(BigPos::simplest_between(&big(1, 1), &big(2, 1)), Some(big(3, 2)));
+        assert_eq!(BigPos::simplest_between(&big(7, 5), &big(6, 5)), Some(big(4, 3)));
+        assert_eq!(BigPos::simplest_between(&big(1, 3), &big(1, 2)), Some(big(2, 5)));
+        assert_eq!(BigPos::simplest_between(&big(1, 1), &big(5, 1)), Some(big(2, 1)));
+        assert_eq!(BigPos::simplest_between(&big(1, 2), &big(2, 4)), None);
+    }
+
+    #[test]
+    fn test_simplest_between_matches_pos() {
+        for (first, second) in [
+            ((1, 7), (2, 9)),
+            ((13, 21), (21, 34)),
+            ((0, 1), (1, 1000)),
+            ((999, 1000), (1, 1)),
+        ] {
+            let pos = Pos::simplest_between(
+                Pos::try_new(first.0, first.1).unwrap(),
+                Pos::try_new(second.0, second.1).unwrap(),
+            );
+            let big_pos = BigPos::simplest_between(&big(first.0, first.1), &big(second.0, second.1));
+
+            assert_eq!(big_pos, pos.map(BigPos::from));
+        }
+    }
+
+    #[test]
+    fn test_between() {
+        let (lower, upper) = (big(1, 1), big(2, 1));
+
+        assert_eq!(BigPos::between(None, None, PositionStrategy::Mediant), Some(big(1, 1)));
+        assert_eq!(
+            BigPos::between(Some(&lower), None, PositionStrategy::Mediant),
+            Some(big(2, 1))
+        );
+        assert_eq!(
+            BigPos::between(None, Some(&lower), PositionStrategy::Mediant),
+            Some(big(1, 2))
+        );
+        assert_eq!(
+            BigPos::between(Some(&lower), Some(&upper), PositionStrategy::Mediant),
+            Some(big(3, 2))
+        );
+        assert_eq!(BigPos::between(Some(&lower), Some(&lower), PositionStrategy::Mediant), None);
+
+        assert_eq!(BigPos::between(None, None, PositionStrategy::SternBrocot), Some(big(1, 1)));
+        assert_eq!(
+            BigPos::between(Some(&lower), None, PositionStrategy::SternBrocot),
+            Some(big(2, 1))
+        );
+        assert_eq!(
+            BigPos::between(None, Some(&lower), PositionStrategy::SternBrocot),
+            Some(big(1, 2))
+        );
+        assert_eq!(
+            BigPos::between(Some(&upper), Some(&lower), PositionStrategy::SternBrocot),
+            None
+        );
+    }
+
+    #[test]
+    fn test_spread() {
+        assert_eq!(BigPos::spread(None, None, 0), Some(Vec::new()));
+        assert_eq!(BigPos::spread(None, None, 3), Some(vec![big(1, 1), big(2, 1), big(3, 1)]));
+        assert_eq!(BigPos::spread(Some(&big(5, 2)), None, 2), Some(vec![big(3, 1), big(4, 1)]));
+        assert_eq!(
+            BigPos::spread(Some(&big(1, 1)), Some(&big(2, 1)), 3),
+            Some(vec![big(5, 4), big(3, 2), big(7, 4)])
+        );
+        assert_eq!(BigPos::spread(Some(&big(2, 1)), Some(&big(1, 1)), 1), None);
+    }
+
+    #[test]
+    fn test_spread_matches_pos() {
+        let (lower, upper) = (Pos::try_new(1, 3).unwrap(), Pos::try_new(2, 5).unwrap());
+
+        for n in 1..20 {
+            let pos = <Pos as Position>::spread(Some(&lower), Some(&upper), n).unwrap();
+            let big_pos = BigPos::spread(Some(&BigPos::from(lower)), Some(&BigPos::from(upper)), n).unwrap();
+
+            assert_eq!(big_pos, pos.into_iter().map(BigPos::from).collect::<Vec<_>>());
+        }
+    }
+
+    #[test]
+    fn test_sequence_never_exhausted() {
+        let mut seq: Sequence<usize, BigPos> = Sequence::with_position();
+        seq.push(0);
+        seq.push(1);
+
+        // Alternating inserts grow denominators like Fibonacci numbers, which would exhaust `Pos` after about 90 inserts.
+        for i in 0..1_000 {
+            let index = if i % 2 == 0 { 1 } else { 2 };
+            assert_eq!(seq.try_insert(index, i).is_ok(), true);
+        }
+
+        assert_eq!(seq.len(), 1_002);
+        assert_eq!(seq.position_from(0) < seq.position_from(1), true);
+
+        seq.rebalance();
+        assert_eq!(seq.position_from(1_001), Some(big(1_002, 1)));
+    }
+}
@@ -0,0 +1,90 @@
+#[cfg(test)]
+#[allow(clippy::bool_assert_comparison)]
+mod tests {
+    use crate::btree_sequence::BTreeSequence;
+    use crate::order_key::OrderKey;
+    use crate::sequence::{Node, Pos};
+    use std::ops::Bound;
+
+    // Operations shared with `Sequence` are tested for both, see `shared_sequence_tests`.
+    // The tests below cover what is specific to `BTreeSequence`.
+
+    pub fn setup_seq_abc() -> BTreeSequence<String> {
+        let mut seq: BTreeSequence<String> = BTreeSequence::new();
+
+        seq.push("A".to_string());
+        seq.push("B".to_string());
+        seq.push("C".to_string());
+
+        seq
+    }
+
+    fn elements<'a>(nodes: impl Iterator<Item = &'a Node<String, Pos>>) -> Vec<&'a str> {
+        nodes.map(|node| node.element_as_ref().unwrap().as_str()).collect()
+    }
+
+    #[test]
+    fn test_range() {
+        let mut seq = setup_seq_abc();
+
+        assert_eq!(elements(seq.range(Pos::new(3, 2)..)), vec!["B", "C"]);
+        assert_eq!(elements(seq.range(..=Pos::new(2, 1))), vec!["A", "B"]);
+        assert_eq!(seq.range(Pos::new(4, 1)..).next(), None);
+
+        seq.remove(1);
+        assert_eq!(elements(seq.range(Pos::new(3, 2)..)), vec!["C"]);
+        assert_eq!(elements(seq.range(..).rev()), vec!["C", "A"]);
+    }
+
+    #[test]
+    fn test_range_inverted() {
+        let seq = setup_seq_abc();
+
+        assert_eq!(seq.range(Pos::new(3, 1)..Pos::new(1, 1)).next(), None);
+        assert_eq!(seq.range(Pos::new(2, 1)..Pos::new(2, 1)).next(), None);
+        assert_eq!(
+            seq.range((Bound::Excluded(Pos::new(2, 1)), Bound::Excluded(Pos::new(2, 1))))
+                .next(),
+            None
+        );
+        assert_eq!(seq.range(Pos::new(2, 1)..=Pos::new(4, 2)).count(), 1);
+    }
+
+    #[test]
+    fn test_iter_debug() {
+        let mut seq = setup_seq_abc();
+        seq.remove(1);
+
+        let mut iter = seq.iter();
+        assert_eq!(
+            format!("{:?}", iter.clone()),
+            format!("{:?}", vec![seq.node(0).unwrap(), seq.node(1).unwrap()])
+        );
+
+        iter.next();
+        assert_eq!(format!("{:?}", iter), format!("{:?}", vec![seq.node(1).unwrap()]));
+    }
+
+    #[test]
+    fn test_into_iter() {
+        let seq = setup_seq_abc();
+
+        assert_eq!(elements((&seq).into_iter()), vec!["A", "B", "C"]);
+
+        let nodes: Vec<Node<String, Pos>> = seq.into_iter().collect();
+        assert_eq!(nodes[0].position(), &Pos::new(1, 1));
+        assert_eq!(nodes.len(), 3);
+    }
+
+    #[test]
+    fn test_order_key() {
+        let mut seq: BTreeSequence<String, OrderKey> = BTreeSequence::with_position();
+
+        seq.push("B".to_string());
+        seq.insert(0, "A".to_string());
+        seq.push("C".to_string());
+
+        let keys: Vec<&str> = seq.iter().map(|node| node.position().as_str()).collect();
+        assert_eq!(keys, vec!["Zz", "a0", "a1"]);
+    }
+}
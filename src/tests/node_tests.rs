@@ -1,14 +1,15 @@
 #[cfg(test)]
+#[allow(clippy::bool_assert_comparison)]
 mod tests {
-    use crate::sequence::{Node, Pos};
+    use crate::sequence::{Node, NodeId, Pos};
 
     // Helpers to setup test
     pub fn setup_node() -> Node<String> {
-        Node::new(Pos::default(), "A".to_string())
+        Node::new(NodeId::default(), Pos::default(), "A".to_string())
     }
 
     pub fn setup_node_empty() -> Node<String> {
-        Node::new_empty(Pos::default())
+        Node::new_empty(NodeId::default(), Pos::default())
     }
 
     #[test]
@@ -31,7 +32,7 @@ mod tests {
     fn test_position() {
         let node = setup_node();
 
-        assert_eq!(node.position(), Pos::default());
+        assert_eq!(node.position(), &Pos::default());
     }
 
     #[test]
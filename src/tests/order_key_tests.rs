@@ -0,0 +1,181 @@
+#[cfg(test)]
+#[allow(clippy::bool_assert_comparison)]
+mod tests {
+    use crate::error::OrderKeyError;
+    use crate::order_key::OrderKey;
+    use crate::sequence::{Position, PositionStrategy};
+
+    // Helpers to setup test
+    fn key(key: &str) -> OrderKey {
+        OrderKey::try_new(key).unwrap()
+    }
+
+    fn generate(lower: Option<&str>, upper: Option<&str>) -> Option<String> {
+        let lower = lower.map(key);
+        let upper = upper.map(key);
+
+        OrderKey::generate(lower.as_ref(), upper.as_ref()).map(String::from)
+    }
+
+    #[test]
+    fn test_try_new() {
+        assert_eq!(OrderKey::try_new("a0").unwrap().as_str(), "a0");
+        assert_eq!(OrderKey::try_new("Zz").unwrap().as_str(), "Zz");
+        assert_eq!(OrderKey::try_new("a0V").unwrap().as_str(), "a0V");
+        assert_eq!(OrderKey::try_new("b125").unwrap().as_str(), "b125");
+    }
+
+    #[test]
+    fn test_try_new_invalid() {
+        assert_eq!(OrderKey::try_new(""), Err(OrderKeyError::InvalidLength));
+        assert_eq!(OrderKey::try_new("a"), Err(OrderKeyError::InvalidLength));
+        assert_eq!(OrderKey::try_new("b1"), Err(OrderKeyError::InvalidLength));
+        assert_eq!(OrderKey::try_new("0"), Err(OrderKeyError::InvalidChar('0')));
+        assert_eq!(OrderKey::try_new("a-"), Err(OrderKeyError::InvalidChar('-')));
+        assert_eq!(OrderKey::try_new("aä"), Err(OrderKeyError::InvalidChar('ä')));
+        assert_eq!(OrderKey::try_new("a00"), Err(OrderKeyError::TrailingZero));
+        assert_eq!(OrderKey::try_new("A00000000000000000000000000"), Err(OrderKeyError::Reserved));
+    }
+
+    #[test]
+    fn test_try_from() {
+        assert_eq!(OrderKey::try_from("a1"), Ok(key("a1")));
+        assert_eq!(OrderKey::try_from("a1".to_string()), Ok(key("a1")));
+        assert_eq!(OrderKey::try_from("a10".to_string()), Err(OrderKeyError::TrailingZero));
+        assert_eq!(String::from(key("a1")), "a1".to_string());
+    }
+
+    #[test]
+    fn test_default() {
+        assert_eq!(OrderKey::default(), key("a0"));
+    }
+
+    #[test]
+    fn test_display() {
+        assert_eq!(format!("{}", key("a0V")), "a0V");
+    }
+
+    #[test]
+    fn test_generate() {
+        assert_eq!(generate(None, None), Some("a0".to_string()));
+        assert_eq!(generate(None, Some("a0")), Some("Zz".to_string()));
+        assert_eq!(generate(None, Some("Zz")), Some("Zy".to_string()));
+        assert_eq!(generate(Some("a0"), None), Some("a1".to_string()));
+        assert_eq!(generate(Some("a1"), None), Some("a2".to_string()));
+        assert_eq!(generate(Some("a0"), Some("a1")), Some("a0V".to_string()));
+        assert_eq!(generate(Some("a1"), Some("a2")), Some("a1V".to_string()));
+        assert_eq!(generate(Some("a0V"), Some("a1")), Some("a0l".to_string()));
+        assert_eq!(generate(Some("Zz"), Some("a0")), Some("ZzV".to_string()));
+        assert_eq!(generate(Some("Zz"), Some("a1")), Some("a0".to_string()));
+        assert_eq!(generate(None, Some("Y00")), Some("Xzzz".to_string()));
+        assert_eq!(generate(Some("bzz"), None), Some("c000".to_string()));
+        assert_eq!(generate(Some("a0"), Some("a0V")), Some("a0G".to_string()));
+        assert_eq!(generate(Some("a0"), Some("a0G")), Some("a08".to_string()));
+        assert_eq!(generate(Some("b125"), Some("b129")), Some("b127".to_string()));
+        assert_eq!(generate(Some("a0"), Some("a1V")), Some("a1".to_string()));
+        assert_eq!(generate(Some("Zz"), Some("a01")), Some("a0".to_string()));
+        assert_eq!(generate(None, Some("a0V")), Some("a0".to_string()));
+        assert_eq!(generate(None, Some("b999")), Some("b99".to_string()));
+    }
+
+    #[test]
+    fn test_generate_limits() {
+        let smallest = "A000000000000000000000000001";
+        let largest = "zzzzzzzzzzzzzzzzzzzzzzzzzzz";
+
+        assert_eq!(
+            generate(None, Some(smallest)),
+            Some("A000000000000000000000000000V".to_string())
+        );
+        assert_eq!(generate(Some("zzzzzzzzzzzzzzzzzzzzzzzzzzy"), None), Some(largest.to_string()));
+        assert_eq!(
+            generate(Some(largest), None),
+            Some("zzzzzzzzzzzzzzzzzzzzzzzzzzzV".to_string())
+        );
+    }
+
+    #[test]
+    fn test_before_past_smallest_integer() {
+        let mut key = key("A00000000000000000000000002");
+
+        for _ in 0..100 {
+            let before = key.before();
+            assert_eq!(before < key, true);
+            assert_eq!(OrderKey::try_new(before.as_str()), Ok(before.clone()));
+            key = before;
+        }
+        assert_eq!(key.as_str().starts_with("A00000000000000000000000000"), true);
+    }
+
+    #[test]
+    fn test_generate_unordered() {
+        assert_eq!(generate(Some("a1"), Some("a0")), None);
+        assert_eq!(generate(Some("a1"), Some("a1")), None);
+    }
+
+    #[test]
+    fn test_between() {
+        assert_eq!(OrderKey::between(&key("a0"), &key("a1")), Some(key("a0V")));
+        assert_eq!(OrderKey::between(&key("a1"), &key("a0")), Some(key("a0V")));
+        assert_eq!(OrderKey::between(&key("a1"), &key("a1")), None);
+    }
+
+    #[test]
+    fn test_before_after() {
+        assert_eq!(key("a0").before(), key("Zz"));
+        assert_eq!(key("a0").after(), key("a1"));
+        assert_eq!(key("a0V").before(), key("a0"));
+        assert_eq!(key("a0V").after(), key("a1"));
+    }
+
+    #[test]
+    fn test_repeated_inserts_stay_ordered() {
+        let lower = key("a0");
+        let mut upper = key("a1");
+
+        for _ in 0..1_000 {
+            let key = OrderKey::between(&lower, &upper).unwrap();
+            assert_eq!(lower < key && key < upper, true);
+            upper = key;
+        }
+
+        let upper = key("a1");
+        let mut lower = key("a0");
+
+        for _ in 0..1_000 {
+            let key = OrderKey::between(&lower, &upper).unwrap();
+            assert_eq!(lower < key && key < upper, true);
+            lower = key;
+        }
+    }
+
+    #[test]
+    fn test_position_spread() {
+        let positions = <OrderKey as Position>::spread(None, None, 5).unwrap();
+        assert_eq!(positions.len(), 5);
+        assert_eq!(positions.windows(2).all(|pair| pair[0] < pair[1]), true);
+
+        let (lower, upper) = (key("a0"), key("a1"));
+        let positions = <OrderKey as Position>::spread(Some(&lower), Some(&upper), 10).unwrap();
+        assert_eq!(positions.len(), 10);
+        assert_eq!(positions.first().unwrap() > &lower, true);
+        assert_eq!(positions.last().unwrap() < &upper, true);
+        assert_eq!(positions.windows(2).all(|pair| pair[0] < pair[1]), true);
+
+        assert_eq!(<OrderKey as Position>::spread(Some(&upper), Some(&lower), 1), None);
+        assert_eq!(
+            <OrderKey as Position>::spread(Some(&upper), Some(&lower), 0),
+            Some(Vec::new())
+        );
+    }
+
+    #[test]
+    fn test_position_between_ignores_strategy() {
+        let (lower, upper) = (key("a0"), key("a1"));
+
+        assert_eq!(
+            <OrderKey as Position>::between(Some(&lower), Some(&upper), PositionStrategy::Mediant),
+            <OrderKey as Position>::between(Some(&lower), Some(&upper), PositionStrategy::SternBrocot)
+        );
+    }
+}
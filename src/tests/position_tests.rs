@@ -1,6 +1,19 @@
 #[cfg(test)]
+#[allow(clippy::bool_assert_comparison)]
 mod tests {
-    use crate::sequence::{Min, Pos};
+    use crate::error::PosError;
+    use crate::sequence::{Min, Pos, PositionStrategy};
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+    use std::collections::hash_map::DefaultHasher;
+    use std::collections::BTreeMap;
+    use std::hash::{Hash, Hasher};
+
+    fn hash_of(pos: Pos) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        pos.hash(&mut hasher);
+        hasher.finish()
+    }
 
     #[test]
     fn test_new_position_5_1() {
@@ -53,20 +66,11 @@ mod tests {
     }
 
     #[test]
-    fn test_mid_1_1_2_1() {
-        let lhs = Pos::new(1, 1);
-        let rhs = Pos::new(2, 1);
-
-        assert_eq!(Pos::mid(lhs, rhs), Pos { num: 3, denom: 2 });
-        assert_eq!(Pos::mid(rhs, lhs), Pos { num: 3, denom: 2 });
-    }
-
-    #[test]
-    fn test_mid_min_1_1() {
+    fn test_checked_mid_min_1_1() {
         let pos = Pos::new(1, 1);
 
-        let mid = Pos::mid(Pos::MIN, pos);
-        assert_eq!(mid, Pos { num: 1, denom: 2 });
+        let mid = Pos::checked_mid(Pos::MIN, pos);
+        assert_eq!(mid, Some(Pos { num: 1, denom: 2 }));
     }
 
     #[test]
@@ -113,6 +117,12 @@ mod tests {
         );
     }
 
+    #[test]
+    #[should_panic(expected = "overflow")]
+    fn test_add_overflow() {
+        let _ = Pos::new(u64::MAX, 1) + Pos::new(1, 1);
+    }
+
     #[test]
     fn test_partial_eq_n5d1_n5d1() {
         let pos1 = Pos::new(5, 1);
@@ -167,6 +177,461 @@ mod tests {
         assert_eq!(pos1 <= pos2, true);
     }
 
+    #[test]
+    fn test_partial_ord_n1d2_n1d3() {
+        // Compares against other's denominator, not self's.
+        let pos1 = Pos::new(1, 2);
+        let pos2 = Pos::new(1, 3);
+
+        assert_eq!(pos1 > pos2, true);
+        assert_eq!(pos2 < pos1, true);
+    }
+
+    #[test]
+    fn test_ord_beyond_f64_precision() {
+        // Both fractions map to the same f64 but differ exactly.
+        let pos1 = Pos::new(u64::MAX - 1, u64::MAX);
+        let pos2 = Pos::new(u64::MAX - 2, u64::MAX - 1);
+
+        assert_eq!(
+            (u64::MAX - 1) as f64 / u64::MAX as f64,
+            (u64::MAX - 2) as f64 / (u64::MAX - 1) as f64
+        );
+        assert_eq!(pos1.cmp(&pos2), std::cmp::Ordering::Greater);
+        assert_eq!(pos1 == pos2, false);
+    }
+
+    #[test]
+    fn test_ord_large_numerators() {
+        let pos1 = Pos::new(u64::MAX, 2);
+        let pos2 = Pos::new(u64::MAX - 1, 2);
+
+        assert_eq!(pos1 > pos2, true);
+        assert_eq!(pos1.max(pos2), pos1);
+    }
+
+    #[test]
+    fn test_eq_n0d1_n0d5() {
+        assert_eq!(Pos::new(0, 1), Pos::new(0, 5));
+        assert_eq!(hash_of(Pos::new(0, 1)), hash_of(Pos::new(0, 5)));
+    }
+
+    #[test]
+    fn test_hash_consistent_with_eq() {
+        assert_eq!(hash_of(Pos::new(2, 1)), hash_of(Pos::new(4, 2)));
+        assert_eq!(hash_of(Pos::new(8, 2)), hash_of(Pos::new(16, 4)));
+        assert_ne!(hash_of(Pos::new(1, 2)), hash_of(Pos::new(2, 1)));
+    }
+
+    #[test]
+    fn test_btree_map_key() {
+        let mut map = BTreeMap::new();
+        map.insert(Pos::new(3, 2), "B");
+        map.insert(Pos::new(1, 1), "A");
+        map.insert(Pos::new(2, 1), "C");
+        map.insert(Pos::new(4, 2), "D");
+
+        assert_eq!(map.len(), 3);
+        assert_eq!(map.values().copied().collect::<Vec<_>>(), vec!["A", "B", "D"]);
+    }
+
+    #[test]
+    fn test_try_new_ok() {
+        let pos = Pos::try_new(3, 2);
+
+        assert_eq!(pos, Ok(Pos { num: 3, denom: 2 }));
+    }
+
+    #[test]
+    fn test_try_new_zero_denominator() {
+        let pos = Pos::try_new(1, 0);
+
+        assert_eq!(pos, Err(PosError::ZeroDenominator));
+        assert_eq!(pos.unwrap_err().to_string(), "denominator of a position must not be zero");
+    }
+
+    #[test]
+    fn test_try_from_tuple() {
+        assert_eq!(Pos::try_from((6, 4)), Ok(Pos { num: 6, denom: 4 }));
+        assert_eq!(Pos::try_from((6, 0)), Err(PosError::ZeroDenominator));
+    }
+
+    #[test]
+    fn test_into_tuple_not_reduced() {
+        let pair: (u64, u64) = Pos::new(6, 4).into();
+
+        assert_eq!(pair, (6, 4));
+        assert_eq!(Pos::try_from(pair).map(<(u64, u64)>::from), Ok((6, 4)));
+    }
+
+    #[cfg(feature = "serde-derive")]
+    #[test]
+    fn test_deserialize_validates() {
+        let pos: Pos = serde_json::from_str(r#"{"num":6,"denom":4}"#).unwrap();
+
+        assert_eq!(<(u64, u64)>::from(pos), (6, 4));
+        assert_eq!(serde_json::from_str::<Pos>(r#"{"num":1,"denom":0}"#).is_err(), true);
+    }
+
+    #[cfg(feature = "serde-derive")]
+    #[test]
+    fn test_serialize_keeps_struct_shape() {
+        let json = serde_json::to_string(&Pos::new(3, 2)).unwrap();
+
+        assert_eq!(json, r#"{"num":3,"denom":2}"#);
+        assert_eq!(<(u64, u64)>::from(serde_json::from_str::<Pos>(&json).unwrap()), (3, 2));
+    }
+
+    #[test]
+    fn test_num_denom() {
+        let pos = Pos::new(27, 5);
+
+        assert_eq!(pos.num(), 27);
+        assert_eq!(pos.denom(), 5);
+    }
+
+    #[test]
+    fn test_reduced() {
+        assert_eq!(<(u64, u64)>::from(Pos::new(6, 4).reduced()), (3, 2));
+        assert_eq!(<(u64, u64)>::from(Pos::new(0, 7).reduced()), (0, 1));
+        assert_eq!(<(u64, u64)>::from(Pos::new(3, 2).reduced()), (3, 2));
+    }
+
+    #[test]
+    fn test_is_reduced() {
+        assert_eq!(Pos::new(3, 2).is_reduced(), true);
+        assert_eq!(Pos::new(6, 4).is_reduced(), false);
+        assert_eq!(Pos::new(0, 1).is_reduced(), true);
+        assert_eq!(Pos::new(0, 7).is_reduced(), false);
+    }
+
+    #[test]
+    fn test_checked_mid() {
+        let lhs = Pos::new(1, 1);
+        let rhs = Pos::new(2, 1);
+
+        assert_eq!(Pos::checked_mid(lhs, rhs), Some(Pos { num: 3, denom: 2 }));
+        assert_eq!(Pos::checked_mid(rhs, lhs), Some(Pos { num: 3, denom: 2 }));
+    }
+
+    #[test]
+    fn test_checked_mid_equal_positions() {
+        assert_eq!(Pos::checked_mid(Pos::new(1, 2), Pos::new(2, 4)), None);
+        assert_eq!(Pos::checked_mid(Pos::MIN, Pos::new(0, 3)), None);
+    }
+
+    #[test]
+    fn test_checked_mid_overflow() {
+        let lhs = Pos::new(u64::MAX - 1, 1);
+        let rhs = Pos::new(u64::MAX, 1);
+
+        assert_eq!(Pos::checked_mid(lhs, rhs), None);
+
+        let lhs = Pos::new(1, u64::MAX);
+        let rhs = Pos::new(2, u64::MAX - 1);
+
+        assert_eq!(Pos::checked_mid(lhs, rhs), None);
+    }
+
+    #[test]
+    fn test_display() {
+        assert_eq!(Pos::new(3, 2).to_string(), "3/2");
+    }
+
+    #[test]
+    fn test_spread_unbounded() {
+        let positions = Pos::spread(None, None, 3).unwrap();
+
+        assert_eq!(positions, vec![Pos::new(1, 1), Pos::new(2, 1), Pos::new(3, 1)]);
+    }
+
+    #[test]
+    fn test_spread_after_lower() {
+        let positions = Pos::spread(Some(Pos::new(5, 2)), None, 2).unwrap();
+
+        assert_eq!(positions, vec![Pos::new(3, 1), Pos::new(4, 1)]);
+    }
+
+    #[test]
+    fn test_spread_before_upper() {
+        let positions = Pos::spread(None, Some(Pos::new(1, 1)), 3).unwrap();
+
+        assert_eq!(positions, vec![Pos::new(1, 4), Pos::new(2, 4), Pos::new(3, 4)]);
+    }
+
+    #[test]
+    fn test_spread_between() {
+        let lower = Pos::new(1, 1);
+        let upper = Pos::new(2, 1);
+        let positions = Pos::spread(Some(lower), Some(upper), 4).unwrap();
+
+        assert_eq!(
+            positions,
+            vec![Pos::new(6, 5), Pos::new(7, 5), Pos::new(8, 5), Pos::new(9, 5)]
+        );
+    }
+
+    #[test]
+    fn test_spread_between_large_denominators() {
+        let lower = Pos::new(u64::MAX / 3, u64::MAX);
+        let upper = Pos::new(u64::MAX / 2, u64::MAX - 1);
+        let positions = Pos::spread(Some(lower), Some(upper), 10).unwrap();
+
+        assert_eq!(positions.len(), 10);
+        assert_eq!(positions.iter().all(|pos| pos.denom() < 100), true);
+        assert_eq!(positions.first() > Some(&lower), true);
+        assert_eq!(positions.last() < Some(&upper), true);
+        assert_eq!(positions.windows(2).all(|pair| pair[0] < pair[1]), true);
+    }
+
+    #[test]
+    fn test_spread_exhausted() {
+        let lower = Pos::new(u64::MAX - 1, 1);
+        let upper = Pos::new(u64::MAX, 1);
+
+        assert_eq!(Pos::spread(Some(upper), None, 1), None);
+        assert_eq!(Pos::spread(Some(lower), Some(upper), 1), None);
+        assert_eq!(
+            Pos::spread(Some(Pos::new(1, u64::MAX)), Some(Pos::new(1, u64::MAX - 1)), 1),
+            None
+        );
+        assert_eq!(
+            Pos::spread(Some(Pos::new(1, u64::MAX)), Some(Pos::new(1, u64::MAX - 2)), 2),
+            None
+        );
+        assert_eq!(Pos::spread(None, Some(Pos::MIN), 1), None);
+    }
+
+    #[test]
+    fn test_simplest_between() {
+        assert_eq!(
+            Pos::simplest_between(Pos::new(1, 1), Pos::new(2, 1)),
+            Some(Pos { num: 3, denom: 2 })
+        );
+        assert_eq!(
+            Pos::simplest_between(Pos::new(1, 3), Pos::new(1, 2)),
+            Some(Pos { num: 2, denom: 5 })
+        );
+        assert_eq!(
+            Pos::simplest_between(Pos::new(6, 5), Pos::new(7, 5)),
+            Some(Pos { num: 4, denom: 3 })
+        );
+        assert_eq!(
+            Pos::simplest_between(Pos::new(1, 1), Pos::new(4, 2)),
+            Some(Pos { num: 3, denom: 2 })
+        );
+        assert_eq!(
+            Pos::simplest_between(Pos::new(1, 1), Pos::new(3, 1)),
+            Some(Pos { num: 2, denom: 1 })
+        );
+    }
+
+    #[test]
+    fn test_simplest_between_reversed() {
+        assert_eq!(
+            Pos::simplest_between(Pos::new(2, 1), Pos::new(1, 1)),
+            Some(Pos { num: 3, denom: 2 })
+        );
+    }
+
+    #[test]
+    fn test_simplest_between_min() {
+        assert_eq!(
+            Pos::simplest_between(Pos::MIN, Pos::new(1, 1000)),
+            Some(Pos { num: 1, denom: 1001 })
+        );
+        assert_eq!(
+            Pos::simplest_between(Pos::MIN, Pos::new(3, 2)),
+            Some(Pos { num: 1, denom: 1 })
+        );
+    }
+
+    #[test]
+    fn test_simplest_between_large_integers() {
+        let pos = Pos::simplest_between(Pos::new(1_000_000, 1), Pos::new(1_000_001, 1));
+
+        assert_eq!(
+            pos,
+            Some(Pos {
+                num: 2_000_001,
+                denom: 2
+            })
+        );
+    }
+
+    #[test]
+    fn test_simplest_between_equal_positions() {
+        assert_eq!(Pos::simplest_between(Pos::new(1, 2), Pos::new(2, 4)), None);
+    }
+
+    #[test]
+    fn test_simplest_between_overflow() {
+        assert_eq!(Pos::simplest_between(Pos::new(u64::MAX - 1, 1), Pos::new(u64::MAX, 1)), None);
+        assert_eq!(Pos::simplest_between(Pos::new(1, u64::MAX), Pos::new(1, u64::MAX - 1)), None);
+    }
+
+    #[test]
+    fn test_simplest_between_is_smallest_denominator() {
+        // Brute force all fractions with denominators up to 50.
+        let bounds = [
+            (Pos::new(7, 9), Pos::new(4, 5)),
+            (Pos::new(13, 17), Pos::new(10, 13)),
+            (Pos::new(1, 7), Pos::new(1, 6)),
+        ];
+
+        for (lower, upper) in bounds {
+            let expected = (1..=50u64)
+                .flat_map(|denom| (0..=denom).map(move |num| Pos::new(num, denom)))
+                .find(|pos| lower < *pos && *pos < upper);
+
+            assert_eq!(
+                Pos::simplest_between(lower, upper).map(|pos| pos.denom()),
+                expected.map(|pos| pos.reduced().denom())
+            );
+        }
+    }
+
+    #[test]
+    fn test_between_mediant() {
+        let strategy = PositionStrategy::Mediant;
+
+        assert_eq!(Pos::between(None, None, strategy), Some(Pos { num: 1, denom: 1 }));
+        assert_eq!(
+            Pos::between(Some(Pos::new(3, 2)), None, strategy),
+            Some(Pos { num: 4, denom: 2 })
+        );
+        assert_eq!(
+            Pos::between(None, Some(Pos::new(3, 2)), strategy),
+            Some(Pos { num: 3, denom: 3 })
+        );
+        assert_eq!(
+            Pos::between(Some(Pos::new(1, 1)), Some(Pos::new(3, 1)), strategy),
+            Some(Pos { num: 4, denom: 2 })
+        );
+        assert_eq!(Pos::between(Some(Pos::new(u64::MAX, 1)), None, strategy), None);
+    }
+
+    #[test]
+    fn test_between_stern_brocot() {
+        let strategy = PositionStrategy::SternBrocot;
+
+        assert_eq!(Pos::between(None, None, strategy), Some(Pos { num: 1, denom: 1 }));
+        assert_eq!(
+            Pos::between(Some(Pos::new(3, 2)), None, strategy),
+            Some(Pos { num: 2, denom: 1 })
+        );
+        assert_eq!(
+            Pos::between(None, Some(Pos::new(3, 2)), strategy),
+            Some(Pos { num: 1, denom: 1 })
+        );
+        assert_eq!(
+            Pos::between(Some(Pos::new(1, 1)), Some(Pos::new(3, 1)), strategy),
+            Some(Pos { num: 2, denom: 1 })
+        );
+        assert_eq!(Pos::between(Some(Pos::new(2, 1)), Some(Pos::new(1, 1)), strategy), None);
+        assert_eq!(Pos::between(None, Some(Pos::MIN), strategy), None);
+        assert_eq!(Pos::between(Some(Pos::new(u64::MAX, 1)), None, strategy), None);
+    }
+
+    #[test]
+    fn test_sort_key() {
+        assert_eq!(Pos::new(0, 1).sort_key(), vec![0x01, 0x00]);
+        assert_eq!(Pos::new(1, 1).sort_key(), vec![0x02, 0x01, 0x00]);
+        assert_eq!(Pos::new(1, 2).sort_key(), vec![0x01, 0xfd, 0xfd, 0xff]);
+        assert_eq!(Pos::new(2, 4).sort_key(), Pos::new(1, 2).sort_key());
+        assert_eq!(Pos::new(0, 7).sort_key(), Pos::new(0, 1).sort_key());
+    }
+
+    #[test]
+    fn test_sort_key_order() {
+        let mut positions: Vec<Pos> = (1..=12u64)
+            .flat_map(|denom| (0..=30).map(move |num| Pos::new(num, denom)))
+            .collect();
+        positions.extend([
+            Pos::new(u64::MAX, 1),
+            Pos::new(u64::MAX - 1, 1),
+            Pos::new(1, u64::MAX),
+            Pos::new(1, u64::MAX - 1),
+            Pos::new(u64::MAX, u64::MAX - 1),
+            Pos::new(u64::MAX - 1, u64::MAX - 2),
+            Pos::new(u64::MAX - 1, u64::MAX),
+        ]);
+
+        for a in &positions {
+            for b in &positions {
+                assert_eq!(a.sort_key().cmp(&b.sort_key()), a.cmp(b), "{} vs {}", a, b);
+            }
+        }
+    }
+
+    #[test]
+    fn test_sort_key_random() {
+        let mut rng = StdRng::seed_from_u64(24);
+        let mut random_pos = || {
+            Pos::new(
+                rng.gen::<u64>() >> rng.gen_range(0..64),
+                rng.gen::<u64>() >> rng.gen_range(0..64),
+            )
+        };
+
+        for _ in 0..10_000 {
+            let (a, b) = (random_pos(), random_pos());
+
+            assert_eq!(a.sort_key().cmp(&b.sort_key()), a.cmp(&b), "{} vs {}", a, b);
+            assert_eq!(Pos::from_sort_key(&a.sort_key()), Ok(a.reduced()));
+        }
+    }
+
+    #[test]
+    fn test_from_sort_key() {
+        let positions = [
+            Pos::new(0, 1),
+            Pos::new(1, 1),
+            Pos::new(2, 4),
+            Pos::new(7, 3),
+            Pos::new(u64::MAX, 1),
+            Pos::new(1, u64::MAX),
+            Pos::new(u64::MAX, u64::MAX - 1),
+        ];
+
+        for pos in positions {
+            let from = Pos::from_sort_key(&pos.sort_key()).unwrap();
+
+            assert_eq!((from.num(), from.denom()), (pos.reduced().num(), pos.reduced().denom()));
+        }
+    }
+
+    #[test]
+    fn test_from_sort_key_invalid() {
+        let keys: [&[u8]; 9] = [
+            // Empty and closed without any term
+            &[],
+            &[0xff],
+            // Missing or wrong closing byte, trailing bytes
+            &[0x01],
+            &[0x01, 0xff],
+            &[0x01, 0x00, 0x00],
+            // Term 0 written with one byte, term 0 after the first term, truncated term
+            &[0x02, 0x00, 0x00],
+            &[0x01, 0xfe, 0xff],
+            &[0x03, 0x01, 0x00],
+            // Last term of 1, i.e. [0; 1] instead of [1]
+            &[0x01, 0xfd, 0xfe, 0xff],
+        ];
+
+        for key in keys {
+            assert_eq!(Pos::from_sort_key(key), Err(PosError::InvalidSortKey), "{:?}", key);
+        }
+    }
+
+    #[test]
+    fn test_from_sort_key_overflow() {
+        // [u64::MAX; 2], i.e. (2 * u64::MAX + 1) / 2
+        let key = [0x09, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xfd, 0xfd, 0xff];
+
+        assert_eq!(Pos::from_sort_key(&key), Err(PosError::InvalidSortKey));
+    }
+
     #[test]
     fn test_default() {
         let pos = Pos::default();
@@ -0,0 +1,304 @@
+#[cfg(test)]
+#[allow(clippy::bool_assert_comparison)]
+mod tests {
+    use std::ops::Bound::{self, Excluded, Included, Unbounded};
+
+    use ::postgres::{Client, NoTls};
+
+    use crate::error::PostgresError;
+    use crate::postgres::{create_table_sql, quote, PostgresCodec, PostgresStore};
+    use crate::sequence::{Pos, Sequence};
+    use crate::test_util::{elements, setup_seq_abc, Point};
+
+    // Tests requiring a database are ignored by default. Run them against the server given by this variable, e.g.
+    // `KODIAK_SETS_POSTGRES_URL="host=localhost user=postgres" cargo test --features postgres -- --ignored`.
+    // Each test uses a table of its own, which it drops first.
+    const URL: &str = "KODIAK_SETS_POSTGRES_URL";
+
+    fn connect() -> Client {
+        let url = std::env::var(URL).unwrap_or_else(|_| panic!("{} must be set to run tests requiring a database", URL));
+
+        Client::connect(&url, NoTls).unwrap()
+    }
+
+    fn setup<T: PostgresCodec>(table: &str) -> PostgresStore {
+        let mut store = PostgresStore::new(connect(), &format!("kodiak_sets_{}", table));
+
+        let sql = format!("DROP TABLE IF EXISTS {}", quote(store.table()));
+        store.client().batch_execute(&sql).unwrap();
+        store.create_table::<T>().unwrap();
+
+        store
+    }
+
+    fn positions<T>(seq: &Sequence<T>) -> Vec<Pos> {
+        seq.positions().cloned().collect()
+    }
+
+    fn rows(store: &mut PostgresStore) -> Vec<(String, String, String)> {
+        let sql = format!(
+            "SELECT numerator::TEXT, denominator::TEXT, payload FROM {} ORDER BY {}",
+            quote(store.table()),
+            PostgresStore::ORDER_BY
+        );
+
+        store
+            .client()
+            .query(&sql, &[])
+            .unwrap()
+            .into_iter()
+            .map(|row| (row.get(0), row.get(1), row.get(2)))
+            .collect()
+    }
+
+    fn row(num: &str, denom: &str, payload: &str) -> (String, String, String) {
+        (num.to_string(), denom.to_string(), payload.to_string())
+    }
+
+    fn execute(store: &mut PostgresStore, sql: &str) {
+        let sql = sql.replace("{table}", &quote(store.table()));
+        store.client().batch_execute(&sql).unwrap();
+    }
+
+    #[test]
+    fn test_quote() {
+        assert_eq!(quote("sequence"), "\"sequence\"");
+        assert_eq!(quote("my \"seq\""), "\"my \"\"seq\"\"\"");
+    }
+
+    #[test]
+    #[ignore = "requires a PostgreSQL server"]
+    fn test_new() {
+        let store = PostgresStore::new(connect(), "items");
+
+        assert_eq!(store.table(), "items");
+        assert_eq!(store.into_client().is_closed(), false);
+    }
+
+    #[test]
+    fn test_create_table_sql() {
+        let sql = create_table_sql::<Vec<u8>>("my \"seq\"");
+
+        assert_eq!(sql.contains("CREATE TABLE IF NOT EXISTS \"my \"\"seq\"\"\" ("), true);
+        assert_eq!(sql.contains("payload BYTEA,"), true);
+        assert_eq!(sql.contains("\"my \"\"seq\"\"_order_idx\""), true);
+        assert_eq!(sql.contains(PostgresStore::ORDER_BY), true);
+    }
+
+    #[test]
+    #[ignore = "requires a PostgreSQL server"]
+    fn test_create_table_twice() {
+        let mut store = setup::<String>("create_table_twice");
+
+        assert_eq!(store.create_table::<String>().is_ok(), true);
+    }
+
+    #[test]
+    #[ignore = "requires a PostgreSQL server"]
+    fn test_load_empty() {
+        let mut store = setup::<String>("load_empty");
+        let seq: Sequence<String> = Sequence::load_from(&mut store).unwrap();
+
+        assert_eq!(seq.is_empty(), true);
+        assert_eq!(seq.is_journal_enabled(), true);
+    }
+
+    #[test]
+    #[ignore = "requires a PostgreSQL server"]
+    fn test_save_and_load() {
+        let mut store = setup::<String>("save_and_load");
+        let mut seq = setup_seq_abc();
+        seq.remove(1);
+
+        assert_eq!(seq.save_to(&mut store).unwrap(), 2);
+        assert_eq!(rows(&mut store), vec![row("1", "1", "A"), row("3", "1", "C")]);
+
+        let loaded: Sequence<String> = Sequence::load_from(&mut store).unwrap();
+        assert_eq!(elements(&loaded), vec!["A", "C"]);
+        assert_eq!(positions(&loaded), positions(&seq));
+    }
+
+    #[test]
+    #[ignore = "requires a PostgreSQL server"]
+    fn test_order_by_is_exact() {
+        let mut store = setup::<String>("order_by_is_exact");
+        let max = u64::MAX;
+        let mut seq: Sequence<String> = Sequence::new();
+
+        // Neighbouring fractions with the largest denominators, which are equal as f64.
+        seq.insert_at(Pos::try_new(max - 1, max - 2).unwrap(), "third".to_string());
+        seq.insert_at(Pos::try_new(max, max - 1).unwrap(), "second".to_string());
+        seq.insert_at(Pos::try_new(1, max).unwrap(), "first".to_string());
+        seq.insert_at(Pos::try_new(max, 1).unwrap(), "last".to_string());
+        seq.save_to(&mut store).unwrap();
+
+        let payloads: Vec<String> = rows(&mut store).into_iter().map(|(_, _, payload)| payload).collect();
+        assert_eq!(payloads, vec!["first", "second", "third", "last"]);
+
+        let loaded: Sequence<String> = Sequence::load_from(&mut store).unwrap();
+        assert_eq!(elements(&loaded), elements(&seq));
+        assert_eq!(positions(&loaded), positions(&seq));
+    }
+
+    #[test]
+    #[ignore = "requires a PostgreSQL server"]
+    fn test_load_range() {
+        let mut store = setup::<String>("load_range");
+        let max = u64::MAX;
+        let mut seq: Sequence<String> = Sequence::new();
+        seq.insert_at(Pos::try_new(max - 1, max - 2).unwrap(), "third".to_string());
+        seq.insert_at(Pos::try_new(max, max - 1).unwrap(), "second".to_string());
+        seq.insert_at(Pos::try_new(1, max).unwrap(), "first".to_string());
+        seq.insert_at(Pos::try_new(max, 1).unwrap(), "last".to_string());
+        seq.save_to(&mut store).unwrap();
+
+        let second = Pos::try_new(max, max - 1).unwrap();
+        let third = Pos::try_new(max - 1, max - 2).unwrap();
+        let load = |store: &mut PostgresStore, range: (Bound<Pos>, Bound<Pos>)| -> Vec<String> {
+            elements(&Sequence::load_range_from(store, range).unwrap())
+        };
+
+        assert_eq!(load(&mut store, (Included(second), Included(third))), vec!["second", "third"]);
+        assert_eq!(load(&mut store, (Excluded(second), Excluded(third))), Vec::<String>::new());
+        assert_eq!(load(&mut store, (Excluded(second), Unbounded)), vec!["third", "last"]);
+        assert_eq!(load(&mut store, (Unbounded, Excluded(second))), vec!["first"]);
+        assert_eq!(load(&mut store, (Included(third), Included(second))), Vec::<String>::new());
+        assert_eq!(load(&mut store, (Unbounded, Unbounded)), elements(&seq));
+    }
+
+    #[test]
+    #[ignore = "requires a PostgreSQL server"]
+    fn test_load_negative_numerator() {
+        let mut store = setup::<String>("load_negative_numerator");
+        let constraint = quote(&format!("{}_numerator_check", store.table()));
+        execute(&mut store, &format!("ALTER TABLE {{table}} DROP CONSTRAINT {}", constraint));
+        execute(&mut store, "INSERT INTO {table} VALUES (-1, 1, 'A')");
+
+        let result: Result<Sequence<String>, PostgresError> = Sequence::load_from(&mut store);
+
+        assert!(matches!(result, Err(PostgresError::Decode(_))));
+    }
+
+    #[test]
+    #[ignore = "requires a PostgreSQL server"]
+    fn test_load_decode_error() {
+        let mut store = setup::<Point>("load_decode_error");
+        execute(&mut store, "INSERT INTO {table} VALUES (1, 1, 'A')");
+
+        let result: Result<Sequence<Point>, PostgresError> = Sequence::load_from(&mut store);
+        assert!(matches!(result, Err(PostgresError::Decode(_))));
+
+        let result: Result<Sequence<i64>, PostgresError> = Sequence::load_from(&mut store);
+        assert!(matches!(result, Err(PostgresError::Postgres(_))));
+    }
+
+    #[test]
+    #[ignore = "requires a PostgreSQL server"]
+    fn test_codecs() {
+        fn roundtrip<T: PostgresCodec + Clone + PartialEq + std::fmt::Debug>(table: &str, values: Vec<T>) {
+            let mut store = setup::<T>(table);
+            let seq: Sequence<T> = values.into_iter().collect();
+
+            seq.save_to(&mut store).unwrap();
+
+            assert_eq!(elements(&Sequence::<T>::load_from(&mut store).unwrap()), elements(&seq));
+        }
+
+        roundtrip("codecs_bytea", vec![vec![0u8, 1], vec![], vec![255]]);
+        roundtrip("codecs_bigint", vec![i64::MIN, 0, i64::MAX]);
+        roundtrip("codecs_double", vec![-1.5f64, 0.0, 2.25]);
+        roundtrip("codecs_boolean", vec![true, false]);
+        roundtrip("codecs_custom", vec![Point { x: 1, y: -2 }, Point { x: 3, y: 4 }]);
+    }
+
+    #[test]
+    #[ignore = "requires a PostgreSQL server"]
+    fn test_sync() {
+        let mut store = setup::<String>("sync");
+        setup_seq_abc().save_to(&mut store).unwrap();
+        let mut seq: Sequence<String> = Sequence::load_from(&mut store).unwrap();
+
+        seq.insert(1, "X".to_string());
+        seq.remove(3);
+        seq.insert_at(Pos::try_new(1, 1).unwrap(), "Y".to_string());
+
+        // X inserted at 3/2, C removed from 3/1, A updated at 1/1
+        assert_eq!(seq.sync_to(&mut store).unwrap(), 3);
+        assert_eq!(
+            rows(&mut store),
+            vec![row("1", "1", "Y"), row("3", "2", "X"), row("2", "1", "B")]
+        );
+        assert_eq!(seq.take_changes().is_empty(), true);
+    }
+
+    #[test]
+    #[ignore = "requires a PostgreSQL server"]
+    fn test_sync_moves() {
+        let mut store = setup::<String>("sync_moves");
+        let seq: Sequence<String> = vec!["D", "B", "A", "C"].into_iter().map(String::from).collect();
+        seq.save_to(&mut store).unwrap();
+        let mut seq: Sequence<String> = Sequence::load_from(&mut store).unwrap();
+
+        seq.move_to(0, 3).unwrap();
+        seq.sort_by(|a, b| a.cmp(b)).unwrap();
+        seq.reverse().unwrap();
+        seq.rebalance();
+        seq.sync_to(&mut store).unwrap();
+
+        let loaded: Sequence<String> = Sequence::load_from(&mut store).unwrap();
+        assert_eq!(elements(&loaded), vec!["D", "C", "B", "A"]);
+        assert_eq!(positions(&loaded), positions(&seq));
+    }
+
+    #[test]
+    #[ignore = "requires a PostgreSQL server"]
+    fn test_sync_not_reduced_position() {
+        let mut store = setup::<String>("sync_not_reduced_position");
+        let mut seq: Sequence<String> = Sequence::new();
+        seq.insert_at(Pos::try_new(2, 4).unwrap(), "A".to_string());
+        seq.save_to(&mut store).unwrap();
+        assert_eq!(rows(&mut store), vec![row("1", "2", "A")]);
+
+        let mut seq: Sequence<String> = Sequence::load_from(&mut store).unwrap();
+        seq.insert_at(Pos::try_new(1, 2).unwrap(), "B".to_string());
+        seq.insert_at(Pos::try_new(3, 6).unwrap(), "C".to_string());
+        seq.insert_at(Pos::try_new(4, 4).unwrap(), "D".to_string());
+
+        assert_eq!(seq.sync_to(&mut store).unwrap(), 2);
+        assert_eq!(rows(&mut store), vec![row("1", "2", "C"), row("1", "1", "D")]);
+        assert_eq!(seq.sync_to(&mut store).unwrap(), 0);
+    }
+
+    #[test]
+    #[ignore = "requires a PostgreSQL server"]
+    fn test_sync_not_reduced_row() {
+        let mut store = setup::<String>("sync_not_reduced_row");
+        execute(&mut store, "INSERT INTO {table} VALUES (2, 4, 'A')");
+
+        let mut seq: Sequence<String> = Sequence::load_from(&mut store).unwrap();
+        seq.insert_at(Pos::try_new(1, 2).unwrap(), "B".to_string());
+        seq.sync_to(&mut store).unwrap();
+
+        assert_eq!(rows(&mut store), vec![row("1", "2", "B")]);
+    }
+
+    #[test]
+    #[ignore = "requires a PostgreSQL server"]
+    fn test_sync_failure_keeps_changes() {
+        let mut store = setup::<String>("sync_failure_keeps_changes");
+        let mut seq: Sequence<String> = Sequence::load_from(&mut store).unwrap();
+        seq.push("A".to_string());
+        seq.push("B".to_string());
+        execute(&mut store, "DROP TABLE {table}");
+
+        assert!(matches!(seq.sync_to(&mut store), Err(PostgresError::Postgres(_))));
+
+        seq.push("C".to_string());
+        store.create_table::<String>().unwrap();
+        assert_eq!(seq.sync_to(&mut store).unwrap(), 3);
+        assert_eq!(
+            rows(&mut store),
+            vec![row("1", "1", "A"), row("2", "1", "B"), row("3", "1", "C")]
+        );
+    }
+}
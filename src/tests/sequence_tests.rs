@@ -1,6 +1,15 @@
 #[cfg(test)]
+#[allow(clippy::bool_assert_comparison)]
 mod tests {
-    use crate::sequence::{Node, Pos, Sequence};
+    use std::ops::Bound;
+
+    use crate::error::PositionExhausted;
+    use crate::order_key::OrderKey;
+    use crate::sequence::{
+        longest_increasing_subsequence, Change, Entry, Node, NodeId, Placement, Pos, PositionStrategy, Sequence,
+    };
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
 
     pub fn setup_seq_empty() -> Sequence<String> {
         let seq: Sequence<String> = Sequence::new();
@@ -350,6 +359,390 @@ mod tests {
         assert_eq!(seq.get(2), Some(&"E".to_string()));
     }
 
+    #[test]
+    fn test_insert_at_uses_given_pos() {
+        let mut seq = setup_seq_abc();
+
+        seq.insert_at(Pos::new(5, 2), "BC".to_string());
+        seq.insert_at(Pos::new(7, 1), "D".to_string());
+
+        assert_eq!(seq.position_from(2), Some(Pos::new(5, 2)));
+        assert_eq!(seq.position_from(4), Some(Pos::new(7, 1)));
+        assert_eq!(seq.get(2), Some(&"BC".to_string()));
+    }
+
+    #[test]
+    fn test_index_from() {
+        let mut seq = setup_seq_abc();
+        seq.insert(1, "AB".to_string());
+
+        assert_eq!(seq.index_from(Pos::new(3, 2)), Some(1));
+        assert_eq!(seq.index_from(Pos::new(6, 2)), Some(3));
+        assert_eq!(seq.index_from(Pos::new(5, 2)), None);
+    }
+
+    #[test]
+    fn test_try_insert_returns_pos() {
+        let mut seq = setup_seq_abc();
+
+        assert_eq!(seq.try_insert(1, "AB".to_string()), Ok(Pos::new(3, 2)));
+        assert_eq!(seq.try_insert(0, "0".to_string()), Ok(Pos::new(1, 2)));
+        assert_eq!(seq.try_insert(10, "D".to_string()), Ok(Pos::new(4, 1)));
+        assert_eq!(seq.len(), 6);
+    }
+
+    #[test]
+    fn test_try_insert_exhausted_between() {
+        let mut seq = setup_seq_empty();
+        seq.insert_at(Pos::new(u64::MAX - 1, 1), "A".to_string());
+        seq.insert_at(Pos::new(u64::MAX, 1), "B".to_string());
+
+        let err = seq.try_insert(1, "AB".to_string()).unwrap_err();
+
+        assert_eq!(
+            err,
+            PositionExhausted::new(Some(Pos::new(u64::MAX - 1, 1)), Some(Pos::new(u64::MAX, 1)))
+        );
+        assert_eq!(err.lower(), Some(&Pos::new(u64::MAX - 1, 1)));
+        assert_eq!(err.upper(), Some(&Pos::new(u64::MAX, 1)));
+        assert_eq!(seq.len(), 2);
+        assert_eq!(seq.get(1), Some(&"B".to_string()));
+    }
+
+    #[test]
+    fn test_try_insert_exhausted_front() {
+        let mut seq = setup_seq_empty();
+        seq.insert_at(Pos::new(0, 1), "A".to_string());
+
+        let err = seq.try_insert(0, "0".to_string()).unwrap_err();
+
+        assert_eq!(err.lower(), None);
+        assert_eq!(err.upper(), Some(&Pos::new(0, 1)));
+        assert_eq!(err.to_string(), "no position left before 0/1");
+        assert_eq!(seq.len(), 1);
+    }
+
+    #[test]
+    fn test_try_insert_repeatedly_at_same_index() {
+        // Denominators grow by u64::MAX / 4 with every insert, so positions run out quickly.
+        let lower = Pos::new(1, u64::MAX / 4);
+        let mut seq = setup_seq_empty();
+        seq.insert_at(lower, "A".to_string());
+        seq.insert_at(Pos::new(1, 1), "B".to_string());
+
+        let mut result = Ok(Pos::default());
+        let mut n = 0;
+
+        while result.is_ok() {
+            n += 1;
+            result = seq.try_insert(1, "A".to_string());
+        }
+
+        assert_eq!(n, 5);
+        assert_eq!(result.unwrap_err().lower(), Some(&lower));
+        assert_eq!(seq.len(), 2 + 4);
+    }
+
+    #[test]
+    fn test_try_push_exhausted() {
+        let mut seq = setup_seq_empty();
+        seq.insert_at(Pos::new(u64::MAX, 1), "A".to_string());
+
+        let err = seq.try_push("B".to_string()).unwrap_err();
+
+        assert_eq!(err.lower(), Some(&Pos::new(u64::MAX, 1)));
+        assert_eq!(err.upper(), None);
+        assert_eq!(err.to_string(), format!("no position left after {}/1", u64::MAX));
+        assert_eq!(seq.len(), 1);
+    }
+
+    #[test]
+    fn test_try_push_returns_pos() {
+        let mut seq = setup_seq_empty();
+
+        assert_eq!(seq.try_push("A".to_string()), Ok(Pos::new(1, 1)));
+        assert_eq!(seq.try_push("B".to_string()), Ok(Pos::new(2, 1)));
+    }
+
+    #[test]
+    #[should_panic(expected = "no position left between")]
+    fn test_insert_panics_when_exhausted() {
+        let mut seq = setup_seq_empty();
+        seq.insert_at(Pos::new(u64::MAX - 1, 1), "A".to_string());
+        seq.insert_at(Pos::new(u64::MAX, 1), "B".to_string());
+
+        seq.insert(1, "AB".to_string());
+    }
+
+    #[test]
+    #[should_panic(expected = "no position left after")]
+    fn test_push_panics_when_exhausted() {
+        let mut seq = setup_seq_empty();
+        seq.insert_at(Pos::new(u64::MAX, 1), "A".to_string());
+
+        seq.push("B".to_string());
+    }
+
+    #[test]
+    fn test_rebalance() {
+        let mut seq = setup_seq_abc();
+        seq.insert(1, "AB".to_string());
+        seq.insert(1, "AAB".to_string());
+        seq.remove(0);
+
+        let changes = seq.rebalance();
+
+        assert_eq!(
+            changes,
+            vec![
+                (Pos::new(4, 3), Pos::new(1, 1)),
+                (Pos::new(3, 2), Pos::new(2, 1)),
+                (Pos::new(2, 1), Pos::new(3, 1)),
+                (Pos::new(3, 1), Pos::new(4, 1)),
+            ]
+        );
+        assert_eq!(seq.len(), 4);
+        assert_eq!(seq.nodes.len(), 4);
+        assert_eq!(seq.get(0), Some(&"AAB".to_string()));
+        assert_eq!(seq.get(3), Some(&"C".to_string()));
+    }
+
+    #[test]
+    fn test_rebalance_unchanged() {
+        let mut seq = setup_seq_abc();
+
+        assert_eq!(seq.rebalance(), vec![]);
+        assert_eq!(seq, setup_seq_abc());
+    }
+
+    #[test]
+    fn test_rebalance_range() {
+        let mut seq = setup_seq_abc();
+        seq.insert(1, "AB".to_string());
+        seq.insert(2, "ABB".to_string());
+        seq.insert(2, "ABBB".to_string());
+
+        let changes = seq.rebalance_range(1..4).unwrap();
+
+        assert_eq!(
+            changes,
+            vec![
+                (Pos::new(3, 2), Pos::new(5, 4)),
+                (Pos::new(8, 5), Pos::new(3, 2)),
+                (Pos::new(5, 3), Pos::new(7, 4)),
+            ]
+        );
+        assert_eq!(seq.position_from(0), Some(Pos::new(1, 1)));
+        assert_eq!(seq.position_from(4), Some(Pos::new(2, 1)));
+        assert_eq!(seq.get(1), Some(&"AB".to_string()));
+        assert_eq!(seq.get(2), Some(&"ABBB".to_string()));
+        assert_eq!(seq.get(3), Some(&"ABB".to_string()));
+    }
+
+    #[test]
+    fn test_rebalance_range_drops_empty_nodes_in_range_only() {
+        let mut seq = setup_seq_abc();
+        seq.push("D".to_string());
+        seq.push("E".to_string());
+        seq.remove(2);
+        seq.remove(0);
+
+        // Nodes: [_, B, _, D, E]
+        let changes = seq.rebalance_range(..2).unwrap();
+
+        assert_eq!(changes, vec![(Pos::new(4, 1), Pos::new(3, 1))]);
+        assert_eq!(seq.nodes.len(), 4);
+        assert_eq!(seq.nodes[0].is_none(), true);
+        assert_eq!(seq.nodes[0].position(), &Pos::new(1, 1));
+        assert_eq!(seq.position_from(0), Some(Pos::new(2, 1)));
+        assert_eq!(seq.len(), 3);
+    }
+
+    #[test]
+    fn test_rebalance_range_to_end() {
+        let mut seq = setup_seq_abc();
+        seq.insert(2, "BC".to_string());
+
+        let changes = seq.rebalance_range(1..=10).unwrap();
+
+        assert_eq!(
+            changes,
+            vec![(Pos::new(5, 2), Pos::new(3, 1)), (Pos::new(3, 1), Pos::new(4, 1)),]
+        );
+    }
+
+    #[test]
+    fn test_rebalance_range_empty() {
+        let mut seq = setup_seq_abc();
+
+        assert_eq!(seq.rebalance_range(2..2), Ok(vec![]));
+        assert_eq!(seq.rebalance_range(5..), Ok(vec![]));
+        assert_eq!(seq, setup_seq_abc());
+    }
+
+    #[test]
+    fn test_rebalance_range_exhausted() {
+        let mut seq = setup_seq_empty();
+        seq.insert_at(Pos::new(1, u64::MAX), "A".to_string());
+        seq.insert_at(Pos::new(1, u64::MAX - 1), "B".to_string());
+        seq.insert_at(Pos::new(1, u64::MAX - 2), "C".to_string());
+        seq.insert_at(Pos::new(1, u64::MAX - 3), "D".to_string());
+
+        let err = seq.rebalance_range(1..3).unwrap_err();
+
+        assert_eq!(err.lower(), Some(&Pos::new(1, u64::MAX)));
+        assert_eq!(err.upper(), Some(&Pos::new(1, u64::MAX - 3)));
+        assert_eq!(seq.len(), 4);
+        assert_eq!(seq.position_from(1), Some(Pos::new(1, u64::MAX - 1)));
+    }
+
+    #[test]
+    fn test_rebalance_range_after_exhaustion() {
+        let lower = Pos::new(1, u64::MAX / 4);
+        let mut seq = setup_seq_empty();
+        seq.insert_at(lower, "A".to_string());
+        seq.insert_at(Pos::new(1, 1), "B".to_string());
+        while seq.try_insert(1, "AB".to_string()).is_ok() {}
+
+        seq.rebalance_range(1..seq.len()).unwrap();
+
+        assert_eq!(seq.try_insert(1, "AB".to_string()).is_ok(), true);
+        assert_eq!(seq.nodes.windows(2).all(|pair| pair[0].position() < pair[1].position()), true);
+    }
+
+    #[test]
+    fn test_new_infers_pos() {
+        let mut seq = Sequence::new();
+        seq.push("A");
+
+        assert_eq!(seq.position_from(0), Some(Pos::new(1, 1)));
+
+        let seq = Sequence::with_capacity(4);
+        assert_eq!(seq.capacity() >= 4, true);
+        assert_eq!(seq.get(0), None::<&char>);
+    }
+
+    #[test]
+    fn test_strategy_default() {
+        let seq = setup_seq_empty();
+
+        assert_eq!(seq.strategy(), PositionStrategy::Mediant);
+    }
+
+    #[test]
+    fn test_with_strategy() {
+        let seq: Sequence<String> = Sequence::with_strategy(PositionStrategy::SternBrocot);
+
+        assert_eq!(seq.strategy(), PositionStrategy::SternBrocot);
+        assert_eq!(seq.len(), 0);
+    }
+
+    #[test]
+    fn test_set_strategy() {
+        let mut seq = setup_seq_abc();
+        seq.set_strategy(PositionStrategy::SternBrocot);
+
+        assert_eq!(seq.strategy(), PositionStrategy::SternBrocot);
+        assert_eq!(seq.position_from(2), Some(Pos::new(3, 1)));
+    }
+
+    #[test]
+    fn test_insert_stern_brocot_keeps_denominators_small() {
+        let mut mediant = setup_seq_empty();
+        let mut stern_brocot: Sequence<String> = Sequence::with_strategy(PositionStrategy::SternBrocot);
+
+        for seq in [&mut mediant, &mut stern_brocot] {
+            seq.insert_at(Pos::new(1, 1), "A".to_string());
+            seq.insert_at(Pos::new(3, 1), "C".to_string());
+            seq.insert(1, "B".to_string());
+            seq.insert(1, "AB".to_string());
+        }
+
+        assert_eq!(mediant.position_from(1).map(<(u64, u64)>::from), Some((5, 3)));
+        assert_eq!(mediant.position_from(2).map(<(u64, u64)>::from), Some((4, 2)));
+        assert_eq!(stern_brocot.position_from(1).map(<(u64, u64)>::from), Some((3, 2)));
+        assert_eq!(stern_brocot.position_from(2).map(<(u64, u64)>::from), Some((2, 1)));
+    }
+
+    #[test]
+    fn test_push_stern_brocot() {
+        let mut seq: Sequence<String> = Sequence::with_strategy(PositionStrategy::SternBrocot);
+        seq.insert_at(Pos::new(3, 2), "A".to_string());
+        seq.push("B".to_string());
+
+        assert_eq!(seq.position_from(1).map(<(u64, u64)>::from), Some((2, 1)));
+    }
+
+    #[test]
+    fn test_insert_stern_brocot_after_rebalance_range() {
+        let mut seq: Sequence<String> = Sequence::with_strategy(PositionStrategy::SternBrocot);
+        seq.push("A".to_string());
+        seq.push("B".to_string());
+        for _ in 0..4 {
+            seq.insert(1, "AB".to_string());
+        }
+        seq.rebalance_range(1..5).unwrap();
+        seq.insert(2, "ABB".to_string());
+
+        assert_eq!(seq.position_from(1).map(<(u64, u64)>::from), Some((6, 5)));
+        assert_eq!(seq.position_from(2).map(<(u64, u64)>::from), Some((4, 3)));
+        assert_eq!(seq.position_from(3).map(<(u64, u64)>::from), Some((7, 5)));
+    }
+
+    #[test]
+    fn test_trait_impl_clone_keeps_strategy() {
+        let seq: Sequence<String> = Sequence::with_strategy(PositionStrategy::SternBrocot);
+
+        assert_eq!(seq.clone().strategy(), PositionStrategy::SternBrocot);
+    }
+
+    #[test]
+    fn test_order_key_push_insert() {
+        let mut seq: Sequence<String, OrderKey> = Sequence::with_position();
+
+        let a = seq.try_push("A".to_string()).unwrap();
+        let c = seq.try_push("C".to_string()).unwrap();
+        let b = seq.try_insert(1, "B".to_string()).unwrap();
+
+        assert_eq!(a.as_str(), "a0");
+        assert_eq!(c.as_str(), "a1");
+        assert_eq!(b.as_str(), "a0V");
+        assert_eq!(seq.index_from(&b), Some(1));
+        assert_eq!(seq.get(1), Some(&"B".to_string()));
+        assert_eq!(seq.nodes.windows(2).all(|pair| pair[0].position() < pair[1].position()), true);
+    }
+
+    #[test]
+    fn test_order_key_insert_at_remove_at() {
+        let mut seq: Sequence<String, OrderKey> = Sequence::with_position();
+        let key = OrderKey::try_new("b12").unwrap();
+
+        seq.push("A".to_string());
+        seq.insert_at(key.clone(), "B".to_string());
+        seq.insert(0, "Z".to_string());
+
+        assert_eq!(seq.len(), 3);
+        assert_eq!(seq.index_from(&key), Some(2));
+        assert_eq!(seq.remove_at(key), Some("B".to_string()));
+        assert_eq!(seq.len(), 2);
+    }
+
+    #[test]
+    fn test_order_key_rebalance() {
+        let mut seq: Sequence<String, OrderKey> = Sequence::with_position();
+
+        seq.push("C".to_string());
+        for element in ["B", "A"] {
+            seq.insert(0, element.to_string());
+        }
+        let changes = seq.rebalance();
+
+        assert_eq!(changes.iter().all(|(old, new)| old != new), true);
+        assert_eq!(seq.nodes.windows(2).all(|pair| pair[0].position() < pair[1].position()), true);
+        assert_eq!(seq.get(0), Some(&"A".to_string()));
+        assert_eq!(seq.get(2), Some(&"C".to_string()));
+    }
+
     #[test]
     fn test_position_from_index_eq_len() {
         let seq = setup_seq_abc();
@@ -375,21 +768,21 @@ mod tests {
     fn test_pos_from_index_eq_len() {
         let seq = setup_seq_abc();
 
-        assert_eq!(seq.pos_from(3), None);
+        assert_eq!(seq.position_from(3).map(<(u64, u64)>::from), None);
     }
 
     #[test]
     fn test_pos_from_index_gt_len() {
         let seq = setup_seq_abc();
 
-        assert_eq!(seq.pos_from(5), None);
+        assert_eq!(seq.position_from(5).map(<(u64, u64)>::from), None);
     }
 
     #[test]
     fn test_pos_from_index_lt_len() {
         let seq = setup_seq_abc();
 
-        assert_eq!(seq.pos_from(1), Some((2, 1)));
+        assert_eq!(seq.position_from(1).map(<(u64, u64)>::from), Some((2, 1)));
     }
 
     #[test]
@@ -449,24 +842,21 @@ mod tests {
     #[test]
     fn test_into_iter_owned_seq_123() {
         let mut seq = setup_seq_123();
-        seq[1] = Node::new_empty(Pos::default());
-
-        println!("{}", seq.len());
+        seq.remove(1);
 
-        for node in seq {
-            println!("{:?}", node);
-        }
+        let elements: Vec<i32> = seq.into_iter().filter_map(|node| node.element()).collect();
+        assert_eq!(elements, vec![1, 3]);
     }
 
     #[test]
     fn test_into_iter_mut_next_seq_123() {
         let mut seq = setup_seq_abc();
 
-        for node in &mut seq {
-            node.set("D".to_string());
+        for element in &mut seq {
+            *element = "D".to_string();
         }
 
-        println!("{:?}", seq);
+        assert_eq!(elements(&seq), vec!["D", "D", "D"]);
     }
 
     #[test]
@@ -537,6 +927,59 @@ mod tests {
         assert_eq!(seq.len(), len - 1);
     }
 
+    #[test]
+    fn test_remove_keeps_nodes_sorted() {
+        let mut seq = setup_seq_abc();
+        seq.push("D".to_string());
+
+        assert_eq!(seq.remove(1), Some("B".to_string()));
+        assert_eq!(seq.nodes.len(), 4);
+        assert_eq!(seq.nodes[1].is_none(), true);
+        assert_eq!(seq.nodes[1].position(), &Pos::new(2, 1));
+        assert_eq!(seq.nodes.windows(2).all(|pair| pair[0].position() < pair[1].position()), true);
+
+        let elements: Vec<String> = seq.into_iter().filter_map(|node| node.element()).collect();
+        assert_eq!(elements, vec!["A", "C", "D"]);
+    }
+
+    #[test]
+    fn test_remove_last_then_push() {
+        let mut seq = setup_seq_abc();
+
+        assert_eq!(seq.remove(2), Some("C".to_string()));
+        seq.push("D".to_string());
+
+        assert_eq!(seq.position_from(2), Some(Pos::new(4, 1)));
+        assert_eq!(seq.nodes.windows(2).all(|pair| pair[0].position() < pair[1].position()), true);
+    }
+
+    #[test]
+    fn test_random_inserts_and_removes_keep_nodes_sorted() {
+        let mut rng = StdRng::seed_from_u64(42);
+        let mut seq: Sequence<usize> = Sequence::new();
+        let mut expected: Vec<usize> = Vec::new();
+
+        for i in 0..1_000 {
+            if !expected.is_empty() && rng.gen_bool(0.4) {
+                let index = rng.gen_range(0..expected.len());
+                assert_eq!(seq.remove(index), Some(expected.remove(index)));
+            } else if rng.gen_bool(0.5) {
+                seq.push(i);
+                expected.push(i);
+            } else {
+                let index = rng.gen_range(0..=expected.len());
+                seq.insert(index, i);
+                expected.insert(index, i);
+            }
+
+            assert_eq!(seq.len(), expected.len());
+            assert_eq!(seq.nodes.windows(2).all(|pair| pair[0].position() < pair[1].position()), true);
+        }
+
+        let elements: Vec<usize> = seq.into_iter().filter_map(|node| node.element()).collect();
+        assert_eq!(elements, expected);
+    }
+
     #[test]
     fn test_remove_at_none() {
         // Remove from empty sequence
@@ -574,61 +1017,1333 @@ mod tests {
     }
 
     #[test]
-    fn test_trait_impl_default() {
-        let seq1: Sequence<String> = Sequence::default();
-        let seq2: Sequence<String> = Sequence::new();
+    fn test_tombstones() {
+        let mut seq = setup_seq_abc();
+        assert_eq!(seq.tombstones(), 0);
 
-        assert_eq!(seq1, seq2);
+        seq.remove(0);
+        seq.remove(1);
+        assert_eq!(seq.tombstones(), 2);
+
+        // Inserting keeps empty nodes, refilling one at its position reduces the number of tombstones.
+        seq.insert(0, "A".to_string());
+        assert_eq!(seq.tombstones(), 2);
+        seq.insert_at(Pos::new(1, 1), "A".to_string());
+        assert_eq!(seq.tombstones(), 1);
     }
 
     #[test]
-    fn test_trait_impl_index() {
-        let seq = setup_seq_abc();
+    fn test_compact() {
+        let mut seq = setup_seq_abc();
+        seq.remove(0);
+        seq.remove(1);
 
-        assert_eq!(seq[0].element_as_ref(), Some(&"A".to_string()));
+        assert_eq!(seq.compact(), 2);
+        assert_eq!(seq.tombstones(), 0);
+        assert_eq!(seq.len(), 1);
+        assert_eq!(seq.nodes.len(), 1);
+        assert_eq!(seq.position_from(0), Some(Pos::new(2, 1)));
+        assert_eq!(seq.compact(), 0);
     }
 
     #[test]
-    fn test_pos_from() {
-        let seq = setup_seq_abc();
+    fn test_compact_before_watermark() {
+        let mut seq = setup_seq_abc();
+        seq.push("D".to_string());
+
+        seq.remove(0);
+        let watermark = seq.watermark();
+        seq.remove(0);
+        seq.remove(0);
 
-        assert_eq!(seq.pos_from(0), Some((1, 1)));
+        assert_eq!(seq.compact_before(watermark), 1);
+        assert_eq!(seq.tombstones(), 2);
+        assert_eq!(seq.nodes[0].position(), &Pos::new(2, 1));
+        assert_eq!(seq.nodes[1].position(), &Pos::new(3, 1));
+        assert_eq!(seq.index_from(Pos::new(4, 1)), Some(0));
+
+        assert_eq!(seq.compact_before(seq.watermark()), 2);
+        assert_eq!(seq.tombstones(), 0);
+        assert_eq!(seq.get(0), Some(&"D".to_string()));
     }
 
     #[test]
-    fn test_trait_impl_index_mut() {
+    fn test_compact_before_keeps_reused_nodes() {
         let mut seq = setup_seq_abc();
-        seq[0].set("H".to_string());
+        seq.remove(1);
+        seq.insert_at(Pos::new(2, 1), "X".to_string());
 
-        assert_eq!(seq[0].element_as_ref(), Some(&"H".to_string()));
+        assert_eq!(seq.compact_before(seq.watermark()), 0);
+        assert_eq!(seq.len(), 3);
+        assert_eq!(seq.get(1), Some(&"X".to_string()));
     }
 
     #[test]
-    fn test_trait_impl_clone() {
-        let seq = setup_seq_abc();
-        let seq2 = seq.clone();
+    fn test_rebalance_ignores_watermark() {
+        let mut seq = setup_seq_abc();
+        let watermark = seq.watermark();
+        seq.remove(0);
 
-        assert_eq!(seq.len(), seq2.len());
-        assert_eq!(seq, seq2);
+        seq.rebalance();
+
+        assert_eq!(seq.tombstones(), 0);
+        assert_eq!(seq.compact_before(watermark), 0);
+        assert_eq!(seq.position_from(0), Some(Pos::new(1, 1)));
     }
 
     #[test]
-    fn test_trait_impl_clone_incl_element_none() {
+    fn test_reserve_shrink_to_fit() {
         let mut seq = setup_seq_abc();
-        seq.remove(1);
-        let seq2 = seq.clone();
 
-        assert_eq!(seq.len(), seq2.len());
-        assert_eq!(seq, seq2);
+        seq.reserve(100);
+        assert_eq!(seq.capacity() >= 103, true);
+
+        seq.remove(0);
+        seq.compact();
+        seq.shrink_to_fit();
+        assert_eq!(seq.capacity(), 2);
+    }
+
+    // Returns the elements in order, skipping empty nodes.
+    fn elements(seq: &Sequence<String>) -> Vec<&str> {
+        seq.nodes
+            .iter()
+            .filter_map(|node| node.element_as_ref())
+            .map(|element| element.as_str())
+            .collect()
     }
 
     #[test]
-    fn test_trait_impl_into_iterator() {
+    fn test_move_to_front() {
         let mut seq = setup_seq_abc();
-        seq.remove(1);
 
-        for node in seq {
-            assert_eq!(node.element.is_some(), true);
-        }
+        assert_eq!(seq.move_to(2, 0), Ok(Some(Pos::new(1, 2))));
+        assert_eq!(elements(&seq), vec!["C", "A", "B"]);
+        assert_eq!(seq.position_from(1), Some(Pos::new(1, 1)));
+        assert_eq!(seq.position_from(2), Some(Pos::new(2, 1)));
+        assert_eq!(seq.len(), 3);
+    }
+
+    #[test]
+    fn test_move_to_back() {
+        let mut seq = setup_seq_abc();
+
+        assert_eq!(seq.move_to(0, 2), Ok(Some(Pos::new(4, 1))));
+        assert_eq!(elements(&seq), vec!["B", "C", "A"]);
+
+        assert_eq!(seq.move_to(0, 10), Ok(Some(Pos::new(5, 1))));
+        assert_eq!(elements(&seq), vec!["C", "A", "B"]);
+    }
+
+    #[test]
+    fn test_move_to_middle() {
+        let mut seq = setup_seq_abc();
+
+        assert_eq!(seq.move_to(0, 1), Ok(Some(Pos::new(5, 2))));
+        assert_eq!(elements(&seq), vec!["B", "A", "C"]);
+
+        assert_eq!(seq.move_to(2, 1), Ok(Some(Pos::new(7, 3))));
+        assert_eq!(elements(&seq), vec!["B", "C", "A"]);
+    }
+
+    #[test]
+    fn test_move_to_same_index_keeps_position() {
+        let mut seq = setup_seq_abc();
+
+        assert_eq!(seq.move_to(1, 1), Ok(Some(Pos::new(2, 1))));
+        assert_eq!(seq, setup_seq_abc());
+    }
+
+    #[test]
+    fn test_move_to_out_of_bounds() {
+        let mut seq = setup_seq_abc();
+
+        assert_eq!(seq.move_to(3, 0), Ok(None));
+        assert_eq!(seq, setup_seq_abc());
+    }
+
+    #[test]
+    fn test_move_to_skips_empty_nodes() {
+        let mut seq = setup_seq_abc();
+        seq.push("D".to_string());
+        seq.remove(1);
+
+        // Elements are A, C, D with an empty node at 2/1 in between A and C.
+        assert_eq!(seq.move_to(2, 1), Ok(Some(Pos::new(5, 2))));
+        assert_eq!(elements(&seq), vec!["A", "D", "C"]);
+        assert_eq!(seq.tombstones(), 2);
+        assert_eq!(seq.nodes.windows(2).all(|pair| pair[0].position() < pair[1].position()), true);
+    }
+
+    #[test]
+    fn test_move_to_leaves_empty_node() {
+        let mut seq = setup_seq_abc();
+        seq.enable_journal();
+
+        assert_eq!(seq.move_to(1, 0), Ok(Some(Pos::new(1, 2))));
+        assert_eq!(seq.tombstones(), 1);
+        assert_eq!(seq.watermark(), 1);
+        assert_eq!(seq.nodes[2].position(), &Pos::new(2, 1));
+        assert_eq!(seq.nodes[2].is_none(), true);
+
+        // Neither insert() nor move_to() hand out the old position again.
+        assert_eq!(seq.try_insert(2, "X".to_string()), Ok(Pos::new(5, 2)));
+        assert_eq!(seq.move_to(0, 2), Ok(Some(Pos::new(8, 3))));
+        assert_eq!(elements(&seq), vec!["A", "X", "B", "C"]);
+        assert_eq!(
+            seq.take_changes(),
+            vec![
+                Change::Moved {
+                    from: Pos::new(2, 1),
+                    to: Pos::new(1, 2)
+                },
+                Change::Inserted { pos: Pos::new(5, 2) },
+                Change::Moved {
+                    from: Pos::new(1, 2),
+                    to: Pos::new(8, 3)
+                },
+            ]
+        );
+        assert_eq!(seq.nodes.windows(2).all(|pair| pair[0].position() < pair[1].position()), true);
+    }
+
+    #[test]
+    fn test_move_to_exhausted() {
+        let mut seq = setup_seq_empty();
+        seq.insert_at(Pos::new(u64::MAX - 1, 1), "A".to_string());
+        seq.insert_at(Pos::new(u64::MAX, 1), "B".to_string());
+        seq.insert_at(Pos::new(1, 1), "C".to_string());
+
+        let err = seq.move_to(0, 2).unwrap_err();
+        assert_eq!(err.lower(), Some(&Pos::new(u64::MAX, 1)));
+        assert_eq!(err.upper(), None);
+
+        let err = seq.move_to(0, 1).unwrap_err();
+        assert_eq!(err.lower(), Some(&Pos::new(u64::MAX - 1, 1)));
+        assert_eq!(err.upper(), Some(&Pos::new(u64::MAX, 1)));
+
+        assert_eq!(elements(&seq), vec!["C", "A", "B"]);
+        assert_eq!(seq.position_from(0), Some(Pos::new(1, 1)));
+    }
+
+    #[test]
+    fn test_move_to_pos() {
+        let mut seq = setup_seq_abc();
+
+        assert_eq!(
+            seq.move_to_pos(Pos::new(3, 1), Placement::Before(Pos::new(1, 1))),
+            Ok(Some(Pos::new(1, 2)))
+        );
+        assert_eq!(elements(&seq), vec!["C", "A", "B"]);
+
+        // The empty node left behind at 3/1 keeps its position.
+        assert_eq!(
+            seq.move_to_pos(Pos::new(1, 2), Placement::After(Pos::new(2, 1))),
+            Ok(Some(Pos::new(5, 2)))
+        );
+        assert_eq!(elements(&seq), vec!["A", "B", "C"]);
+
+        assert_eq!(
+            seq.move_to_pos(Pos::new(1, 1), Placement::After(Pos::new(2, 1))),
+            Ok(Some(Pos::new(7, 3)))
+        );
+        assert_eq!(elements(&seq), vec!["B", "A", "C"]);
+    }
+
+    #[test]
+    fn test_move_to_pos_keeps_position() {
+        let mut seq = setup_seq_abc();
+
+        assert_eq!(
+            seq.move_to_pos(Pos::new(1, 1), Placement::Before(Pos::new(2, 1))),
+            Ok(Some(Pos::new(1, 1)))
+        );
+        assert_eq!(
+            seq.move_to_pos(Pos::new(2, 1), Placement::After(Pos::new(2, 1))),
+            Ok(Some(Pos::new(2, 1)))
+        );
+        assert_eq!(seq, setup_seq_abc());
+    }
+
+    #[test]
+    fn test_move_to_pos_none() {
+        let mut seq = setup_seq_abc();
+        seq.remove(1);
+
+        assert_eq!(seq.move_to_pos(Pos::new(5, 1), Placement::Before(Pos::new(1, 1))), Ok(None));
+        assert_eq!(seq.move_to_pos(Pos::new(1, 1), Placement::After(Pos::new(5, 1))), Ok(None));
+        assert_eq!(seq.move_to_pos(Pos::new(2, 1), Placement::After(Pos::new(3, 1))), Ok(None));
+        assert_eq!(seq.move_to_pos(Pos::new(1, 1), Placement::After(Pos::new(2, 1))), Ok(None));
+        assert_eq!(elements(&seq), vec!["A", "C"]);
+    }
+
+    #[test]
+    fn test_ids_are_unique() {
+        let mut seq = setup_seq_abc();
+        seq.insert(1, "AB".to_string());
+        seq.insert_at(Pos::new(5, 1), "E".to_string());
+
+        let mut ids: Vec<NodeId> = seq.nodes.iter().map(|node| node.id()).collect();
+        ids.sort();
+        ids.dedup();
+        assert_eq!(ids.len(), 5);
+        assert_eq!(seq.id_of(Pos::new(1, 1)), Some(NodeId::from(0)));
+        assert_eq!(seq.id_of(Pos::new(3, 2)), Some(NodeId::from(3)));
+        assert_eq!(seq.id_of(Pos::new(4, 1)), None);
+        assert_eq!(u64::from(NodeId::from(7)), 7);
+        assert_eq!(format!("{}", NodeId::from(7)), "7");
+    }
+
+    #[test]
+    fn test_ids_are_stable() {
+        let mut seq = setup_seq_abc();
+        let id = seq.id_of(Pos::new(3, 1)).unwrap();
+
+        seq.move_to(2, 0).unwrap();
+        seq.insert(0, "0".to_string());
+        seq.rebalance();
+
+        assert_eq!(seq.get_by_id(id), Some(&"C".to_string()));
+        assert_eq!(seq.position_of(id), Some(Pos::new(2, 1)));
+        assert_eq!(seq.clone().get_by_id(id), Some(&"C".to_string()));
+
+        // Overwriting an element keeps its id.
+        seq.insert_at(Pos::new(2, 1), "CC".to_string());
+        assert_eq!(seq.id_of(Pos::new(2, 1)), Some(id));
+    }
+
+    #[test]
+    fn test_ids_are_not_reused() {
+        let mut seq = setup_seq_abc();
+        let id = seq.id_of(Pos::new(2, 1)).unwrap();
+        seq.remove(1);
+
+        // The empty node at 2/1 is refilled by the new element, which gets a new id.
+        seq.insert_at(Pos::new(2, 1), "BB".to_string());
+        assert_eq!(seq.id_of(Pos::new(2, 1)), Some(NodeId::from(3)));
+        seq.remove(1);
+        seq.insert_at(Pos::new(2, 1), "BBB".to_string());
+        assert_eq!(seq.id_of(Pos::new(2, 1)), Some(NodeId::from(4)));
+
+        assert_eq!(seq.get_by_id(id), None);
+        assert_eq!(seq.position_of(id), None);
+    }
+
+    #[test]
+    fn test_remove_by_id() {
+        let mut seq = setup_seq_abc();
+        let id = seq.id_of(Pos::new(2, 1)).unwrap();
+
+        assert_eq!(seq.remove_by_id(id), Some("B".to_string()));
+        assert_eq!(seq.remove_by_id(id), None);
+        assert_eq!(seq.remove_by_id(NodeId::from(9)), None);
+        assert_eq!(elements(&seq), vec!["A", "C"]);
+        assert_eq!(seq.tombstones(), 1);
+        assert_eq!(seq.watermark(), 1);
+    }
+
+    #[test]
+    fn test_move_by_id() {
+        let mut seq = setup_seq_abc();
+        let (a, b, c) = (NodeId::from(0), NodeId::from(1), NodeId::from(2));
+
+        assert_eq!(seq.move_by_id(c, Placement::Before(a)), Ok(Some(Pos::new(1, 2))));
+        assert_eq!(elements(&seq), vec!["C", "A", "B"]);
+
+        assert_eq!(seq.move_by_id(a, Placement::After(b)), Ok(Some(Pos::new(5, 2))));
+        assert_eq!(elements(&seq), vec!["C", "B", "A"]);
+        assert_eq!(seq.position_of(a), Some(Pos::new(5, 2)));
+
+        seq.remove_by_id(b);
+        assert_eq!(seq.move_by_id(a, Placement::Before(b)), Ok(None));
+        assert_eq!(seq.move_by_id(b, Placement::Before(a)), Ok(None));
+    }
+
+    // Returns the elements of the nodes in order.
+    fn node_elements<'a>(nodes: impl IntoIterator<Item = &'a Node<String>>) -> Vec<&'a str> {
+        nodes
+            .into_iter()
+            .filter_map(|node| node.element_as_ref())
+            .map(|element| element.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn test_range() {
+        let mut seq = setup_seq_abc();
+        seq.push("D".to_string());
+        seq.remove(1);
+
+        assert_eq!(node_elements(seq.range(Pos::new(1, 1)..Pos::new(3, 1))), vec!["A"]);
+        assert_eq!(node_elements(seq.range(Pos::new(1, 1)..=Pos::new(3, 1))), vec!["A", "C"]);
+        assert_eq!(node_elements(seq.range(Pos::new(3, 2)..)), vec!["C", "D"]);
+        assert_eq!(node_elements(seq.range(..Pos::new(7, 2))), vec!["A", "C"]);
+        assert_eq!(node_elements(seq.range(..)), vec!["A", "C", "D"]);
+        assert_eq!(node_elements(seq.range(Pos::new(4, 1)..Pos::new(1, 1))), Vec::<&str>::new());
+        assert_eq!(seq.range(..).next_back().map(|node| node.position()), Some(&Pos::new(4, 1)));
+
+        let bounds = (Bound::Excluded(Pos::new(1, 1)), Bound::Excluded(Pos::new(4, 1)));
+        assert_eq!(node_elements(seq.range(bounds)), vec!["C"]);
+
+        let mut range = seq.range(..);
+        range.next();
+        assert_eq!(
+            format!("{:?}", range),
+            format!("{:?}", [seq.node(1).unwrap(), seq.node(2).unwrap()])
+        );
+    }
+
+    #[test]
+    fn test_range_matches_sorted_positions() {
+        let mut rng = StdRng::seed_from_u64(14);
+        let mut seq: Sequence<String> = Sequence::new();
+        for i in 0..200 {
+            seq.insert(rng.gen_range(0..=seq.len()), i.to_string());
+        }
+        let positions: Vec<Pos> = seq.nodes.iter().map(|node| *node.position()).collect();
+
+        for _ in 0..50 {
+            let (a, b) = (positions[rng.gen_range(0..200)], positions[rng.gen_range(0..200)]);
+            let expected: Vec<&Pos> = positions.iter().filter(|pos| a <= **pos && **pos < b).collect();
+            let actual: Vec<&Pos> = seq.range(a..b).map(|node| node.position()).collect();
+            assert_eq!(actual, expected);
+        }
+    }
+
+    #[test]
+    fn test_range_by_index() {
+        let mut seq = setup_seq_abc();
+        seq.push("D".to_string());
+        seq.remove(1);
+
+        assert_eq!(node_elements(seq.range_by_index(1..)), vec!["C", "D"]);
+        assert_eq!(node_elements(seq.range_by_index(0..2)), vec!["A", "C"]);
+        assert_eq!(node_elements(seq.range_by_index(1..=1)), vec!["C"]);
+        assert_eq!(node_elements(seq.range_by_index(2..10)), vec!["D"]);
+        assert_eq!(
+            node_elements(seq.range_by_index((Bound::Included(2), Bound::Excluded(1)))),
+            Vec::<&str>::new()
+        );
+        assert_eq!(node_elements(seq.range_by_index(3..)), Vec::<&str>::new());
+    }
+
+    #[test]
+    fn test_split_off_at() {
+        let mut seq = setup_seq_abc();
+        seq.push("D".to_string());
+        seq.remove(2);
+
+        let other = seq.split_off_at(Pos::new(5, 2));
+        assert_eq!(elements(&seq), vec!["A", "B"]);
+        assert_eq!(elements(&other), vec!["D"]);
+        assert_eq!((seq.len(), seq.tombstones()), (2, 0));
+        assert_eq!((other.len(), other.tombstones()), (1, 1));
+        assert_eq!(other.position_of(NodeId::from(3)), Some(Pos::new(4, 1)));
+
+        let other = seq.split_off_at(Pos::new(5, 1));
+        assert_eq!(other.is_empty(), true);
+        assert_eq!(seq.len(), 2);
+    }
+
+    #[test]
+    fn test_drain_range() {
+        let mut seq = setup_seq_abc();
+        seq.push("D".to_string());
+
+        let drained = seq.drain_range(Pos::new(2, 1)..Pos::new(4, 1));
+        assert_eq!(node_elements(&drained), vec!["B", "C"]);
+        assert_eq!(drained[1].id(), NodeId::from(2));
+        assert_eq!(drained[1].position(), &Pos::new(3, 1));
+        assert_eq!(elements(&seq), vec!["A", "D"]);
+        assert_eq!(seq.tombstones(), 2);
+        assert_eq!(seq.watermark(), 2);
+
+        assert_eq!(seq.drain_range(Pos::new(2, 1)..Pos::new(4, 1)), Vec::new());
+    }
+
+    // Returns a sequence with elements A, C, D and empty nodes at positions 2/1 and 5/1.
+    fn setup_seq_with_tombstones() -> Sequence<String> {
+        let mut seq = setup_seq_abc();
+        seq.push("D".to_string());
+        seq.push("E".to_string());
+        seq.remove_at(Pos::new(2, 1));
+        seq.remove_at(Pos::new(5, 1));
+
+        seq
+    }
+
+    #[test]
+    fn test_iter_skips_empty_nodes() {
+        let seq = setup_seq_with_tombstones();
+
+        let mut iter = seq.iter();
+        assert_eq!(iter.len(), 3);
+        assert_eq!(iter.next().map(|node| node.position()), Some(&Pos::new(1, 1)));
+        assert_eq!(iter.next_back().map(|node| node.position()), Some(&Pos::new(4, 1)));
+        assert_eq!(iter.len(), 1);
+        assert_eq!(iter.next().map(|node| node.position()), Some(&Pos::new(3, 1)));
+        assert_eq!(iter.next(), None);
+        assert_eq!(iter.next_back(), None);
+
+        assert_eq!(node_elements(&seq), vec!["A", "C", "D"]);
+        assert_eq!(seq.iter_with_tombstones().len(), 5);
+    }
+
+    #[test]
+    fn test_iter_mut() {
+        let mut seq = setup_seq_with_tombstones();
+
+        for element in seq.iter_mut().rev().take(2) {
+            element.push('!');
+        }
+        assert_eq!(seq.iter_mut().len(), 3);
+        assert_eq!(elements(&seq), vec!["A", "C!", "D!"]);
+        assert_eq!(seq.tombstones(), 2);
+    }
+
+    #[test]
+    fn test_iterators_debug() {
+        let mut seq = setup_seq_with_tombstones();
+
+        let mut iter = seq.iter();
+        iter.next();
+        assert_eq!(
+            format!("{:?}", iter),
+            format!("{:?}", [seq.node(1).unwrap(), seq.node(2).unwrap()])
+        );
+        assert_eq!(iter.len(), 2);
+
+        assert_eq!(format!("{:?}", seq.values()), r#"["A", "C", "D"]"#);
+        let mut positions = seq.positions();
+        positions.nth(1);
+        assert_eq!(format!("{:?}", positions), "[4/1 (4)]");
+        let mut enumerate_positions = seq.enumerate_positions();
+        enumerate_positions.next();
+        enumerate_positions.next_back();
+        assert_eq!(format!("{:?}", enumerate_positions), "[(1, 3/1 (3))]");
+
+        let mut iter_mut = seq.iter_mut();
+        iter_mut.next_back();
+        assert_eq!(format!("{:?}", iter_mut), r#"["A", "C"]"#);
+    }
+
+    #[test]
+    fn test_values_positions() {
+        let seq = setup_seq_with_tombstones();
+
+        assert_eq!(seq.values().collect::<Vec<_>>(), vec!["A", "C", "D"]);
+        assert_eq!(seq.values().rev().collect::<Vec<_>>(), vec!["D", "C", "A"]);
+        assert_eq!(seq.values().len(), 3);
+        assert_eq!(
+            seq.positions().collect::<Vec<_>>(),
+            vec![&Pos::new(1, 1), &Pos::new(3, 1), &Pos::new(4, 1)]
+        );
+        assert_eq!(seq.positions().next_back(), Some(&Pos::new(4, 1)));
+        assert_eq!(seq.positions().len(), 3);
+    }
+
+    #[test]
+    fn test_enumerate_positions() {
+        let seq = setup_seq_with_tombstones();
+
+        assert_eq!(
+            seq.enumerate_positions().collect::<Vec<_>>(),
+            vec![(0, &Pos::new(1, 1)), (1, &Pos::new(3, 1)), (2, &Pos::new(4, 1))]
+        );
+
+        let mut iter = seq.enumerate_positions();
+        assert_eq!(iter.next(), Some((0, &Pos::new(1, 1))));
+        assert_eq!(iter.next_back(), Some((2, &Pos::new(4, 1))));
+        assert_eq!(iter.len(), 1);
+        assert_eq!(iter.next_back(), Some((1, &Pos::new(3, 1))));
+        assert_eq!(iter.next(), None);
+
+        for (index, position) in seq.enumerate_positions() {
+            assert_eq!(
+                seq.range_by_index(index..=index).next().map(|node| node.position()),
+                Some(position)
+            );
+        }
+    }
+
+    #[test]
+    fn test_from_iter() {
+        let seq: Sequence<String> = ["A", "B", "C"].into_iter().map(String::from).collect();
+
+        assert_eq!(seq, setup_seq_abc());
+        assert_eq!(Sequence::<String>::from_iter(Vec::new()), setup_seq_empty());
+    }
+
+    #[test]
+    fn test_extend() {
+        let mut seq = setup_seq_abc();
+        seq.remove_at(Pos::new(3, 1));
+
+        seq.extend(["D".to_string(), "E".to_string()]);
+        assert_eq!(elements(&seq), vec!["A", "B", "D", "E"]);
+        assert_eq!(
+            seq.positions().skip(2).collect::<Vec<_>>(),
+            vec![&Pos::new(4, 1), &Pos::new(5, 1)]
+        );
+        assert_eq!(seq.len(), 4);
+    }
+
+    #[test]
+    fn test_insert_many() {
+        let mut seq = setup_seq_abc();
+        seq.remove_at(Pos::new(2, 1));
+
+        let elements_ab = ["AB1", "AB2", "AB3"].into_iter().map(String::from);
+        assert_eq!(
+            seq.try_insert_many(1, elements_ab),
+            Ok(vec![Pos::new(9, 4), Pos::new(5, 2), Pos::new(11, 4)])
+        );
+        assert_eq!(elements(&seq), vec!["A", "AB1", "AB2", "AB3", "C"]);
+        assert_eq!(seq.len(), 5);
+        assert_eq!(seq.id_of(Pos::new(11, 4)), Some(NodeId::from(5)));
+
+        seq.insert_many(0, ["0".to_string()]);
+        seq.insert_many(10, ["D".to_string()]);
+        assert_eq!(seq.try_insert_many(2, Vec::new()), Ok(Vec::new()));
+        assert_eq!(elements(&seq), vec!["0", "A", "AB1", "AB2", "AB3", "C", "D"]);
+        assert_eq!(seq.nodes.windows(2).all(|pair| pair[0].position() < pair[1].position()), true);
+    }
+
+    #[test]
+    fn test_insert_many_keeps_denominators_small() {
+        let mut seq: Sequence<usize> = Sequence::new();
+        seq.push(0);
+        seq.push(1);
+
+        // Inserting 1_000 elements one by one in between exhausts `Pos`, as denominators grow like Fibonacci numbers.
+        seq.insert_many(1, 0..1_000);
+        assert_eq!(seq.len(), 1_002);
+        assert_eq!(seq.nodes.iter().all(|node| node.denom() <= 1_024), true);
+    }
+
+    #[test]
+    fn test_insert_many_exhausted() {
+        let mut seq = setup_seq_empty();
+        seq.insert_at(Pos::new(u64::MAX - 1, 1), "A".to_string());
+        seq.insert_at(Pos::new(u64::MAX, 1), "B".to_string());
+
+        let err = seq.try_insert_many(2, ["C".to_string()]).unwrap_err();
+        assert_eq!(err.lower(), Some(&Pos::new(u64::MAX, 1)));
+        assert_eq!(err.upper(), None);
+        assert_eq!(seq.len(), 2);
+    }
+
+    #[test]
+    #[should_panic(expected = "no position left after")]
+    fn test_extend_exhausted_panics() {
+        let mut seq = setup_seq_empty();
+        seq.insert_at(Pos::new(u64::MAX, 1), "A".to_string());
+
+        seq.extend(["B".to_string()]);
+    }
+
+    #[test]
+    fn test_indices_skip_empty_nodes() {
+        let mut seq = setup_seq_with_tombstones();
+
+        assert_eq!(seq.get(1), Some(&"C".to_string()));
+        assert_eq!(seq.get(3), None);
+        assert_eq!(seq.get_mut(2), Some(&mut "D".to_string()));
+        assert_eq!(seq.get_mut(3), None);
+        assert_eq!(seq.position_from(1), Some(Pos::new(3, 1)));
+        assert_eq!(seq.position_from(3), None);
+        assert_eq!(seq.index_from(Pos::new(3, 1)), Some(1));
+        assert_eq!(seq.index_from(Pos::new(2, 1)), None);
+        assert_eq!(seq[2], "D".to_string());
+
+        assert_eq!(seq.remove(1), Some("C".to_string()));
+        assert_eq!(seq.remove(2), None);
+        assert_eq!(elements(&seq), vec!["A", "D"]);
+    }
+
+    #[test]
+    fn test_insert_keeps_empty_node_before_index() {
+        let mut seq = setup_seq_with_tombstones();
+        seq.enable_journal();
+
+        assert_eq!(seq.try_insert(1, "B".to_string()), Ok(Pos::new(5, 2)));
+        assert_eq!(seq.try_insert(3, "CD".to_string()), Ok(Pos::new(7, 2)));
+        assert_eq!(elements(&seq), vec!["A", "B", "C", "CD", "D"]);
+        assert_eq!(seq.tombstones(), 2);
+        assert_eq!(seq.nodes[1].position(), &Pos::new(2, 1));
+        assert_eq!(seq.nodes[1].is_none(), true);
+        assert_eq!(
+            seq.take_changes(),
+            vec![
+                Change::Inserted { pos: Pos::new(5, 2) },
+                Change::Inserted { pos: Pos::new(7, 2) }
+            ]
+        );
+    }
+
+    #[test]
+    fn test_node() {
+        let seq = setup_seq_with_tombstones();
+
+        assert_eq!(seq.node(1).map(|node| node.position()), Some(&Pos::new(3, 1)));
+        assert_eq!(seq.node(1).and_then(|node| node.element_as_ref()), Some(&"C".to_string()));
+        assert_eq!(seq.node(3), None);
+        assert_eq!(seq.node_at(Pos::new(3, 1)).map(|node| node.id()), Some(NodeId::from(2)));
+        assert_eq!(seq.node_at(Pos::new(2, 1)), None);
+        assert_eq!(seq.node_at(Pos::new(7, 1)), None);
+    }
+
+    #[test]
+    #[allow(deprecated)]
+    fn test_node_at_slot() {
+        let seq = setup_seq_with_tombstones();
+
+        assert_eq!(seq.node_at_slot(1).map(|node| node.is_none()), Some(true));
+        assert_eq!(seq.node_at_slot(2).map(|node| node.position()), Some(&Pos::new(3, 1)));
+        assert_eq!(seq.node_at_slot(5), None);
+    }
+
+    #[test]
+    fn test_get_at() {
+        let mut seq = setup_seq_with_tombstones();
+
+        assert_eq!(seq.get_at(Pos::new(3, 1)), Some(&"C".to_string()));
+        assert_eq!(seq.get_at(Pos::new(6, 2)), Some(&"C".to_string()));
+        assert_eq!(seq.get_at(Pos::new(2, 1)), None);
+        assert_eq!(seq.get_at(Pos::new(7, 2)), None);
+
+        seq.get_at_mut(Pos::new(3, 1)).unwrap().push('!');
+        assert_eq!(seq.get(1), Some(&"C!".to_string()));
+        assert_eq!(seq.get_at_mut(Pos::new(2, 1)), None);
+        assert_eq!(seq.get_at_mut(Pos::new(7, 2)), None);
+    }
+
+    #[test]
+    fn test_contains_pos() {
+        let seq = setup_seq_with_tombstones();
+
+        assert_eq!(seq.contains_pos(Pos::new(1, 1)), true);
+        assert_eq!(seq.contains_pos(Pos::new(4, 1)), true);
+        assert_eq!(seq.contains_pos(Pos::new(2, 1)), false);
+        assert_eq!(seq.contains_pos(Pos::new(1, 2)), false);
+    }
+
+    #[test]
+    fn test_predecessor_successor() {
+        let seq = setup_seq_with_tombstones();
+        let position = |node: Option<&Node<String>>| node.map(|node| *node.position());
+
+        // Elements are at 1/1, 3/1 and 4/1, empty nodes at 2/1 and 5/1.
+        assert_eq!(position(seq.predecessor(Pos::new(3, 1))), Some(Pos::new(1, 1)));
+        assert_eq!(position(seq.predecessor(Pos::new(7, 2))), Some(Pos::new(3, 1)));
+        assert_eq!(position(seq.predecessor(Pos::new(9, 1))), Some(Pos::new(4, 1)));
+        assert_eq!(position(seq.predecessor(Pos::new(1, 1))), None);
+
+        assert_eq!(position(seq.successor(Pos::new(1, 1))), Some(Pos::new(3, 1)));
+        assert_eq!(position(seq.successor(Pos::new(1, 2))), Some(Pos::new(1, 1)));
+        assert_eq!(position(seq.successor(Pos::new(7, 2))), Some(Pos::new(4, 1)));
+        assert_eq!(position(seq.successor(Pos::new(4, 1))), None);
+    }
+
+    #[test]
+    fn test_position_between() {
+        let seq = setup_seq_with_tombstones();
+
+        assert_eq!(seq.position_between(Pos::new(3, 1), Pos::new(4, 1)), Ok(Pos::new(7, 2)));
+        // Skips the empty node at 2/1 in between.
+        assert_eq!(seq.position_between(Pos::new(1, 1), Pos::new(3, 1)), Ok(Pos::new(3, 2)));
+        assert_eq!(seq.position_between(Pos::new(4, 1), Pos::new(9, 1)), Ok(Pos::new(9, 2)));
+        assert_eq!(seq.position_between(Pos::new(1, 2), Pos::new(1, 1)), Ok(Pos::new(2, 3)));
+
+        let position = seq.position_between(Pos::new(1, 1), Pos::new(4, 1)).unwrap();
+        assert_eq!(seq.node_at(position), None);
+        assert_eq!(seq.nodes.iter().all(|node| node.position() != &position), true);
+
+        let err = seq.position_between(Pos::new(3, 1), Pos::new(3, 1)).unwrap_err();
+        assert_eq!((err.lower(), err.upper()), (Some(&Pos::new(3, 1)), Some(&Pos::new(3, 1))));
+
+        let mut seq = setup_seq_empty();
+        seq.insert_at(Pos::new(u64::MAX - 1, 1), "A".to_string());
+        let err = seq
+            .position_between(Pos::new(u64::MAX - 1, 1), Pos::new(u64::MAX, 1))
+            .unwrap_err();
+        assert_eq!(err.upper(), Some(&Pos::new(u64::MAX, 1)));
+    }
+
+    #[test]
+    fn test_entry_occupied() {
+        let mut seq = setup_seq_abc();
+
+        let Entry::Occupied(mut entry) = seq.entry(Pos::new(2, 1)) else {
+            panic!("expected an occupied entry");
+        };
+        assert_eq!(entry.position(), &Pos::new(2, 1));
+        assert_eq!(entry.id(), NodeId::from(1));
+        assert_eq!(entry.get(), &"B".to_string());
+        assert_eq!(entry.insert("BB".to_string()), "B".to_string());
+        assert_eq!(entry.remove(), "BB".to_string());
+
+        assert_eq!(elements(&seq), vec!["A", "C"]);
+        assert_eq!(seq.tombstones(), 1);
+    }
+
+    #[test]
+    fn test_entry_vacant() {
+        let mut seq = setup_seq_abc();
+
+        let entry = seq.entry(Pos::new(5, 2));
+        assert_eq!(matches!(entry, Entry::Vacant(_)), true);
+        assert_eq!(entry.position(), &Pos::new(5, 2));
+        entry.or_insert("BC".to_string()).push('!');
+
+        assert_eq!(elements(&seq), vec!["A", "B", "BC!", "C"]);
+        assert_eq!(seq.id_of(Pos::new(5, 2)), Some(NodeId::from(3)));
+        assert_eq!(seq.len(), 4);
+    }
+
+    #[test]
+    fn test_entry_tombstoned() {
+        let mut seq = setup_seq_abc();
+        seq.remove(1);
+
+        let Entry::Tombstoned(entry) = seq.entry(Pos::new(2, 1)) else {
+            panic!("expected a tombstoned entry");
+        };
+        assert_eq!(entry.position(), &Pos::new(2, 1));
+        assert_eq!(entry.id(), NodeId::from(1));
+        entry.revive("B!".to_string());
+
+        assert_eq!(elements(&seq), vec!["A", "B!", "C"]);
+        assert_eq!(seq.id_of(Pos::new(2, 1)), Some(NodeId::from(1)));
+        assert_eq!(seq.tombstones(), 0);
+        assert_eq!(seq.len(), 3);
+    }
+
+    #[test]
+    fn test_entry_or_insert() {
+        let mut seq = setup_seq_abc();
+        seq.remove(2);
+
+        assert_eq!(seq.entry(Pos::new(1, 1)).or_insert("0".to_string()), &mut "A".to_string());
+        assert_eq!(
+            seq.entry(Pos::new(3, 1)).or_insert_with(|| "C".to_string()),
+            &mut "C".to_string()
+        );
+        assert_eq!(seq.entry(Pos::new(4, 1)).or_default(), &mut String::new());
+        assert_eq!(elements(&seq), vec!["A", "B", "C", ""]);
+        assert_eq!(seq.entry(Pos::new(1, 2)).position(), &Pos::new(1, 2));
+        assert_eq!(seq.entry(Pos::new(3, 1)).position(), &Pos::new(3, 1));
+    }
+
+    #[test]
+    fn test_entry_and_modify() {
+        let mut seq = setup_seq_abc();
+        seq.remove(2);
+
+        seq.entry(Pos::new(1, 1))
+            .and_modify(|element| element.push('!'))
+            .or_insert("0".to_string());
+        seq.entry(Pos::new(1, 2))
+            .and_modify(|element| element.push('!'))
+            .or_insert("0".to_string());
+        seq.entry(Pos::new(3, 1))
+            .and_modify(|element| element.push('!'))
+            .or_insert("C".to_string());
+
+        assert_eq!(elements(&seq), vec!["0", "A!", "B", "C"]);
+    }
+
+    #[test]
+    fn test_entry_replay_is_idempotent() {
+        let events = [(Pos::new(1, 1), "A"), (Pos::new(3, 2), "AB"), (Pos::new(2, 1), "B")];
+        let mut seq: Sequence<String> = Sequence::new();
+
+        for _ in 0..2 {
+            for (position, element) in events {
+                seq.entry(position).or_insert(element.to_string());
+            }
+        }
+
+        assert_eq!(elements(&seq), vec!["A", "AB", "B"]);
+        assert_eq!(seq.len(), 3);
+    }
+
+    #[test]
+    fn test_longest_increasing_subsequence() {
+        let items = [3, 1, 4, 1, 5, 9, 2, 6];
+        let refs: Vec<&i32> = items.iter().collect();
+
+        let keep = longest_increasing_subsequence(&refs);
+        let kept: Vec<i32> = items
+            .iter()
+            .zip(keep)
+            .filter(|(_, keep)| *keep)
+            .map(|(item, _)| *item)
+            .collect();
+        assert_eq!(kept, vec![1, 4, 5, 6]);
+        assert_eq!(longest_increasing_subsequence::<i32>(&[]), Vec::<bool>::new());
+    }
+
+    #[test]
+    fn test_sort_by_key() {
+        let mut seq: Sequence<String> = ["A", "C", "B", "D"].into_iter().map(String::from).collect();
+
+        // Only B moves in between A and C.
+        let changes = seq.sort_by_key(|element| element.clone()).unwrap();
+        assert_eq!(changes, vec![(Pos::new(3, 1), Pos::new(3, 2))]);
+        assert_eq!(elements(&seq), vec!["A", "B", "C", "D"]);
+        assert_eq!(seq.sort_by_key(|element| element.clone()), Ok(Vec::new()));
+    }
+
+    #[test]
+    fn test_sort_by_is_stable() {
+        let mut seq: Sequence<String> = ["b1", "a1", "b2", "a2"].into_iter().map(String::from).collect();
+
+        seq.sort_by(|a, b| a[..1].cmp(&b[..1])).unwrap();
+        assert_eq!(elements(&seq), vec!["a1", "a2", "b1", "b2"]);
+    }
+
+    #[test]
+    fn test_sort_changes_minimal_positions() {
+        let mut rng = StdRng::seed_from_u64(20);
+        let mut seq: Sequence<usize> = (0..1_000).map(|_| rng.gen_range(0..10_000)).collect();
+        seq.remove(10);
+        seq.remove(500);
+        let ids: Vec<(NodeId, usize)> = seq.iter().map(|node| (node.id(), *node.element_as_ref().unwrap())).collect();
+
+        let positions: Vec<Pos> = seq.positions().copied().collect();
+        let mut order: Vec<usize> = (0..seq.len()).collect();
+        order.sort_by_key(|&index| seq[index]);
+        let old: Vec<&Pos> = order.iter().map(|&index| &positions[index]).collect();
+        let kept = longest_increasing_subsequence(&old).into_iter().filter(|keep| *keep).count();
+
+        let changes = seq.sort_by_key(|element| *element).unwrap();
+        assert_eq!(changes.len(), seq.len() - kept);
+        assert_eq!(
+            seq.values().collect::<Vec<_>>().windows(2).all(|pair| pair[0] <= pair[1]),
+            true
+        );
+        assert_eq!(seq.nodes.windows(2).all(|pair| pair[0].position() < pair[1].position()), true);
+        assert_eq!(seq.tombstones(), 2);
+        for (id, element) in ids {
+            assert_eq!(seq.get_by_id(id), Some(&element));
+        }
+    }
+
+    #[test]
+    fn test_sort_skips_empty_nodes() {
+        let mut seq: Sequence<String> = ["B", "C", "A"].into_iter().map(String::from).collect();
+        seq.insert(0, "X".to_string());
+        seq.remove(0);
+
+        // A moves in front of B and the empty node at 1/2 in between.
+        let changes = seq.sort_by_key(|element| element.clone()).unwrap();
+        assert_eq!(changes, vec![(Pos::new(3, 1), Pos::new(1, 3))]);
+        assert_eq!(elements(&seq), vec!["A", "B", "C"]);
+        assert_eq!(seq.nodes[1].position(), &Pos::new(1, 2));
+        assert_eq!(seq.nodes[1].is_none(), true);
+    }
+
+    #[test]
+    fn test_sort_exhausted() {
+        let mut seq = setup_seq_empty();
+        seq.insert_at(Pos::new(1, 1), "C".to_string());
+        seq.insert_at(Pos::new(u64::MAX - 1, 1), "A".to_string());
+        seq.insert_at(Pos::new(u64::MAX, 1), "B".to_string());
+
+        let err = seq.sort_by_key(|element| element.clone()).unwrap_err();
+        assert_eq!(err.lower(), Some(&Pos::new(u64::MAX, 1)));
+        assert_eq!(err.upper(), None);
+        assert_eq!(elements(&seq), vec!["C", "A", "B"]);
+    }
+
+    #[test]
+    fn test_reverse() {
+        let mut seq = setup_seq_abc();
+
+        let changes = seq.reverse().unwrap();
+        assert_eq!(changes.len(), 2);
+        assert_eq!(elements(&seq), vec!["C", "B", "A"]);
+        assert_eq!(seq.nodes.windows(2).all(|pair| pair[0].position() < pair[1].position()), true);
+
+        let mut seq = setup_seq_empty();
+        assert_eq!(seq.reverse(), Ok(Vec::new()));
+    }
+
+    #[test]
+    fn test_retain() {
+        let mut seq: Sequence<usize> = (0..6).collect();
+        seq.remove(0);
+
+        seq.enable_journal();
+        let changes = seq.retain(|element| element % 2 == 0);
+        assert_eq!(
+            changes,
+            vec![
+                Change::Removed { pos: Pos::new(2, 1) },
+                Change::Removed { pos: Pos::new(4, 1) },
+                Change::Removed { pos: Pos::new(6, 1) },
+            ]
+        );
+        assert_eq!(seq.take_changes(), changes);
+        assert_eq!(seq.values().collect::<Vec<_>>(), vec![&2, &4]);
+        assert_eq!(seq.positions().collect::<Vec<_>>(), vec![&Pos::new(3, 1), &Pos::new(5, 1)]);
+        assert_eq!(seq.tombstones(), 4);
+    }
+
+    #[test]
+    fn test_dedup_by_key() {
+        let mut seq: Sequence<String> = ["a", "A", "b", "x", "B", "b"].into_iter().map(String::from).collect();
+        seq.remove(3);
+
+        // b and B are consecutive, as x has been removed.
+        seq.enable_journal();
+        let changes = seq.dedup_by_key(|element| element.to_lowercase());
+        assert_eq!(
+            changes,
+            vec![
+                Change::Removed { pos: Pos::new(2, 1) },
+                Change::Removed { pos: Pos::new(5, 1) },
+                Change::Removed { pos: Pos::new(6, 1) },
+            ]
+        );
+        assert_eq!(seq.take_changes(), changes);
+        assert_eq!(elements(&seq), vec!["a", "b"]);
+        assert_eq!(seq.positions().collect::<Vec<_>>(), vec![&Pos::new(1, 1), &Pos::new(3, 1)]);
+    }
+
+    #[test]
+    fn test_journal_disabled() {
+        let mut seq = setup_seq_abc();
+
+        assert_eq!(seq.is_journal_enabled(), false);
+        seq.remove(0);
+        assert_eq!(seq.take_changes(), Vec::new());
+    }
+
+    #[test]
+    fn test_journal() {
+        let mut seq = setup_seq_abc();
+        seq.enable_journal();
+        seq.enable_journal();
+        assert_eq!(seq.is_journal_enabled(), true);
+
+        seq.insert(1, "AB".to_string());
+        seq.push("D".to_string());
+        seq.insert_at(Pos::new(5, 1), "E".to_string());
+        seq.insert_at(Pos::new(5, 1), "EE".to_string());
+        seq.remove(0);
+        seq.remove_at(Pos::new(5, 1));
+        seq.insert_at(Pos::new(5, 1), "E".to_string());
+
+        assert_eq!(
+            seq.take_changes(),
+            vec![
+                Change::Inserted { pos: Pos::new(3, 2) },
+                Change::Inserted { pos: Pos::new(4, 1) },
+                Change::Inserted { pos: Pos::new(5, 1) },
+                Change::Updated { pos: Pos::new(5, 1) },
+                Change::Removed { pos: Pos::new(1, 1) },
+                Change::Removed { pos: Pos::new(5, 1) },
+                Change::Inserted { pos: Pos::new(5, 1) },
+            ]
+        );
+        assert_eq!(seq.take_changes(), Vec::new());
+
+        seq.disable_journal();
+        seq.push("F".to_string());
+        seq.enable_journal();
+        assert_eq!(seq.take_changes(), Vec::new());
+    }
+
+    #[test]
+    fn test_journal_moves() {
+        let mut seq = setup_seq_abc();
+        seq.enable_journal();
+
+        seq.move_to(2, 0).unwrap();
+        seq.move_to(1, 1).unwrap();
+        seq.rebalance();
+
+        assert_eq!(
+            seq.take_changes(),
+            vec![
+                Change::Moved {
+                    from: Pos::new(3, 1),
+                    to: Pos::new(1, 2)
+                },
+                Change::Moved {
+                    from: Pos::new(1, 2),
+                    to: Pos::new(1, 1)
+                },
+                Change::Moved {
+                    from: Pos::new(1, 1),
+                    to: Pos::new(2, 1)
+                },
+                Change::Moved {
+                    from: Pos::new(2, 1),
+                    to: Pos::new(3, 1)
+                },
+            ]
+        );
+
+        seq.sort_by_key(|element| element.clone()).unwrap();
+        assert_eq!(seq.take_changes().len(), 1);
+    }
+
+    #[test]
+    fn test_journal_entry_and_batch() {
+        let mut seq = setup_seq_abc();
+        seq.remove(1);
+        seq.enable_journal();
+
+        seq.entry(Pos::new(1, 1)).and_modify(|element| element.push('!'));
+        if let Entry::Occupied(mut entry) = seq.entry(Pos::new(1, 1)) {
+            entry.insert("A".to_string());
+        }
+        seq.entry(Pos::new(2, 1)).or_insert("B".to_string());
+        seq.entry(Pos::new(4, 1)).or_insert("D".to_string());
+        seq.insert_many(0, ["0".to_string()]);
+        seq.retain(|element| element != "C");
+
+        assert_eq!(
+            seq.take_changes(),
+            vec![
+                Change::Updated { pos: Pos::new(1, 1) },
+                Change::Inserted { pos: Pos::new(2, 1) },
+                Change::Inserted { pos: Pos::new(4, 1) },
+                Change::Inserted { pos: Pos::new(1, 2) },
+                Change::Removed { pos: Pos::new(3, 1) },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_journal_split_off_at() {
+        let mut seq = setup_seq_abc();
+        seq.enable_journal();
+
+        let mut other = seq.split_off_at(Pos::new(2, 1));
+        assert_eq!(
+            seq.take_changes(),
+            vec![
+                Change::Removed { pos: Pos::new(2, 1) },
+                Change::Removed { pos: Pos::new(3, 1) }
+            ]
+        );
+        assert_eq!(
+            other.take_changes(),
+            vec![
+                Change::Inserted { pos: Pos::new(2, 1) },
+                Change::Inserted { pos: Pos::new(3, 1) }
+            ]
+        );
+
+        let mut seq = setup_seq_abc();
+        assert_eq!(seq.split_off_at(Pos::new(2, 1)).is_journal_enabled(), false);
+    }
+
+    #[test]
+    fn test_journal_replays_to_vec() {
+        let mut rng = StdRng::seed_from_u64(21);
+        let mut seq: Sequence<usize> = (0..20).collect();
+        let mut table: Vec<(Pos, usize)> = seq
+            .iter()
+            .map(|node| (*node.position(), *node.element_as_ref().unwrap()))
+            .collect();
+        seq.enable_journal();
+
+        for i in 0..500 {
+            match rng.gen_range(0..4) {
+                0 => seq.insert(rng.gen_range(0..=seq.len()), i),
+                1 if !seq.is_empty() => {
+                    seq.remove(rng.gen_range(0..seq.len()));
+                }
+                2 if !seq.is_empty() => {
+                    seq.move_to(rng.gen_range(0..seq.len()), rng.gen_range(0..seq.len())).unwrap();
+                }
+                _ => seq.push(i),
+            }
+
+            // Flush deltas into a table of rows ordered by position.
+            for change in seq.take_changes() {
+                match change {
+                    Change::Inserted { pos } | Change::Updated { pos } => {
+                        table.retain(|(row, _)| *row != pos);
+                        table.push((pos, *seq.get_at(pos).unwrap()));
+                    }
+                    Change::Removed { pos } => table.retain(|(row, _)| *row != pos),
+                    Change::Moved { from, to } => table.iter_mut().filter(|(row, _)| *row == from).for_each(|row| row.0 = to),
+                }
+            }
+            table.sort();
+        }
+
+        let elements: Vec<usize> = table.into_iter().map(|(_, element)| element).collect();
+        assert_eq!(elements, seq.values().copied().collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_change_positions() {
+        let pos = Pos::new(1, 1);
+        let to = Pos::new(2, 1);
+
+        assert_eq!(Change::Inserted { pos }.positions().collect::<Vec<_>>(), vec![&pos]);
+        assert_eq!(Change::Updated { pos }.positions().collect::<Vec<_>>(), vec![&pos]);
+        assert_eq!(Change::Removed { pos }.positions().collect::<Vec<_>>(), vec![&pos]);
+        assert_eq!(
+            Change::Moved { from: pos, to }.positions().collect::<Vec<_>>(),
+            vec![&pos, &to]
+        );
+    }
+
+    #[test]
+    fn test_trait_impl_default() {
+        let seq1: Sequence<String> = Sequence::default();
+        let seq2: Sequence<String> = Sequence::new();
+
+        assert_eq!(seq1, seq2);
+    }
+
+    #[test]
+    fn test_trait_impl_index() {
+        let mut seq = setup_seq_abc();
+        seq.remove(0);
+
+        assert_eq!(seq[0], "B".to_string());
+        assert_eq!(seq[1], "C".to_string());
+    }
+
+    #[test]
+    #[should_panic(expected = "index out of bounds: the len is 2 but the index is 2")]
+    fn test_trait_impl_index_out_of_bounds() {
+        let mut seq = setup_seq_abc();
+        seq.remove(0);
+
+        let _ = &seq[2];
+    }
+
+    #[test]
+    fn test_pos_from() {
+        let seq = setup_seq_abc();
+
+        assert_eq!(seq.position_from(0).map(<(u64, u64)>::from), Some((1, 1)));
+    }
+
+    #[test]
+    fn test_trait_impl_index_mut() {
+        let mut seq = setup_seq_abc();
+        seq[0] = "H".to_string();
+        seq[2].push('!');
+
+        assert_eq!(seq[0], "H".to_string());
+        assert_eq!(seq.last(), Some(&"C!".to_string()));
+    }
+
+    #[test]
+    fn test_trait_impl_clone() {
+        let seq = setup_seq_abc();
+        let seq2 = seq.clone();
+
+        assert_eq!(seq.len(), seq2.len());
+        assert_eq!(seq, seq2);
+    }
+
+    #[test]
+    fn test_trait_impl_clone_incl_element_none() {
+        let mut seq = setup_seq_abc();
+        seq.remove(1);
+        let seq2 = seq.clone();
+
+        assert_eq!(seq.len(), seq2.len());
+        assert_eq!(seq, seq2);
+    }
+
+    #[test]
+    fn test_trait_impl_into_iterator() {
+        let mut seq = setup_seq_abc();
+        seq.remove(1);
+
+        for node in seq {
+            assert_eq!(node.element.is_some(), true);
+        }
+    }
+
+    #[cfg(feature = "serde-derive")]
+    #[test]
+    fn test_serde_round_trip() {
+        let mut seq = setup_seq_abc();
+        seq.remove(1);
+
+        let json = serde_json::to_string(&seq).unwrap();
+        let loaded: Sequence<String> = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(loaded, seq);
+    }
+
+    #[cfg(feature = "serde-derive")]
+    #[test]
+    fn test_deserialize_rejects_unsorted_nodes() {
+        let json = r#"{"nodes":[
+            {"position":{"num":2,"denom":1},"element":"B"},
+            {"position":{"num":1,"denom":1},"element":"A"}
+        ],"len":2}"#;
+        assert_eq!(serde_json::from_str::<Sequence<String>>(json).is_err(), true);
+
+        let json = r#"{"nodes":[
+            {"position":{"num":1,"denom":1},"element":"A"},
+            {"position":{"num":2,"denom":2},"element":"B"}
+        ],"len":2}"#;
+        assert_eq!(serde_json::from_str::<Sequence<String>>(json).is_err(), true);
+    }
+
+    #[cfg(feature = "serde-derive")]
+    #[test]
+    fn test_deserialize_rejects_wrong_len() {
+        let json = r#"{"nodes":[
+            {"position":{"num":1,"denom":1},"element":"A"},
+            {"position":{"num":2,"denom":1},"element":null}
+        ],"len":2}"#;
+
+        assert_eq!(serde_json::from_str::<Sequence<String>>(json).is_err(), true);
+    }
+
+    #[cfg(feature = "serde-derive")]
+    #[test]
+    fn test_deserialize_assigns_ids_if_missing() {
+        let json = r#"{"nodes":[
+            {"position":{"num":1,"denom":1},"element":"A"},
+            {"position":{"num":2,"denom":1},"element":null},
+            {"position":{"num":3,"denom":1},"element":"C"}
+        ],"len":2}"#;
+        let mut seq: Sequence<String> = serde_json::from_str(json).unwrap();
+
+        assert_eq!(seq.id_of(Pos::new(1, 1)), Some(NodeId::from(0)));
+        assert_eq!(seq.id_of(Pos::new(3, 1)), Some(NodeId::from(2)));
+        seq.push("D".to_string());
+        assert_eq!(seq.id_of(Pos::new(4, 1)), Some(NodeId::from(3)));
+        assert_eq!(seq.get_by_id(NodeId::from(0)), Some(&"A".to_string()));
+    }
+
+    #[cfg(feature = "serde-derive")]
+    #[test]
+    fn test_deserialize_rejects_shared_ids() {
+        let json = r#"{"nodes":[
+            {"id":0,"position":{"num":1,"denom":1},"element":"A"},
+            {"id":0,"position":{"num":2,"denom":1},"element":"B"}
+        ],"len":2,"next_id":2}"#;
+        assert_eq!(serde_json::from_str::<Sequence<String>>(json).is_err(), true);
+
+        let json = r#"{"nodes":[
+            {"id":0,"position":{"num":1,"denom":1},"element":"A"},
+            {"id":2,"position":{"num":2,"denom":1},"element":"B"}
+        ],"len":2,"next_id":2}"#;
+        assert_eq!(serde_json::from_str::<Sequence<String>>(json).is_err(), true);
+
+        // An element moved away leaves its id with the empty node at its old position.
+        let mut seq = setup_seq_abc();
+        seq.move_to(0, 3).unwrap();
+        let json = serde_json::to_string(&seq).unwrap();
+        assert_eq!(serde_json::from_str::<Sequence<String>>(&json).unwrap(), seq);
     }
 }
@@ -0,0 +1,462 @@
+#[cfg(test)]
+#[allow(clippy::bool_assert_comparison)]
+mod tests {
+    use crate::btree_sequence::BTreeSequence;
+    use crate::sequence::{Node, Pos, PositionStrategy, Sequence};
+    use crate::tree_sequence::TreeSequence;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    // Tests the semantics all sequence types share, i.e. the positions and ids they hand out, their empty nodes and
+    // their journal, once for each type.
+    macro_rules! sequence_tests {
+        ($module:ident, $sequence:ident) => {
+            mod $module {
+                use crate::sequence::{Change, NodeId, Pos, PositionStrategy};
+                use crate::$sequence;
+
+                fn setup_seq_abc() -> $sequence<String> {
+                    let mut seq: $sequence<String> = $sequence::new();
+
+                    seq.push("A".to_string());
+                    seq.push("B".to_string());
+                    seq.push("C".to_string());
+
+                    seq
+                }
+
+                fn elements(seq: &$sequence<String>) -> Vec<&str> {
+                    seq.iter().map(|node| node.element_as_ref().unwrap().as_str()).collect()
+                }
+
+                #[test]
+                fn test_new() {
+                    let seq: $sequence<String> = $sequence::new();
+
+                    assert_eq!(seq.is_empty(), true);
+                    assert_eq!(seq.len(), 0);
+                    assert_eq!(seq.first(), None);
+                    assert_eq!(seq.last(), None);
+                    assert_eq!(seq.tombstones(), 0);
+                    assert_eq!(seq.is_journal_enabled(), false);
+                    assert_eq!(seq.strategy(), PositionStrategy::Mediant);
+                    assert_eq!(seq, $sequence::default());
+                }
+
+                #[test]
+                fn test_with_strategy() {
+                    let mut seq: $sequence<String> = $sequence::with_strategy(PositionStrategy::SternBrocot);
+                    assert_eq!(seq.strategy(), PositionStrategy::SternBrocot);
+
+                    seq.set_strategy(PositionStrategy::Mediant);
+                    assert_eq!(seq.strategy(), PositionStrategy::Mediant);
+                }
+
+                #[test]
+                fn test_push() {
+                    let seq = setup_seq_abc();
+
+                    assert_eq!(seq.len(), 3);
+                    assert_eq!(seq.first(), Some(&"A".to_string()));
+                    assert_eq!(seq.last(), Some(&"C".to_string()));
+                    assert_eq!(seq.position_from(2), Some(Pos::new(3, 1)));
+                    assert_eq!(seq.id_of(Pos::new(3, 1)), Some(NodeId::from(2)));
+                }
+
+                #[test]
+                fn test_get() {
+                    let mut seq = setup_seq_abc();
+
+                    assert_eq!(seq.get(1), Some(&"B".to_string()));
+                    assert_eq!(seq.get(3), None);
+                    assert_eq!(seq.node(1).map(|node| node.id()), Some(NodeId::from(1)));
+
+                    seq.get_mut(1).unwrap().push('B');
+                    assert_eq!(seq.get(1), Some(&"BB".to_string()));
+                }
+
+                #[test]
+                fn test_get_at() {
+                    let mut seq = setup_seq_abc();
+
+                    assert_eq!(seq.get_at(Pos::new(2, 1)), Some(&"B".to_string()));
+                    assert_eq!(seq.get_at(Pos::new(4, 2)), Some(&"B".to_string()));
+                    assert_eq!(seq.get_at(Pos::new(5, 2)), None);
+                    assert_eq!(seq.node_at(Pos::new(2, 1)).map(|node| node.id()), Some(NodeId::from(1)));
+
+                    seq.get_at_mut(Pos::new(2, 1)).unwrap().push('B');
+                    assert_eq!(seq.get_at(Pos::new(2, 1)), Some(&"BB".to_string()));
+                }
+
+                #[test]
+                fn test_index_from_position_from() {
+                    let mut seq = setup_seq_abc();
+                    seq.remove(0);
+
+                    assert_eq!(seq.index_from(Pos::new(1, 1)), None);
+                    assert_eq!(seq.index_from(Pos::new(3, 1)), Some(1));
+                    assert_eq!(seq.index_from(Pos::new(3, 2)), None);
+                    assert_eq!(seq.position_from(0), Some(Pos::new(2, 1)));
+                    assert_eq!(seq.position_from(2), None);
+                }
+
+                #[test]
+                fn test_insert() {
+                    let mut seq = setup_seq_abc();
+
+                    seq.insert(0, "0".to_string());
+                    seq.insert(2, "AB".to_string());
+                    seq.insert(10, "D".to_string());
+
+                    assert_eq!(elements(&seq), vec!["0", "A", "AB", "B", "C", "D"]);
+                    assert_eq!(seq.position_from(0), Some(Pos::new(1, 2)));
+                    assert_eq!(seq.position_from(2), Some(Pos::new(3, 2)));
+                    assert_eq!(seq.position_from(5), Some(Pos::new(4, 1)));
+                }
+
+                #[test]
+                fn test_insert_into_empty() {
+                    let mut seq: $sequence<String> = $sequence::new();
+
+                    assert_eq!(seq.try_insert(0, "A".to_string()), Ok(Pos::new(1, 1)));
+                    assert_eq!(seq.try_insert(0, "0".to_string()), Ok(Pos::new(1, 2)));
+                }
+
+                #[test]
+                fn test_try_insert_exhausted() {
+                    let mut seq: $sequence<String> = $sequence::new();
+                    seq.insert_at(Pos::new(u64::MAX - 1, 1), "A".to_string());
+                    seq.insert_at(Pos::new(u64::MAX, 1), "B".to_string());
+
+                    let err = seq.try_insert(1, "C".to_string()).unwrap_err();
+                    assert_eq!(err.lower(), Some(&Pos::new(u64::MAX - 1, 1)));
+                    assert_eq!(err.upper(), Some(&Pos::new(u64::MAX, 1)));
+                    assert_eq!(seq.len(), 2);
+
+                    let err = seq.try_push("C".to_string()).unwrap_err();
+                    assert_eq!(err.lower(), Some(&Pos::new(u64::MAX, 1)));
+                }
+
+                #[test]
+                #[should_panic(expected = "no position left after")]
+                fn test_push_exhausted_panics() {
+                    let mut seq: $sequence<String> = $sequence::new();
+                    seq.insert_at(Pos::new(u64::MAX, 1), "A".to_string());
+
+                    seq.push("B".to_string());
+                }
+
+                #[test]
+                #[should_panic(expected = "no position left between")]
+                fn test_insert_exhausted_panics() {
+                    let mut seq: $sequence<String> = $sequence::new();
+                    seq.insert_at(Pos::new(u64::MAX - 1, 1), "A".to_string());
+                    seq.insert_at(Pos::new(u64::MAX, 1), "B".to_string());
+
+                    seq.insert(1, "C".to_string());
+                }
+
+                #[test]
+                fn test_insert_at() {
+                    let mut seq = setup_seq_abc();
+
+                    seq.insert_at(Pos::new(5, 2), "BC".to_string());
+                    seq.insert_at(Pos::new(4, 2), "BB".to_string());
+                    assert_eq!(seq.len(), 4);
+                    assert_eq!(elements(&seq), vec!["A", "BB", "BC", "C"]);
+                    assert_eq!(seq.id_of(Pos::new(2, 1)), Some(NodeId::from(1)));
+                    assert_eq!(seq.id_of(Pos::new(5, 2)), Some(NodeId::from(3)));
+                }
+
+                #[test]
+                fn test_insert_at_tombstone() {
+                    let mut seq = setup_seq_abc();
+                    seq.remove(1);
+
+                    seq.insert_at(Pos::new(2, 1), "BB".to_string());
+                    assert_eq!(seq.len(), 3);
+                    assert_eq!(seq.tombstones(), 0);
+                    assert_eq!(seq.get(1), Some(&"BB".to_string()));
+                    assert_eq!(seq.id_of(Pos::new(2, 1)), Some(NodeId::from(3)));
+                }
+
+                #[test]
+                fn test_remove() {
+                    let mut seq = setup_seq_abc();
+
+                    assert_eq!(seq.remove(1), Some("B".to_string()));
+                    assert_eq!(seq.remove(2), None);
+                    assert_eq!(seq.len(), 2);
+                    assert_eq!(seq.tombstones(), 1);
+                    assert_eq!(seq.get(1), Some(&"C".to_string()));
+                    assert_eq!(seq.get_at(Pos::new(2, 1)), None);
+                    assert_eq!(seq.iter_with_tombstones().count(), 3);
+                }
+
+                #[test]
+                fn test_remove_at() {
+                    let mut seq = setup_seq_abc();
+
+                    assert_eq!(seq.remove_at(Pos::new(1, 1)), Some("A".to_string()));
+                    assert_eq!(seq.remove_at(Pos::new(1, 1)), None);
+                    assert_eq!(seq.len(), 2);
+                    assert_eq!(seq.first(), Some(&"B".to_string()));
+                }
+
+                #[test]
+                fn test_insert_after_remove() {
+                    let mut seq = setup_seq_abc();
+                    seq.remove(1);
+
+                    // The mediant of 1/1 and 3/1 would hand out 2/1 of the removed element again.
+                    assert_eq!(seq.try_insert(1, "X".to_string()), Ok(Pos::new(5, 2)));
+                    assert_eq!(seq.index_from(Pos::new(5, 2)), Some(1));
+                }
+
+                #[test]
+                fn test_push_after_remove_last() {
+                    let mut seq = setup_seq_abc();
+                    seq.remove(2);
+                    seq.remove_at(Pos::new(2, 1));
+
+                    assert_eq!(seq.try_push("D".to_string()), Ok(Pos::new(4, 1)));
+                    assert_eq!(seq.position_from(1), Some(Pos::new(4, 1)));
+                }
+
+                #[test]
+                fn test_ids() {
+                    let mut seq = setup_seq_abc();
+
+                    assert_eq!(seq.id_of(Pos::new(2, 1)), Some(NodeId::from(1)));
+                    assert_eq!(seq.get_by_id(NodeId::from(1)), Some(&"B".to_string()));
+                    assert_eq!(seq.position_of(NodeId::from(2)), Some(Pos::new(3, 1)));
+
+                    assert_eq!(seq.remove_by_id(NodeId::from(1)), Some("B".to_string()));
+                    assert_eq!(seq.remove_by_id(NodeId::from(1)), None);
+                    assert_eq!(seq.id_of(Pos::new(2, 1)), None);
+                    assert_eq!(seq.get_by_id(NodeId::from(1)), None);
+                    assert_eq!(seq.position_of(NodeId::from(1)), None);
+
+                    let position = seq.try_insert(1, "X".to_string()).unwrap();
+                    assert_eq!(seq.id_of(position), Some(NodeId::from(3)));
+                }
+
+                #[test]
+                fn test_journal() {
+                    let mut seq = setup_seq_abc();
+                    seq.enable_journal();
+                    assert_eq!(seq.is_journal_enabled(), true);
+
+                    seq.insert(0, "0".to_string());
+                    seq.insert_at(Pos::new(4, 2), "BB".to_string());
+                    seq.remove_at(Pos::new(3, 1));
+                    seq.remove_at(Pos::new(3, 1));
+                    seq.rebalance();
+
+                    assert_eq!(
+                        seq.take_changes(),
+                        vec![
+                            Change::Inserted { pos: Pos::new(1, 2) },
+                            Change::Updated { pos: Pos::new(2, 1) },
+                            Change::Removed { pos: Pos::new(3, 1) },
+                            Change::Moved {
+                                from: Pos::new(1, 2),
+                                to: Pos::new(1, 1)
+                            },
+                            Change::Moved {
+                                from: Pos::new(1, 1),
+                                to: Pos::new(2, 1)
+                            },
+                            Change::Moved {
+                                from: Pos::new(2, 1),
+                                to: Pos::new(3, 1)
+                            },
+                        ]
+                    );
+                    assert_eq!(seq.take_changes(), vec![]);
+
+                    seq.disable_journal();
+                    seq.push("D".to_string());
+                    assert_eq!(seq.is_journal_enabled(), false);
+                    assert_eq!(seq.take_changes(), vec![]);
+                }
+
+                #[test]
+                fn test_eq_ignores_journal() {
+                    let seq = setup_seq_abc();
+                    let mut other = setup_seq_abc();
+                    other.enable_journal();
+
+                    assert_eq!(other, seq);
+                    assert_eq!(other.clone(), seq);
+
+                    other.remove(0);
+                    assert_eq!(other == seq, false);
+                }
+
+                #[test]
+                fn test_compact() {
+                    let mut seq = setup_seq_abc();
+                    seq.remove(0);
+                    let watermark = seq.watermark();
+                    seq.remove_at(Pos::new(3, 1));
+
+                    assert_eq!(seq.tombstones(), 2);
+                    assert_eq!(seq.compact_before(watermark), 1);
+                    assert_eq!(seq.tombstones(), 1);
+                    assert_eq!(seq.try_insert(0, "A".to_string()), Ok(Pos::new(1, 1)));
+                    assert_eq!(seq.try_push("D".to_string()), Ok(Pos::new(4, 1)));
+
+                    assert_eq!(seq.compact(), 1);
+                    assert_eq!(seq.tombstones(), 0);
+                    assert_eq!(elements(&seq), vec!["A", "B", "D"]);
+                }
+
+                #[test]
+                fn test_rebalance() {
+                    let mut seq = setup_seq_abc();
+                    seq.insert(0, "0".to_string());
+                    seq.remove(2);
+
+                    let changes = seq.rebalance();
+
+                    assert_eq!(
+                        changes,
+                        vec![(Pos::new(1, 2), Pos::new(1, 1)), (Pos::new(1, 1), Pos::new(2, 1))]
+                    );
+                    assert_eq!(seq.tombstones(), 0);
+                    assert_eq!(seq.position_from(2), Some(Pos::new(3, 1)));
+                    assert_eq!(seq.get(2), Some(&"C".to_string()));
+                    assert_eq!(seq.id_of(Pos::new(2, 1)), Some(NodeId::from(0)));
+                    assert_eq!(seq.try_push("D".to_string()), Ok(Pos::new(4, 1)));
+                }
+
+                #[test]
+                fn test_iter() {
+                    let mut seq = setup_seq_abc();
+                    seq.remove(1);
+
+                    let positions: Vec<&Pos> = seq.iter().map(|node| node.position()).collect();
+                    assert_eq!(positions, vec![&Pos::new(1, 1), &Pos::new(3, 1)]);
+                    assert_eq!((&seq).into_iter().count(), 2);
+
+                    let nodes: Vec<(NodeId, Option<String>)> = seq.into_iter().map(|node| (node.id(), node.element())).collect();
+                    assert_eq!(
+                        nodes,
+                        vec![
+                            (NodeId::from(0), Some("A".to_string())),
+                            (NodeId::from(2), Some("C".to_string()))
+                        ]
+                    );
+                }
+            }
+        };
+    }
+
+    sequence_tests!(sequence, Sequence);
+    sequence_tests!(btree_sequence, BTreeSequence);
+    sequence_tests!(tree_sequence, TreeSequence);
+
+    // Nodes as (id, position, element) including empty nodes, to compare sequences of different types.
+    fn nodes<'a>(nodes: impl Iterator<Item = &'a Node<usize, Pos>>) -> Vec<(u64, Pos, Option<usize>)> {
+        nodes
+            .map(|node| (node.id().into(), *node.position(), node.element_as_ref().copied()))
+            .collect()
+    }
+
+    #[test]
+    fn test_random_operations_agree() {
+        let mut rng = StdRng::seed_from_u64(11);
+        let mut seq: Sequence<usize> = Sequence::with_strategy(PositionStrategy::SternBrocot);
+        let mut btree_seq: BTreeSequence<usize> = BTreeSequence::with_strategy(PositionStrategy::SternBrocot);
+        let mut tree_seq: TreeSequence<usize> = TreeSequence::with_strategy(PositionStrategy::SternBrocot);
+        seq.enable_journal();
+        btree_seq.enable_journal();
+        tree_seq.enable_journal();
+        let mut watermark = 0;
+
+        for i in 0..2_000 {
+            let index = rng.gen_range(0..=seq.len());
+            match rng.gen_range(0..20) {
+                0..=4 => {
+                    let element = seq.remove(index);
+                    assert_eq!(btree_seq.remove(index), element);
+                    assert_eq!(tree_seq.remove(index), element);
+                }
+                5 => {
+                    let position = seq.position_from(index).unwrap_or(Pos::new(i as u64, 1));
+                    let element = seq.remove_at(position);
+                    assert_eq!(btree_seq.remove_at(position), element);
+                    assert_eq!(tree_seq.remove_at(position), element);
+                }
+                6 => {
+                    let id = (rng.gen_range(0..=i) as u64).into();
+                    let element = seq.remove_by_id(id);
+                    assert_eq!(btree_seq.remove_by_id(id), element);
+                    assert_eq!(tree_seq.remove_by_id(id), element);
+                }
+                7..=8 => {
+                    let position = Pos::new(rng.gen_range(1..100), rng.gen_range(1..100));
+                    seq.insert_at(position, i);
+                    btree_seq.insert_at(position, i);
+                    tree_seq.insert_at(position, i);
+                }
+                9..=10 => {
+                    let position = seq.try_push(i);
+                    assert_eq!(btree_seq.try_push(i), position);
+                    assert_eq!(tree_seq.try_push(i), position);
+                }
+                11 => {
+                    let changes = seq.rebalance();
+                    assert_eq!(btree_seq.rebalance(), changes);
+                    assert_eq!(tree_seq.rebalance(), changes);
+                }
+                12 => {
+                    let dropped = seq.compact_before(watermark);
+                    assert_eq!(btree_seq.compact_before(watermark), dropped);
+                    assert_eq!(tree_seq.compact_before(watermark), dropped);
+                    watermark = seq.watermark();
+                }
+                _ => {
+                    let position = seq.try_insert(index, i);
+                    assert_eq!(btree_seq.try_insert(index, i), position);
+                    assert_eq!(tree_seq.try_insert(index, i), position);
+                }
+            }
+
+            let index = rng.gen_range(0..=seq.len());
+            for (len, tombstones, watermark) in [
+                (btree_seq.len(), btree_seq.tombstones(), btree_seq.watermark()),
+                (tree_seq.len(), tree_seq.tombstones(), tree_seq.watermark()),
+            ] {
+                assert_eq!((len, tombstones, watermark), (seq.len(), seq.tombstones(), seq.watermark()));
+            }
+            assert_eq!(btree_seq.get(index), seq.get(index));
+            assert_eq!(tree_seq.get(index), seq.get(index));
+            assert_eq!(btree_seq.position_from(index), seq.position_from(index));
+            assert_eq!(tree_seq.position_from(index), seq.position_from(index));
+            if let Some(position) = seq.position_from(index) {
+                assert_eq!(btree_seq.index_from(position), Some(index));
+                assert_eq!(tree_seq.index_from(position), Some(index));
+                assert_eq!(btree_seq.id_of(position), seq.id_of(position));
+                assert_eq!(tree_seq.id_of(position), seq.id_of(position));
+            }
+        }
+
+        assert_eq!(btree_seq.first(), seq.first());
+        assert_eq!(tree_seq.first(), seq.first());
+        assert_eq!(btree_seq.last(), seq.last());
+        assert_eq!(tree_seq.last(), seq.last());
+        assert_eq!(nodes(btree_seq.iter_with_tombstones()), nodes(seq.iter_with_tombstones()));
+        assert_eq!(nodes(tree_seq.iter_with_tombstones()), nodes(seq.iter_with_tombstones()));
+        let changes = seq.take_changes();
+        assert_eq!(btree_seq.take_changes(), changes);
+        assert_eq!(tree_seq.take_changes(), changes);
+
+        let values: Vec<Option<usize>> = seq.into_iter().map(|node| node.element()).collect();
+        let btree_values: Vec<Option<usize>> = btree_seq.into_iter().map(|node| node.element()).collect();
+        let tree_values: Vec<Option<usize>> = tree_seq.into_iter().map(|node| node.element()).collect();
+        assert_eq!(btree_values, values);
+        assert_eq!(tree_values, values);
+    }
+}
@@ -0,0 +1,365 @@
+#[cfg(test)]
+#[allow(clippy::bool_assert_comparison)]
+mod tests {
+    use rusqlite::Connection;
+
+    use crate::error::{PosError, SqliteError};
+    use crate::sequence::{Pos, Sequence};
+    use crate::sqlite::SqliteStore;
+    use crate::store::SequenceStore;
+    use crate::test_util::{elements, setup_seq_abc, Point};
+
+    fn setup_store() -> SqliteStore {
+        let store = SqliteStore::new(Connection::open_in_memory().unwrap(), "sequence");
+        store.create_table().unwrap();
+
+        store
+    }
+
+    fn rows(store: &SqliteStore) -> Vec<(i64, i64, String)> {
+        let mut stmt = store
+            .connection()
+            .prepare("SELECT numerator, denominator, payload FROM sequence ORDER BY numerator, denominator")
+            .unwrap();
+        let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?))).unwrap();
+
+        rows.map(Result::unwrap).collect()
+    }
+
+    fn execute(store: &SqliteStore, sql: &str) {
+        store.connection().execute(sql, []).unwrap();
+    }
+
+    #[test]
+    fn test_new() {
+        let store = SqliteStore::new(Connection::open_in_memory().unwrap(), "items");
+
+        assert_eq!(store.table(), "items");
+        assert_eq!(store.into_connection().is_autocommit(), true);
+    }
+
+    #[test]
+    fn test_create_table_twice() {
+        let store = setup_store();
+
+        assert_eq!(store.create_table().is_ok(), true);
+    }
+
+    #[test]
+    fn test_create_table_quoted_name() {
+        let mut store = SqliteStore::new(Connection::open_in_memory().unwrap(), "my \"seq\"; DROP TABLE x");
+
+        store.create_table().unwrap();
+        let mut seq = setup_seq_abc();
+        seq.save_to(&mut store).unwrap();
+
+        assert_eq!(elements(&Sequence::<String>::load_from(&mut store).unwrap()), elements(&seq));
+        seq.push("D".to_string());
+        assert_eq!(seq.save_to(&mut store).unwrap(), 4);
+    }
+
+    #[test]
+    fn test_load_empty() {
+        let mut store = setup_store();
+        let seq: Sequence<String> = Sequence::load_from(&mut store).unwrap();
+
+        assert_eq!(seq.is_empty(), true);
+        assert_eq!(seq.is_journal_enabled(), true);
+    }
+
+    #[test]
+    fn test_load_missing_table() {
+        let mut store = SqliteStore::new(Connection::open_in_memory().unwrap(), "sequence");
+        let result: Result<Sequence<String>, SqliteError> = Sequence::load_from(&mut store);
+
+        assert!(matches!(result, Err(SqliteError::Sqlite(_))));
+    }
+
+    #[test]
+    fn test_save_and_load() {
+        let mut store = setup_store();
+        let mut seq = setup_seq_abc();
+        seq.remove(1);
+
+        assert_eq!(seq.save_to(&mut store).unwrap(), 2);
+        assert_eq!(rows(&store), vec![(1, 1, "A".to_string()), (3, 1, "C".to_string())]);
+
+        let loaded: Sequence<String> = Sequence::load_from(&mut store).unwrap();
+        assert_eq!(elements(&loaded), vec!["A", "C"]);
+        assert_eq!(
+            loaded.positions().cloned().collect::<Vec<_>>(),
+            seq.positions().cloned().collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_save_replaces_rows() {
+        let mut store = setup_store();
+
+        setup_seq_abc().save_to(&mut store).unwrap();
+        let mut seq: Sequence<String> = Sequence::new();
+        seq.push("X".to_string());
+        seq.save_to(&mut store).unwrap();
+
+        assert_eq!(rows(&store), vec![(1, 1, "X".to_string())]);
+    }
+
+    #[test]
+    fn test_load_orders_by_exact_fraction() {
+        let mut store = setup_store();
+        let big = i64::MAX - 1;
+
+        // Both fractions are equal as f64, so ordering by a REAL column could not tell them apart.
+        store
+            .connection()
+            .execute("INSERT INTO sequence VALUES (?1, ?2, 'second')", [big - 1, big - 2])
+            .unwrap();
+        store
+            .connection()
+            .execute("INSERT INTO sequence VALUES (?1, ?2, 'first')", [big, big - 1])
+            .unwrap();
+        execute(&store, "INSERT INTO sequence VALUES (1, 2, 'zero')");
+
+        let seq: Sequence<String> = Sequence::load_from(&mut store).unwrap();
+
+        assert_eq!(elements(&seq), vec!["zero", "first", "second"]);
+    }
+
+    #[test]
+    fn test_load_range() {
+        let mut store = setup_store();
+        let big = i64::MAX - 1;
+        store
+            .connection()
+            .execute("INSERT INTO sequence VALUES (?1, ?2, 'second')", [big - 1, big - 2])
+            .unwrap();
+        store
+            .connection()
+            .execute("INSERT INTO sequence VALUES (?1, ?2, 'first')", [big, big - 1])
+            .unwrap();
+        execute(&store, "INSERT INTO sequence VALUES (2, 1, 'two')");
+
+        let first = Pos::new(big as u64, big as u64 - 1);
+        let seq: Sequence<String> = Sequence::load_range_from(&mut store, first..Pos::new(2, 1)).unwrap();
+        assert_eq!(elements(&seq), vec!["first", "second"]);
+
+        let seq: Sequence<String> = Sequence::load_range_from(&mut store, ..first).unwrap();
+        assert_eq!(seq.is_empty(), true);
+
+        let seq: Sequence<String> = Sequence::load_range_from(&mut store, Pos::new(2, 1)..first).unwrap();
+        assert_eq!(seq.is_empty(), true);
+    }
+
+    #[test]
+    fn test_load_large_positions() {
+        let mut store = setup_store();
+        let mut seq: Sequence<String> = Sequence::new();
+        seq.insert_at(Pos::try_new(u64::MAX, 1).unwrap(), "max".to_string());
+        seq.insert_at(Pos::try_new(1, u64::MAX).unwrap(), "min".to_string());
+
+        seq.save_to(&mut store).unwrap();
+        let loaded: Sequence<String> = Sequence::load_from(&mut store).unwrap();
+
+        assert_eq!(elements(&loaded), vec!["min", "max"]);
+        assert_eq!(
+            loaded.positions().cloned().collect::<Vec<_>>(),
+            seq.positions().cloned().collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_load_zero_denominator() {
+        let mut store = setup_store();
+        execute(&store, "INSERT INTO sequence VALUES (1, 0, 'A')");
+
+        let result: Result<Sequence<String>, SqliteError> = Sequence::load_from(&mut store);
+
+        assert!(matches!(result, Err(SqliteError::InvalidPos(PosError::ZeroDenominator))));
+    }
+
+    #[test]
+    fn test_load_decode_error() {
+        let mut store = setup_store();
+        execute(&store, "INSERT INTO sequence VALUES (1, 1, 'A')");
+
+        let result: Result<Sequence<i64>, SqliteError> = Sequence::load_from(&mut store);
+
+        assert!(matches!(
+            result,
+            Err(SqliteError::Sqlite(rusqlite::Error::FromSqlConversionFailure(2, _, _)))
+        ));
+    }
+
+    #[test]
+    fn test_codecs() {
+        fn roundtrip<T: crate::sqlite::SqliteCodec + Clone + PartialEq + std::fmt::Debug>(table: &str, values: Vec<T>) {
+            let mut store = SqliteStore::new(Connection::open_in_memory().unwrap(), table);
+            store.create_table().unwrap();
+            let seq: Sequence<T> = values.into_iter().collect();
+
+            seq.save_to(&mut store).unwrap();
+
+            assert_eq!(elements(&Sequence::<T>::load_from(&mut store).unwrap()), elements(&seq));
+        }
+
+        roundtrip("blobs", vec![vec![0u8, 1], vec![], vec![255]]);
+        roundtrip("integers", vec![i64::MIN, 0, i64::MAX]);
+        roundtrip("reals", vec![-1.5f64, 0.0, 2.25]);
+        roundtrip("bools", vec![true, false]);
+        roundtrip("points", vec![Point { x: 1, y: -2 }, Point { x: 3, y: 4 }]);
+    }
+
+    #[test]
+    fn test_sync() {
+        let mut store = setup_store();
+        setup_seq_abc().save_to(&mut store).unwrap();
+        let mut seq: Sequence<String> = Sequence::load_from(&mut store).unwrap();
+
+        seq.insert(1, "X".to_string());
+        seq.remove(3);
+        seq.insert_at(Pos::try_new(1, 1).unwrap(), "Y".to_string());
+
+        // X inserted at 3/2, C removed from 3/1, A updated at 1/1
+        assert_eq!(seq.sync_to(&mut store).unwrap(), 3);
+        assert_eq!(
+            rows(&store),
+            vec![(1, 1, "Y".to_string()), (2, 1, "B".to_string()), (3, 2, "X".to_string())]
+        );
+        assert_eq!(seq.take_changes().is_empty(), true);
+        assert_eq!(elements(&Sequence::<String>::load_from(&mut store).unwrap()), elements(&seq));
+    }
+
+    #[test]
+    fn test_sync_nothing_changed() {
+        let mut store = setup_store();
+        let mut seq = setup_seq_abc();
+
+        // The journal is disabled.
+        assert_eq!(seq.sync_to(&mut store).unwrap(), 0);
+        assert_eq!(rows(&store).is_empty(), true);
+
+        seq.enable_journal();
+        assert_eq!(seq.sync_to(&mut store).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_sync_moves() {
+        let mut store = setup_store();
+        let seq: Sequence<String> = vec!["D", "B", "A", "C"].into_iter().map(String::from).collect();
+        seq.save_to(&mut store).unwrap();
+        let mut seq: Sequence<String> = Sequence::load_from(&mut store).unwrap();
+
+        seq.move_to(0, 3).unwrap();
+        seq.sort_by(|a, b| a.cmp(b)).unwrap();
+        seq.reverse().unwrap();
+        seq.rebalance();
+        seq.sync_to(&mut store).unwrap();
+
+        let loaded: Sequence<String> = Sequence::load_from(&mut store).unwrap();
+        assert_eq!(elements(&loaded), vec!["D", "C", "B", "A"]);
+        assert_eq!(
+            loaded.positions().cloned().collect::<Vec<_>>(),
+            seq.positions().cloned().collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_sync_removed_and_reinserted() {
+        let mut store = setup_store();
+        setup_seq_abc().save_to(&mut store).unwrap();
+        let mut seq: Sequence<String> = Sequence::load_from(&mut store).unwrap();
+
+        seq.remove(1);
+        seq.insert_at(Pos::try_new(2, 1).unwrap(), "Z".to_string());
+        seq.remove(0);
+        seq.sync_to(&mut store).unwrap();
+
+        assert_eq!(rows(&store), vec![(2, 1, "Z".to_string()), (3, 1, "C".to_string())]);
+    }
+
+    #[test]
+    fn test_sync_not_reduced_position() {
+        let mut store = setup_store();
+        let mut seq: Sequence<String> = Sequence::new();
+        seq.insert_at(Pos::try_new(2, 4).unwrap(), "A".to_string());
+        seq.save_to(&mut store).unwrap();
+        assert_eq!(rows(&store), vec![(1, 2, "A".to_string())]);
+
+        let mut seq: Sequence<String> = Sequence::load_from(&mut store).unwrap();
+        seq.insert_at(Pos::try_new(1, 2).unwrap(), "B".to_string());
+        seq.insert_at(Pos::try_new(3, 6).unwrap(), "C".to_string());
+        seq.insert_at(Pos::try_new(4, 4).unwrap(), "D".to_string());
+
+        assert_eq!(seq.sync_to(&mut store).unwrap(), 2);
+        assert_eq!(rows(&store), vec![(1, 1, "D".to_string()), (1, 2, "C".to_string())]);
+        assert_eq!(seq.sync_to(&mut store).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_sync_not_reduced_row() {
+        let mut store = setup_store();
+        execute(&store, "INSERT INTO sequence VALUES (2, 4, 'A')");
+
+        let mut seq: Sequence<String> = Sequence::load_from(&mut store).unwrap();
+        seq.insert_at(Pos::try_new(1, 2).unwrap(), "B".to_string());
+        seq.sync_to(&mut store).unwrap();
+
+        assert_eq!(rows(&store), vec![(1, 2, "B".to_string())]);
+    }
+
+    #[test]
+    fn test_sync_failure_keeps_changes() {
+        let mut store = setup_store();
+        let mut seq: Sequence<String> = Sequence::load_from(&mut store).unwrap();
+        seq.push("A".to_string());
+        seq.push("B".to_string());
+        execute(&store, "DROP TABLE sequence");
+
+        assert!(matches!(seq.sync_to(&mut store), Err(SqliteError::Sqlite(_))));
+        assert_eq!(store.connection().is_autocommit(), true);
+
+        seq.push("C".to_string());
+        store.create_table().unwrap();
+        assert_eq!(seq.sync_to(&mut store).unwrap(), 3);
+        assert_eq!(
+            rows(&store),
+            vec![(1, 1, "A".to_string()), (2, 1, "B".to_string()), (3, 1, "C".to_string())]
+        );
+    }
+
+    #[test]
+    fn test_batch() {
+        let mut store = setup_store();
+        SequenceStore::<String>::upsert(&mut store, &Pos::new(1, 1), &"A".to_string()).unwrap();
+
+        SequenceStore::<String>::begin(&mut store).unwrap();
+        SequenceStore::<String>::delete(&mut store, &Pos::new(1, 1)).unwrap();
+
+        // Beginning again drops the uncommitted batch.
+        SequenceStore::<String>::begin(&mut store).unwrap();
+        SequenceStore::<String>::upsert(&mut store, &Pos::new(2, 1), &"B".to_string()).unwrap();
+        SequenceStore::<String>::commit(&mut store).unwrap();
+
+        assert_eq!(rows(&store), vec![(1, 1, "A".to_string()), (2, 1, "B".to_string())]);
+    }
+
+    #[test]
+    fn test_sync_on_disk() {
+        let path = std::env::temp_dir().join(format!("kodiak-sets-sqlite-{}.db", std::process::id()));
+        {
+            let mut store = SqliteStore::new(Connection::open(&path).unwrap(), "sequence");
+            store.create_table().unwrap();
+            let mut seq: Sequence<String> = Sequence::load_from(&mut store).unwrap();
+            seq.push("A".to_string());
+            seq.push("B".to_string());
+            seq.sync_to(&mut store).unwrap();
+        }
+
+        let mut store = SqliteStore::new(Connection::open(&path).unwrap(), "sequence");
+        let seq: Sequence<String> = Sequence::load_from(&mut store).unwrap();
+        drop(store);
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(elements(&seq), vec!["A", "B"]);
+    }
+}
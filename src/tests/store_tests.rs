@@ -0,0 +1,424 @@
+#[cfg(test)]
+#[allow(clippy::bool_assert_comparison)]
+mod tests {
+    use std::io;
+    use std::ops::RangeBounds;
+    use std::path::PathBuf;
+
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    use crate::sequence::{Pos, Sequence};
+    use crate::store::{FileCodec, FileStore, MemoryStore, SequenceStore};
+    use crate::test_util::{elements, setup_seq_abc};
+
+    fn stored<T: Clone, S: SequenceStore<T, Error = E>, E: std::fmt::Debug>(store: &mut S) -> Vec<(Pos, T)> {
+        let mut elements = store.load_all().unwrap();
+        elements.sort_by_key(|(position, _)| *position);
+
+        elements
+    }
+
+    fn nodes<T: Clone>(seq: &Sequence<T>) -> Vec<(Pos, T)> {
+        seq.iter()
+            .map(|node| (*node.position(), node.element_as_ref().unwrap().clone()))
+            .collect()
+    }
+
+    fn temp_path(name: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!("kodiak-sets-{}-{}.seq", name, std::process::id()));
+        let _ = std::fs::remove_file(&path);
+
+        path
+    }
+
+    // Fails on commit while fail is set, e.g. like a database losing its connection.
+    #[derive(Default)]
+    struct FailingStore {
+        memory: MemoryStore<String>,
+        fail: bool,
+    }
+
+    impl SequenceStore<String> for FailingStore {
+        type Error = &'static str;
+
+        fn load_all(&mut self) -> Result<Vec<(Pos, String)>, Self::Error> {
+            let Ok(elements) = self.memory.load_all();
+            Ok(elements)
+        }
+
+        fn load_range<R: RangeBounds<Pos>>(&mut self, range: R) -> Result<Vec<(Pos, String)>, Self::Error> {
+            let Ok(elements) = self.memory.load_range(range);
+            Ok(elements)
+        }
+
+        fn upsert(&mut self, position: &Pos, element: &String) -> Result<(), Self::Error> {
+            let Ok(()) = self.memory.upsert(position, element);
+            Ok(())
+        }
+
+        fn delete(&mut self, position: &Pos) -> Result<(), Self::Error> {
+            let Ok(()) = self.memory.delete(position);
+            Ok(())
+        }
+
+        fn begin(&mut self) -> Result<(), Self::Error> {
+            let Ok(()) = self.memory.begin();
+            Ok(())
+        }
+
+        fn commit(&mut self) -> Result<(), Self::Error> {
+            if self.fail {
+                return Err("connection lost");
+            }
+            let Ok(()) = self.memory.commit();
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_memory_store_new() {
+        let store: MemoryStore<String> = MemoryStore::new();
+
+        assert_eq!(store.is_empty(), true);
+        assert_eq!(store.len(), 0);
+        assert_eq!(store, MemoryStore::default());
+    }
+
+    #[test]
+    fn test_memory_store_upsert_and_delete() {
+        let mut store: MemoryStore<String> = MemoryStore::new();
+
+        store.upsert(&Pos::new(1, 1), &"A".to_string()).unwrap();
+        store.upsert(&Pos::new(2, 1), &"B".to_string()).unwrap();
+        store.upsert(&Pos::new(2, 2), &"C".to_string()).unwrap();
+        assert_eq!(store.len(), 2);
+        assert_eq!(store.get(&Pos::new(1, 1)), Some(&"C".to_string()));
+
+        store.delete(&Pos::new(2, 1)).unwrap();
+        store.delete(&Pos::new(3, 1)).unwrap();
+        assert_eq!(store.iter().collect::<Vec<_>>(), vec![(&Pos::new(1, 1), &"C".to_string())]);
+    }
+
+    #[test]
+    fn test_memory_store_batch() {
+        let mut store: MemoryStore<String> = MemoryStore::new();
+        store.upsert(&Pos::new(1, 1), &"A".to_string()).unwrap();
+
+        store.begin().unwrap();
+        store.upsert(&Pos::new(2, 1), &"B".to_string()).unwrap();
+        store.delete(&Pos::new(1, 1)).unwrap();
+        assert_eq!(store.get(&Pos::new(1, 1)), Some(&"A".to_string()));
+        assert_eq!(store.get(&Pos::new(2, 1)), None);
+
+        store.commit().unwrap();
+        assert_eq!(stored(&mut store), vec![(Pos::new(2, 1), "B".to_string())]);
+
+        // Beginning again drops the uncommitted batch.
+        store.begin().unwrap();
+        store.delete(&Pos::new(2, 1)).unwrap();
+        store.begin().unwrap();
+        store.commit().unwrap();
+        assert_eq!(store.len(), 1);
+    }
+
+    #[test]
+    fn test_memory_store_load_range() {
+        let mut store: MemoryStore<usize> = MemoryStore::new();
+        for i in 1..=5 {
+            store.upsert(&Pos::new(i, 1), &(i as usize)).unwrap();
+        }
+
+        let elements: Vec<usize> = store
+            .load_range(Pos::new(2, 1)..Pos::new(4, 1))
+            .unwrap()
+            .into_iter()
+            .map(|(_, element)| element)
+            .collect();
+
+        assert_eq!(elements, vec![2, 3]);
+    }
+
+    #[test]
+    fn test_memory_store_load_range_empty() {
+        use std::ops::Bound;
+
+        let mut store: MemoryStore<usize> = MemoryStore::new();
+        for i in 1..=5 {
+            store.upsert(&Pos::new(i, 1), &(i as usize)).unwrap();
+        }
+
+        assert_eq!(store.load_range(Pos::new(4, 1)..Pos::new(2, 1)).unwrap(), vec![]);
+        assert_eq!(store.load_range(Pos::new(4, 1)..=Pos::new(2, 1)).unwrap(), vec![]);
+        assert_eq!(store.load_range(Pos::new(3, 1)..Pos::new(3, 1)).unwrap(), vec![]);
+        assert_eq!(
+            store
+                .load_range((Bound::Excluded(Pos::new(3, 1)), Bound::Excluded(Pos::new(3, 1))))
+                .unwrap(),
+            vec![]
+        );
+        assert_eq!(
+            store.load_range(Pos::new(3, 1)..=Pos::new(3, 1)).unwrap(),
+            vec![(Pos::new(3, 1), 3)]
+        );
+    }
+
+    #[test]
+    fn test_load_from() {
+        let mut store: MemoryStore<String> = MemoryStore::new();
+        store.upsert(&Pos::new(3, 1), &"C".to_string()).unwrap();
+        store.upsert(&Pos::new(1, 2), &"A".to_string()).unwrap();
+        store.upsert(&Pos::new(2, 1), &"B".to_string()).unwrap();
+
+        let seq: Sequence<String> = Sequence::load_from(&mut store).unwrap();
+
+        assert_eq!(elements(&seq), vec!["A", "B", "C"]);
+        assert_eq!(seq.position_from(0), Some(Pos::new(1, 2)));
+        assert_eq!(seq.is_journal_enabled(), true);
+    }
+
+    #[test]
+    fn test_load_range_from() {
+        let mut store: MemoryStore<String> = MemoryStore::new();
+        for (i, element) in ["A", "B", "C", "D"].into_iter().enumerate() {
+            store.upsert(&Pos::new(i as u64 + 1, 1), &element.to_string()).unwrap();
+        }
+
+        let mut seq: Sequence<String> = Sequence::load_range_from(&mut store, Pos::new(2, 1)..=Pos::new(3, 1)).unwrap();
+        assert_eq!(elements(&seq), vec!["B", "C"]);
+
+        // Elements outside of range are kept.
+        seq.insert(1, "X".to_string());
+        seq.remove(0);
+        seq.sync_to(&mut store).unwrap();
+        let stored: Vec<String> = stored(&mut store).into_iter().map(|(_, element)| element).collect();
+        assert_eq!(stored, vec!["A", "X", "C", "D"]);
+    }
+
+    #[test]
+    fn test_sync_to() {
+        let mut store: MemoryStore<String> = MemoryStore::new();
+        let mut seq = setup_seq_abc();
+
+        // The journal is disabled.
+        assert_eq!(seq.sync_to(&mut store).unwrap(), 0);
+        assert_eq!(store.is_empty(), true);
+
+        let mut seq: Sequence<String> = Sequence::load_from(&mut store).unwrap();
+        seq.extend(["A", "B", "C"].map(String::from));
+        assert_eq!(seq.sync_to(&mut store).unwrap(), 3);
+        assert_eq!(stored(&mut store), nodes(&seq));
+
+        seq.insert(1, "X".to_string());
+        seq.remove(3);
+        seq.insert_at(Pos::new(1, 1), "Y".to_string());
+        assert_eq!(seq.sync_to(&mut store).unwrap(), 3);
+        assert_eq!(stored(&mut store), nodes(&seq));
+        assert_eq!(seq.sync_to(&mut store).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_sync_to_moves() {
+        let mut store: MemoryStore<String> = MemoryStore::new();
+        let mut seq: Sequence<String> = Sequence::load_from(&mut store).unwrap();
+        seq.extend(["D", "B", "A", "C"].map(String::from));
+
+        seq.move_to(0, 3).unwrap();
+        seq.sort_by(|a, b| a.cmp(b)).unwrap();
+        seq.reverse().unwrap();
+        seq.rebalance();
+        seq.sync_to(&mut store).unwrap();
+
+        assert_eq!(stored(&mut store), nodes(&seq));
+        assert_eq!(elements(&Sequence::load_from(&mut store).unwrap()), vec!["D", "C", "B", "A"]);
+    }
+
+    #[test]
+    fn test_sync_to_random() {
+        let mut rng = StdRng::seed_from_u64(25);
+        let mut store: MemoryStore<usize> = MemoryStore::new();
+        let mut seq: Sequence<usize> = Sequence::load_from(&mut store).unwrap();
+
+        for i in 0..500 {
+            match rng.gen_range(0..5) {
+                0 => seq.insert(rng.gen_range(0..=seq.len()), i),
+                1 if !seq.is_empty() => {
+                    seq.remove(rng.gen_range(0..seq.len()));
+                }
+                2 if !seq.is_empty() => {
+                    seq.move_to(rng.gen_range(0..seq.len()), rng.gen_range(0..seq.len())).unwrap();
+                }
+                3 if !seq.is_empty() => {
+                    let position = seq.position_from(rng.gen_range(0..seq.len())).unwrap();
+                    seq.insert_at(position, i);
+                }
+                _ => seq.push(i),
+            }
+
+            if rng.gen_bool(0.3) {
+                seq.sync_to(&mut store).unwrap();
+                assert_eq!(stored(&mut store), nodes(&seq));
+            }
+        }
+    }
+
+    #[test]
+    fn test_sync_to_failure_keeps_changes() {
+        let mut store = FailingStore::default();
+        let mut seq: Sequence<String> = Sequence::load_from(&mut store).unwrap();
+        seq.push("A".to_string());
+        seq.push("B".to_string());
+        store.fail = true;
+
+        assert_eq!(seq.sync_to(&mut store), Err("connection lost"));
+
+        seq.push("C".to_string());
+        store.fail = false;
+        assert_eq!(seq.sync_to(&mut store), Ok(3));
+        assert_eq!(stored(&mut store), nodes(&seq));
+    }
+
+    #[test]
+    fn test_file_store_open_missing_file() {
+        let path = temp_path("missing");
+        let store: FileStore<String> = FileStore::open(&path).unwrap();
+
+        assert_eq!(store.is_empty(), true);
+        assert_eq!(store.path(), path.as_path());
+        assert_eq!(path.exists(), false);
+    }
+
+    #[test]
+    fn test_file_store_sync_and_reopen() {
+        let path = temp_path("reopen");
+        let mut store: FileStore<String> = FileStore::open(&path).unwrap();
+        let mut seq: Sequence<String> = Sequence::load_from(&mut store).unwrap();
+        seq.extend(["A", "B", "C"].map(String::from));
+        seq.sync_to(&mut store).unwrap();
+        seq.remove(1);
+        seq.insert(0, "X".to_string());
+        seq.sync_to(&mut store).unwrap();
+
+        let mut reopened: FileStore<String> = FileStore::open(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(reopened.len(), 3);
+        assert_eq!(reopened.get(&Pos::new(1, 1)), Some(&"A".to_string()));
+        assert_eq!(stored(&mut reopened), nodes(&seq));
+        assert_eq!(reopened, store);
+    }
+
+    #[test]
+    fn test_file_store_batch() {
+        let path = temp_path("batch");
+        let mut store: FileStore<String> = FileStore::open(&path).unwrap();
+        store.upsert(&Pos::new(1, 1), &"A".to_string()).unwrap();
+
+        store.begin().unwrap();
+        store.upsert(&Pos::new(2, 1), &"B".to_string()).unwrap();
+        store.delete(&Pos::new(1, 1)).unwrap();
+        assert_eq!(FileStore::<String>::open(&path).unwrap().len(), 1);
+        assert_eq!(store.get(&Pos::new(1, 1)), Some(&"A".to_string()));
+
+        store.commit().unwrap();
+        let mut reopened: FileStore<String> = FileStore::open(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(stored(&mut reopened), vec![(Pos::new(2, 1), "B".to_string())]);
+    }
+
+    #[test]
+    fn test_file_store_write_failure() {
+        // The directory of the file does not exist, so every write fails.
+        let path = temp_path("missing_dir").join("store.seq");
+        let mut store: FileStore<String> = FileStore::open(&path).unwrap();
+
+        assert_eq!(store.upsert(&Pos::new(1, 1), &"A".to_string()).is_err(), true);
+        assert_eq!(store.is_empty(), true);
+
+        store.begin().unwrap();
+        store.upsert(&Pos::new(1, 1), &"A".to_string()).unwrap();
+        assert_eq!(store.commit().is_err(), true);
+        assert_eq!(store.get(&Pos::new(1, 1)), None);
+
+        // The batch is still open, so committing it can be repeated.
+        assert_eq!(store.commit().is_err(), true);
+        assert_eq!(store.is_empty(), true);
+    }
+
+    #[test]
+    fn test_file_store_load_range() {
+        let path = temp_path("load_range");
+        let mut store: FileStore<i64> = FileStore::open(&path).unwrap();
+        store.begin().unwrap();
+        for i in 1..=5 {
+            store.upsert(&Pos::new(i, 1), &(i as i64)).unwrap();
+        }
+        store.commit().unwrap();
+
+        let seq: Sequence<i64> = Sequence::load_range_from(&mut store, Pos::new(4, 1)..).unwrap();
+        let inverted: Sequence<i64> = Sequence::load_range_from(&mut store, Pos::new(4, 1)..Pos::new(2, 1)).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(elements(&seq), vec![4, 5]);
+        assert_eq!(inverted.is_empty(), true);
+    }
+
+    #[test]
+    fn test_file_store_large_positions() {
+        let path = temp_path("large_positions");
+        let mut store: FileStore<String> = FileStore::open(&path).unwrap();
+        store.upsert(&Pos::new(u64::MAX, 1), &"max".to_string()).unwrap();
+        store.upsert(&Pos::new(1, u64::MAX), &"min".to_string()).unwrap();
+
+        let seq: Sequence<String> = Sequence::load_from(&mut FileStore::open(&path).unwrap()).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(nodes(&seq), stored(&mut store));
+    }
+
+    #[test]
+    fn test_file_store_invalid_file() {
+        let path = temp_path("invalid");
+        let mut store: FileStore<String> = FileStore::open(&path).unwrap();
+        store.upsert(&Pos::new(1, 1), &"A".to_string()).unwrap();
+        let bytes = std::fs::read(&path).unwrap();
+
+        let invalid: [&[u8]; 4] = [
+            // Missing header
+            b"a sequence",
+            // Truncated record
+            &bytes[..bytes.len() - 1],
+            // Zero denominator
+            &[&bytes[..bytes.len() - 10], &[0], &bytes[bytes.len() - 9..]].concat(),
+            // Invalid element
+            &[&bytes[..bytes.len() - 1], &[0xff]].concat(),
+        ];
+        for bytes in invalid {
+            std::fs::write(&path, bytes).unwrap();
+            let error = FileStore::<String>::open(&path).unwrap_err();
+
+            assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+        }
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_file_codecs() {
+        fn roundtrip<T: FileCodec + PartialEq + std::fmt::Debug>(element: T) {
+            assert_eq!(T::decode(&element.encode()).unwrap(), element);
+        }
+
+        roundtrip("Kodiak 🐻".to_string());
+        roundtrip(vec![0u8, 255]);
+        roundtrip(i64::MIN);
+        roundtrip(-1.5f64);
+        roundtrip(true);
+        roundtrip(false);
+
+        assert_eq!(String::decode(&[0xff]).is_err(), true);
+        assert_eq!(i64::decode(&[0; 7]).is_err(), true);
+        assert_eq!(f64::decode(&[0; 9]).is_err(), true);
+        assert_eq!(bool::decode(&[2]).is_err(), true);
+    }
+}
@@ -0,0 +1,148 @@
+#[cfg(test)]
+#[allow(clippy::bool_assert_comparison)]
+mod tests {
+    use crate::order_key::OrderKey;
+    use crate::sequence::{Node, Pos, PositionStrategy};
+    use crate::tree_sequence::{height, size, Link, TreeSequence};
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    // Operations shared with `Sequence` are tested for both, see `shared_sequence_tests`.
+    // The tests below cover what is specific to the tree.
+
+    pub fn setup_seq_abc() -> TreeSequence<String> {
+        let mut seq: TreeSequence<String> = TreeSequence::new();
+
+        seq.push("A".to_string());
+        seq.push("B".to_string());
+        seq.push("C".to_string());
+
+        seq
+    }
+
+    // Asserts the AVL property, subtree sizes and the order of positions, returns the subtree's nodes.
+    fn assert_invariants<T>(link: &Link<T, Pos>) -> Vec<&Node<T, Pos>> {
+        match link {
+            None => Vec::new(),
+            Some(tree_node) => {
+                let mut nodes = assert_invariants(&tree_node.left);
+                nodes.push(&tree_node.node);
+                nodes.extend(assert_invariants(&tree_node.right));
+
+                assert_eq!(tree_node.balance_factor().abs() <= 1, true);
+                assert_eq!(tree_node.height, height(&tree_node.left).max(height(&tree_node.right)) + 1);
+                assert_eq!(tree_node.size, nodes.iter().filter(|node| node.is_some()).count());
+                assert_eq!(
+                    tree_node.size,
+                    size(&tree_node.left) + size(&tree_node.right) + usize::from(tree_node.node.is_some())
+                );
+                assert_eq!(nodes.windows(2).all(|pair| pair[0].position() < pair[1].position()), true);
+
+                nodes
+            }
+        }
+    }
+
+    #[test]
+    fn test_iter() {
+        let mut seq = setup_seq_abc();
+        seq.remove(1);
+
+        let mut iter = seq.iter();
+        assert_eq!(iter.len(), 2);
+        assert_eq!(iter.next().map(|node| node.position()), Some(&Pos::new(1, 1)));
+        assert_eq!(iter.len(), 1);
+        assert_eq!(format!("{:?}", iter), format!("{:?}", vec![seq.node(1).unwrap()]));
+        assert_eq!(iter.len(), 1);
+
+        let mut iter = seq.iter_with_tombstones();
+        assert_eq!(iter.len(), 3);
+        assert_eq!(iter.nth(1).map(|node| node.is_none()), Some(true));
+
+        let nodes: Vec<Node<String, Pos>> = seq.into_iter().collect();
+        assert_eq!(nodes[1].position(), &Pos::new(3, 1));
+        assert_eq!(nodes.len(), 2);
+    }
+
+    #[test]
+    fn test_clone_eq_debug() {
+        let seq = setup_seq_abc();
+        let mut clone = seq.clone();
+
+        assert_eq!(clone, seq);
+        assert_eq!(
+            format!("{:?}", seq),
+            format!(
+                "TreeSequence {{ nodes: {:?}, strategy: Mediant, removals: 0, next_id: 3, journal: None }}",
+                seq.iter().collect::<Vec<_>>()
+            )
+        );
+
+        clone.remove(0);
+        assert_eq!(clone == seq, false);
+    }
+
+    #[test]
+    fn test_order_key() {
+        let mut seq: TreeSequence<String, OrderKey> = TreeSequence::with_position();
+
+        seq.push("B".to_string());
+        seq.insert(0, "A".to_string());
+        seq.push("C".to_string());
+
+        let keys: Vec<&str> = seq.iter().map(|node| node.position().as_str()).collect();
+        assert_eq!(keys, vec!["Zz", "a0", "a1"]);
+    }
+
+    #[test]
+    fn test_random_operations_keep_invariants() {
+        let mut rng = StdRng::seed_from_u64(11);
+        let mut seq: TreeSequence<usize> = TreeSequence::with_strategy(PositionStrategy::SternBrocot);
+
+        for i in 0..2_000 {
+            let index = rng.gen_range(0..=seq.len());
+            match rng.gen_range(0..20) {
+                0..=5 => {
+                    seq.remove(index);
+                }
+                6..=7 => seq.insert_at(Pos::new(rng.gen_range(1..100), rng.gen_range(1..100)), i),
+                8 => {
+                    seq.rebalance();
+                }
+                9 => {
+                    seq.compact_before(seq.watermark().saturating_sub(10));
+                }
+                _ => seq.insert(index, i),
+            }
+
+            if i % 100 == 0 {
+                let nodes = assert_invariants(&seq.root);
+                assert_eq!(nodes.len(), seq.len() + seq.tombstones());
+            }
+        }
+
+        assert_eq!(assert_invariants(&seq.root).len(), seq.len() + seq.tombstones());
+    }
+
+    #[test]
+    fn test_sorted_pushes_stay_balanced() {
+        let mut seq: TreeSequence<usize> = TreeSequence::new();
+
+        for i in 0..10_000 {
+            seq.push(i);
+        }
+
+        // An AVL tree of n nodes has a height of less than 1.45 * log2(n + 2).
+        assert_eq!(height(&seq.root) <= 19, true);
+        assert_eq!(seq.get(5_000), Some(&5_000));
+        assert_invariants(&seq.root);
+
+        for index in (0..10_000).step_by(2) {
+            seq.remove_at(Pos::new(index + 1, 1));
+        }
+        assert_eq!(seq.get(2_500), Some(&5_001));
+        assert_eq!(seq.compact(), 5_000);
+        assert_eq!(height(&seq.root) <= 13, true);
+        assert_invariants(&seq.root);
+    }
+}
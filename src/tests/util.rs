@@ -0,0 +1,63 @@
+// Helpers shared by the tests of several modules.
+
+use crate::sequence::Sequence;
+
+pub fn setup_seq_abc() -> Sequence<String> {
+    let mut seq: Sequence<String> = Sequence::new();
+
+    seq.push("A".to_string());
+    seq.push("B".to_string());
+    seq.push("C".to_string());
+
+    seq
+}
+
+pub fn elements<T: Clone>(seq: &Sequence<T>) -> Vec<T> {
+    seq.values().cloned().collect()
+}
+
+// An element type without a codec of the crate, encoded as text `x,y`.
+#[cfg(any(feature = "sqlite", feature = "postgres"))]
+#[derive(Clone, Debug, PartialEq)]
+pub struct Point {
+    pub x: i64,
+    pub y: i64,
+}
+
+#[cfg(feature = "sqlite")]
+impl crate::sqlite::SqliteCodec for Point {
+    fn encode(&self) -> rusqlite::types::Value {
+        rusqlite::types::Value::Text(format!("{},{}", self.x, self.y))
+    }
+
+    fn decode(value: rusqlite::types::ValueRef<'_>) -> rusqlite::types::FromSqlResult<Self> {
+        use rusqlite::types::FromSqlError;
+
+        let text = value.as_str()?;
+        let (x, y) = text.split_once(',').ok_or(FromSqlError::InvalidType)?;
+
+        Ok(Point {
+            x: x.parse().map_err(|error| FromSqlError::Other(Box::new(error)))?,
+            y: y.parse().map_err(|error| FromSqlError::Other(Box::new(error)))?,
+        })
+    }
+}
+
+#[cfg(feature = "postgres")]
+impl crate::postgres::PostgresCodec for Point {
+    const SQL_TYPE: &'static str = "TEXT";
+    type Column = String;
+
+    fn encode(&self) -> Self::Column {
+        format!("{},{}", self.x, self.y)
+    }
+
+    fn decode(column: Self::Column) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        let (x, y) = column.split_once(',').ok_or("missing ','")?;
+
+        Ok(Point {
+            x: x.parse()?,
+            y: y.parse()?,
+        })
+    }
+}
@@ -0,0 +1,814 @@
+//! `TreeSequence` is a `Sequence` backed by an order-statistic tree, i.e. an AVL tree whose nodes know the number
+//! of elements in their subtree. Operations on indices as well as on positions take O(log n).
+
+use std::borrow::Borrow;
+use std::cmp::Ordering;
+use std::fmt::{Debug, Formatter};
+
+use crate::error::PositionExhausted;
+use crate::sequence::{Change, Node, NodeId, Pos, Position, PositionStrategy};
+
+type Link<T, P> = Option<Box<TreeNode<T, P>>>;
+
+#[derive(Clone)]
+struct TreeNode<T, P> {
+    node: Node<T, P>,
+    height: u8,
+    // Number of elements in the subtree rooted at this node, i.e. of nodes which are not empty.
+    size: usize,
+    left: Link<T, P>,
+    right: Link<T, P>,
+}
+
+impl<T, P> TreeNode<T, P> {
+    #[inline]
+    fn new(node: Node<T, P>) -> Box<Self> {
+        let size = usize::from(node.is_some());
+        Box::new(TreeNode {
+            node,
+            height: 1,
+            size,
+            left: None,
+            right: None,
+        })
+    }
+
+    /// Recomputes height and size from the node's children.
+    #[inline]
+    fn update(&mut self) {
+        self.height = height(&self.left).max(height(&self.right)) + 1;
+        self.size = size(&self.left) + size(&self.right) + usize::from(self.node.is_some());
+    }
+
+    #[inline]
+    fn balance_factor(&self) -> i16 {
+        height(&self.left) as i16 - height(&self.right) as i16
+    }
+}
+
+#[inline]
+fn height<T, P>(link: &Link<T, P>) -> u8 {
+    link.as_ref().map_or(0, |tree_node| tree_node.height)
+}
+
+#[inline]
+fn size<T, P>(link: &Link<T, P>) -> usize {
+    link.as_ref().map_or(0, |tree_node| tree_node.size)
+}
+
+fn rotate_left<T, P>(mut tree_node: Box<TreeNode<T, P>>) -> Box<TreeNode<T, P>> {
+    let mut right = tree_node.right.take().expect("rotating left requires a right child");
+    tree_node.right = right.left.take();
+    tree_node.update();
+    right.left = Some(tree_node);
+    right.update();
+
+    right
+}
+
+fn rotate_right<T, P>(mut tree_node: Box<TreeNode<T, P>>) -> Box<TreeNode<T, P>> {
+    let mut left = tree_node.left.take().expect("rotating right requires a left child");
+    tree_node.left = left.right.take();
+    tree_node.update();
+    left.right = Some(tree_node);
+    left.update();
+
+    left
+}
+
+/// Restores the AVL property of tree_node, whose subtrees differ in height by at most 2.
+fn rebalance<T, P>(mut tree_node: Box<TreeNode<T, P>>) -> Box<TreeNode<T, P>> {
+    tree_node.update();
+
+    match tree_node.balance_factor() {
+        2.. => {
+            if tree_node.left.as_ref().is_some_and(|left| left.balance_factor() < 0) {
+                tree_node.left = tree_node.left.take().map(rotate_left);
+            }
+            rotate_right(tree_node)
+        }
+        ..=-2 => {
+            if tree_node.right.as_ref().is_some_and(|right| right.balance_factor() > 0) {
+                tree_node.right = tree_node.right.take().map(rotate_right);
+            }
+            rotate_left(tree_node)
+        }
+        _ => tree_node,
+    }
+}
+
+/// Inserts node, whose position must not be taken by another node.
+fn insert<T, P: Ord>(link: Link<T, P>, node: Node<T, P>) -> Box<TreeNode<T, P>> {
+    match link {
+        None => TreeNode::new(node),
+        Some(mut tree_node) => {
+            if node.position < tree_node.node.position {
+                tree_node.left = Some(insert(tree_node.left.take(), node));
+            } else {
+                tree_node.right = Some(insert(tree_node.right.take(), node));
+            }
+            rebalance(tree_node)
+        }
+    }
+}
+
+/// Applies f to the node at position and updates the sizes along the path, or returns None if there is no node.
+fn modify<T, P: Ord, R>(link: &mut Link<T, P>, position: &P, f: impl FnOnce(&mut Node<T, P>) -> R) -> Option<R> {
+    let tree_node = link.as_deref_mut()?;
+    let result = match position.cmp(&tree_node.node.position) {
+        Ordering::Less => modify(&mut tree_node.left, position, f),
+        Ordering::Equal => Some(f(&mut tree_node.node)),
+        Ordering::Greater => modify(&mut tree_node.right, position, f),
+    };
+    tree_node.update();
+
+    result
+}
+
+/// Builds a balanced tree of the next count nodes, which must be ordered by position.
+fn build<T, P>(nodes: &mut impl Iterator<Item = Node<T, P>>, count: usize) -> Link<T, P> {
+    if count == 0 {
+        return None;
+    }
+
+    let left = build(nodes, count / 2);
+    let mut tree_node = TreeNode::new(nodes.next().expect("count nodes are left"));
+    tree_node.left = left;
+    tree_node.right = build(nodes, count - count / 2 - 1);
+    tree_node.update();
+
+    Some(tree_node)
+}
+
+/// Moves the nodes of the tree into nodes, in order.
+fn collect<T, P>(link: Link<T, P>, nodes: &mut Vec<Node<T, P>>) {
+    if let Some(tree_node) = link {
+        let TreeNode { node, left, right, .. } = *tree_node;
+        collect(left, nodes);
+        nodes.push(node);
+        collect(right, nodes);
+    }
+}
+
+/// A sequence with a deterministic and easy to persist ordering, backed by an order-statistic tree.
+///
+/// `TreeSequence` offers the operations of [`BTreeSequence`](`crate::BTreeSequence`) but range(). Addressing elements
+/// by index or by position takes O(log n), while addressing them by id takes O(n).
+/// Prefer `TreeSequence` for large sequences which are mostly edited by index, e.g. in a UI.
+///
+/// Methods of the same name behave like those of [`Sequence`](`crate::Sequence`) and hand out the same positions and ids:
+/// removed elements leave empty nodes behind, whose positions are not handed out again until they are dropped,
+/// elements have stable ids, and changes are recorded in a journal if enabled.
+///
+/// What you can do
+/// * Create a TreeSequence: [new()](`TreeSequence::new()`) or with_strategy()
+/// * Create a TreeSequence of another position type: with_position()
+/// * Determine if it contains elements: is_empty()
+/// * Determine how many elements it contains: len()
+/// * Get the first element: first()
+/// * Get the last element: last()
+/// * Get a reference to an element at a defined index: get() and get_mut()
+/// * Get the node holding an element at a defined index or position: node() and node_at()
+/// * Get a reference to an element at a defined position: get_at() and get_at_mut()
+/// * Insert an element at a defined index: insert() and try_insert()
+/// * Insert an element at a defined position: insert_at()
+/// * Get an element's index from its position and vice versa: index_from() and position_from()
+/// * Append an element to the sequence: push() and try_push()
+/// * Remove an element at a defined index: remove()
+/// * Remove an element at a defined position: remove_at()
+/// * Address an element by its stable id: id_of(), get_by_id(), position_of() and remove_by_id()
+/// * Iterate over the nodes: iter()
+/// * Iterate over all nodes including empty ones: iter_with_tombstones()
+/// * Reassign compact positions to all elements: rebalance()
+/// * Drop empty nodes left behind by removed elements: tombstones(), compact() and compact_before()
+/// * Record changes to persist them incrementally: enable_journal(), disable_journal() and take_changes()
+#[derive(Clone)]
+pub struct TreeSequence<T, P = Pos> {
+    root: Link<T, P>,
+    // Number of nodes including empty ones, while the root's size is the number of elements.
+    nodes: usize,
+    strategy: PositionStrategy,
+    removals: u64,
+    next_id: u64,
+    journal: Option<Vec<Change<P>>>,
+}
+
+impl<T> TreeSequence<T, Pos> {
+    /// Creates an empty sequence with positions of type `Pos`.
+    /// Use [`with_position()`](`TreeSequence::with_position()`) for other position types.
+    #[inline]
+    #[must_use]
+    pub fn new() -> Self {
+        Self::with_position()
+    }
+
+    /// Creates an empty sequence with positions of type `Pos`,
+    /// choosing the positions of inserted elements as defined by strategy.
+    #[inline]
+    #[must_use]
+    pub fn with_strategy(strategy: PositionStrategy) -> Self {
+        let mut seq = Self::new();
+        seq.strategy = strategy;
+        seq
+    }
+}
+
+impl<T, P: Position> TreeSequence<T, P> {
+    /// Creates an empty sequence with positions of type `P`, e.g. `TreeSequence::<char, OrderKey>::with_position()`.
+    #[inline]
+    #[must_use]
+    pub fn with_position() -> Self {
+        Self {
+            root: None,
+            nodes: 0,
+            strategy: PositionStrategy::default(),
+            removals: 0,
+            next_id: 0,
+            journal: None,
+        }
+    }
+
+    /// Returns the strategy used to choose the positions of inserted elements.
+    #[inline]
+    pub fn strategy(&self) -> PositionStrategy {
+        self.strategy
+    }
+
+    /// Sets the strategy used to choose the positions of inserted elements.
+    /// Positions of elements already in the sequence do not change.
+    #[inline]
+    pub fn set_strategy(&mut self, strategy: PositionStrategy) {
+        self.strategy = strategy;
+    }
+
+    /// Returns true if the sequence contains no elements.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the number of elements in the sequence.
+    #[inline]
+    pub fn len(&self) -> usize {
+        size(&self.root)
+    }
+
+    /// Returns the first element, or None if the sequence is empty.
+    #[inline]
+    #[must_use]
+    pub fn first(&self) -> Option<&T> {
+        self.get(0)
+    }
+
+    /// Returns the last element, or None if the sequence is empty.
+    #[inline]
+    #[must_use]
+    pub fn last(&self) -> Option<&T> {
+        self.len().checked_sub(1).and_then(|index| self.get(index))
+    }
+
+    /// Returns the element at index, or None if out of bounds. Indices count elements only, i.e. skip empty nodes.
+    #[inline]
+    #[must_use]
+    pub fn get(&self, index: usize) -> Option<&T> {
+        self.node(index).and_then(Node::element_as_ref)
+    }
+
+    /// Returns the element at index mutably, or None if out of bounds.
+    #[must_use]
+    pub fn get_mut(&mut self, mut index: usize) -> Option<&mut T> {
+        let mut link = self.root.as_deref_mut();
+
+        while let Some(tree_node) = link {
+            let left = size(&tree_node.left);
+            if index < left {
+                link = tree_node.left.as_deref_mut();
+            } else if index == left && tree_node.node.is_some() {
+                return tree_node.node.element_as_mut();
+            } else {
+                index -= left + usize::from(tree_node.node.is_some());
+                link = tree_node.right.as_deref_mut();
+            }
+        }
+
+        None
+    }
+
+    /// Returns the node holding the element at index, or None if out of bounds.
+    #[must_use]
+    pub fn node(&self, mut index: usize) -> Option<&Node<T, P>> {
+        let mut link = self.root.as_deref();
+
+        while let Some(tree_node) = link {
+            let left = size(&tree_node.left);
+            if index < left {
+                link = tree_node.left.as_deref();
+            } else if index == left && tree_node.node.is_some() {
+                return Some(&tree_node.node);
+            } else {
+                index -= left + usize::from(tree_node.node.is_some());
+                link = tree_node.right.as_deref();
+            }
+        }
+
+        None
+    }
+
+    /// Returns the node holding the element at position, or None if there is none.
+    #[inline]
+    #[must_use]
+    pub fn node_at<Q: Borrow<P>>(&self, position: Q) -> Option<&Node<T, P>> {
+        self.find(position.borrow()).filter(|node| node.is_some())
+    }
+
+    /// Returns the element at position, or None if there is none.
+    #[inline]
+    #[must_use]
+    pub fn get_at<Q: Borrow<P>>(&self, position: Q) -> Option<&T> {
+        self.node_at(position).and_then(Node::element_as_ref)
+    }
+
+    /// Returns the element at position mutably, or None if there is none.
+    #[must_use]
+    pub fn get_at_mut<Q: Borrow<P>>(&mut self, position: Q) -> Option<&mut T> {
+        let position = position.borrow();
+        let mut link = self.root.as_deref_mut();
+
+        while let Some(tree_node) = link {
+            match position.cmp(&tree_node.node.position) {
+                Ordering::Less => link = tree_node.left.as_deref_mut(),
+                Ordering::Equal => return tree_node.node.element_as_mut(),
+                Ordering::Greater => link = tree_node.right.as_deref_mut(),
+            }
+        }
+
+        None
+    }
+
+    /// Returns the index of the element at position, or None if there is none.
+    pub fn index_from<Q: Borrow<P>>(&self, position: Q) -> Option<usize> {
+        let position = position.borrow();
+        let mut link = self.root.as_deref();
+        let mut index = 0;
+
+        while let Some(tree_node) = link {
+            match position.cmp(&tree_node.node.position) {
+                Ordering::Less => link = tree_node.left.as_deref(),
+                Ordering::Equal => return tree_node.node.is_some().then(|| index + size(&tree_node.left)),
+                Ordering::Greater => {
+                    index += size(&tree_node.left) + usize::from(tree_node.node.is_some());
+                    link = tree_node.right.as_deref();
+                }
+            }
+        }
+
+        None
+    }
+
+    /// Returns the position of the element at index, or None if out of bounds.
+    #[inline]
+    pub fn position_from(&self, index: usize) -> Option<P> {
+        self.node(index).map(|node| node.position().clone())
+    }
+
+    /// Inserts an element at index, shifting all elements after it to the right.
+    /// Indices count elements only, i.e. skip empty nodes. Appends the element if index >= len.
+    ///
+    /// # Panics
+    /// Panics if there is no position left between the element's neighbours,
+    /// see [`try_insert()`](`TreeSequence::try_insert()`) for a non-panicking alternative.
+    pub fn insert(&mut self, index: usize, element: T) {
+        if let Err(err) = self.try_insert(index, element) {
+            panic!("{}", err);
+        }
+    }
+
+    /// Inserts an element at index like [`insert()`](`TreeSequence::insert()`) and returns the element's position.
+    ///
+    /// # Errors
+    /// Returns `PositionExhausted` if there is no position left between the element's neighbours.
+    /// The sequence is left unchanged and the element is dropped.
+    pub fn try_insert(&mut self, index: usize, element: T) -> Result<P, PositionExhausted<P>> {
+        let Some(upper) = self.node(index).map(Node::position) else {
+            return self.try_push(element);
+        };
+
+        // The node before may be empty, its position is not handed out again.
+        let lower = self.position_before(upper);
+        let pos = P::between(lower, Some(upper), self.strategy)
+            .ok_or_else(|| PositionExhausted::new(lower.cloned(), Some(upper.clone())))?;
+        self.insert_node(pos.clone(), element);
+
+        Ok(pos)
+    }
+
+    /// Inserts an element at position. If there is an element at the position, it is overwritten and keeps its id.
+    /// If not, element is inserted with a new id, also if an empty node is left at the position.
+    pub fn insert_at(&mut self, position: P, element: T) {
+        let Some(node) = self.find(&position) else {
+            self.insert_node(position, element);
+            return;
+        };
+
+        // Record the node's position, which may differ from an equal position, e.g. `2/4` for `1/2`.
+        let pos = node.position().clone();
+        let id = if node.is_none() {
+            self.record(Change::Inserted { pos: pos.clone() });
+            Some(self.generate_id())
+        } else {
+            self.record(Change::Updated { pos: pos.clone() });
+            None
+        };
+
+        modify(&mut self.root, &pos, |node| {
+            node.id = id.unwrap_or(node.id);
+            node.set(element);
+        });
+    }
+
+    /// Appends an element to the back of the sequence.
+    ///
+    /// # Panics
+    /// Panics if there is no position left after the last position,
+    /// see [`try_push()`](`TreeSequence::try_push()`) for a non-panicking alternative.
+    #[inline]
+    pub fn push(&mut self, element: T) {
+        if let Err(err) = self.try_push(element) {
+            panic!("{}", err);
+        }
+    }
+
+    /// Appends an element to the back of the sequence and returns the element's position.
+    /// The position is after the last node, which may be empty.
+    ///
+    /// # Errors
+    /// Returns `PositionExhausted` if there is no position left after the last position.
+    /// The sequence is left unchanged and the element is dropped.
+    pub fn try_push(&mut self, element: T) -> Result<P, PositionExhausted<P>> {
+        let last = self.last_position();
+        let pos = P::between(last, None, self.strategy).ok_or_else(|| PositionExhausted::new(last.cloned(), None))?;
+        self.insert_node(pos.clone(), element);
+
+        Ok(pos)
+    }
+
+    /// Removes and returns the element at index, or None if out of bounds.
+    /// Like [`Sequence::remove()`](`crate::Sequence::remove()`), an empty node is left at the element's position.
+    pub fn remove(&mut self, index: usize) -> Option<T> {
+        let position = self.position_from(index)?;
+        self.remove_at(position)
+    }
+
+    /// Removes and returns the element at position, or None if there is none, see [`remove()`](`TreeSequence::remove()`).
+    pub fn remove_at<Q: Borrow<P>>(&mut self, position: Q) -> Option<T> {
+        let pos = self.node_at(position)?.position().clone();
+        let removed = self.removals;
+        let element = modify(&mut self.root, &pos, |node| node.take(removed)).flatten();
+
+        self.removals += 1;
+        self.record(Change::Removed { pos });
+        element
+    }
+
+    /// Returns the id of the element at position, or None if there is none.
+    #[inline]
+    pub fn id_of<Q: Borrow<P>>(&self, position: Q) -> Option<NodeId> {
+        self.node_at(position).map(Node::id)
+    }
+
+    /// Returns the element with id, or None if there is none. Takes O(n).
+    #[inline]
+    pub fn get_by_id(&self, id: NodeId) -> Option<&T> {
+        self.iter().find(|node| node.id() == id).and_then(Node::element_as_ref)
+    }
+
+    /// Returns the position of the element with id, or None if there is none. Takes O(n).
+    #[inline]
+    pub fn position_of(&self, id: NodeId) -> Option<P> {
+        self.iter().find(|node| node.id() == id).map(|node| node.position().clone())
+    }
+
+    /// Removes and returns the element with id, or None if there is none, see [`remove()`](`TreeSequence::remove()`).
+    /// Takes O(n).
+    pub fn remove_by_id(&mut self, id: NodeId) -> Option<T> {
+        let position = self.position_of(id)?;
+        self.remove_at(position)
+    }
+
+    /// Returns an iterator over the nodes in order. Empty nodes are skipped,
+    /// see [`iter_with_tombstones()`](`TreeSequence::iter_with_tombstones()`).
+    #[inline]
+    pub fn iter(&self) -> TreeSequenceIter<'_, T, P> {
+        TreeSequenceIter::new(self.root.as_deref(), self.len(), false)
+    }
+
+    /// Returns an iterator over all nodes in order, including the empty nodes left behind by removed elements.
+    #[inline]
+    pub fn iter_with_tombstones(&self) -> TreeSequenceIter<'_, T, P> {
+        TreeSequenceIter::new(self.root.as_deref(), self.nodes, true)
+    }
+
+    /// Reassigns compact positions to all elements and drops all empty nodes, e.g. `1/1, 2/1, 3/1, ...` for `Pos`.
+    /// The order of elements does not change.
+    ///
+    /// Returns the list of changed positions as `(old, new)` pairs, see [`Sequence::rebalance()`](`crate::Sequence::rebalance()`).
+    pub fn rebalance(&mut self) -> Vec<(P, P)> {
+        let positions = P::spread(None, None, self.len()).expect("positions are unbounded");
+        let mut nodes = self.take_nodes();
+        nodes.retain(|node| node.is_some());
+
+        let mut changes = Vec::new();
+        for (node, pos) in nodes.iter_mut().zip(positions) {
+            if node.position != pos {
+                let old = std::mem::replace(&mut node.position, pos.clone());
+                changes.push((old, pos));
+            }
+        }
+        self.put_nodes(nodes);
+        self.record_moves(&changes);
+
+        changes
+    }
+
+    /// Enables the journal, see [`Sequence::enable_journal()`](`crate::Sequence::enable_journal()`).
+    #[inline]
+    pub fn enable_journal(&mut self) {
+        self.journal.get_or_insert_with(Vec::new);
+    }
+
+    /// Stops recording changes and drops the changes recorded so far.
+    #[inline]
+    pub fn disable_journal(&mut self) {
+        self.journal = None;
+    }
+
+    /// Returns true if changes are recorded, see [`enable_journal()`](`TreeSequence::enable_journal()`).
+    #[inline]
+    pub fn is_journal_enabled(&self) -> bool {
+        self.journal.is_some()
+    }
+
+    /// Returns the changes recorded since the journal was enabled or changes were last taken, in order,
+    /// see [`Sequence::take_changes()`](`crate::Sequence::take_changes()`).
+    #[inline]
+    pub fn take_changes(&mut self) -> Vec<Change<P>> {
+        self.journal.as_mut().map(std::mem::take).unwrap_or_default()
+    }
+
+    /// Returns the number of empty nodes left behind by removed elements.
+    #[inline]
+    pub fn tombstones(&self) -> usize {
+        self.nodes - self.len()
+    }
+
+    /// Returns a watermark to pass to [`compact_before()`](`TreeSequence::compact_before()`) later on,
+    /// see [`Sequence::watermark()`](`crate::Sequence::watermark()`).
+    #[inline]
+    pub fn watermark(&self) -> u64 {
+        self.removals
+    }
+
+    /// Drops all empty nodes and rebuilds the tree, which takes O(n). Positions of the remaining elements do not change.
+    ///
+    /// Returns the number of dropped nodes.
+    pub fn compact(&mut self) -> usize {
+        self.compact_before(u64::MAX)
+    }
+
+    /// Drops the empty nodes left behind by removals before watermark was taken, see [`watermark()`](`TreeSequence::watermark()`).
+    /// Rebuilds the tree like [`compact()`](`TreeSequence::compact()`). Positions of the remaining elements do not change.
+    ///
+    /// Returns the number of dropped nodes.
+    pub fn compact_before(&mut self, watermark: u64) -> usize {
+        let tombstones = self.tombstones();
+        let mut nodes = self.take_nodes();
+        nodes.retain(|node| node.is_some() || node.removed.is_some_and(|removed| removed >= watermark));
+        self.put_nodes(nodes);
+
+        tombstones - self.tombstones()
+    }
+
+    /// Returns the node at position, which may be empty, or None if there is none.
+    fn find(&self, position: &P) -> Option<&Node<T, P>> {
+        let mut link = self.root.as_deref();
+
+        while let Some(tree_node) = link {
+            match position.cmp(&tree_node.node.position) {
+                Ordering::Less => link = tree_node.left.as_deref(),
+                Ordering::Equal => return Some(&tree_node.node),
+                Ordering::Greater => link = tree_node.right.as_deref(),
+            }
+        }
+
+        None
+    }
+
+    /// Returns the position of the node right before position, which may be empty.
+    fn position_before(&self, position: &P) -> Option<&P> {
+        let mut link = self.root.as_deref();
+        let mut before = None;
+
+        while let Some(tree_node) = link {
+            if tree_node.node.position < *position {
+                before = Some(&tree_node.node.position);
+                link = tree_node.right.as_deref();
+            } else {
+                link = tree_node.left.as_deref();
+            }
+        }
+
+        before
+    }
+
+    /// Returns the position of the last node, which may be empty.
+    fn last_position(&self) -> Option<&P> {
+        let mut tree_node = self.root.as_deref()?;
+        while let Some(right) = tree_node.right.as_deref() {
+            tree_node = right;
+        }
+
+        Some(&tree_node.node.position)
+    }
+
+    /// Inserts element into a new node at position, which must not be taken by a node.
+    fn insert_node(&mut self, position: P, element: T) {
+        let id = self.generate_id();
+        self.root = Some(insert(self.root.take(), Node::new(id, position.clone(), element)));
+        self.nodes += 1;
+        self.record(Change::Inserted { pos: position });
+    }
+
+    /// Takes all nodes out of the tree, in order.
+    fn take_nodes(&mut self) -> Vec<Node<T, P>> {
+        let mut nodes = Vec::with_capacity(self.nodes);
+        collect(self.root.take(), &mut nodes);
+        self.nodes = 0;
+
+        nodes
+    }
+
+    /// Builds a balanced tree of nodes, which must be ordered by position.
+    fn put_nodes(&mut self, nodes: Vec<Node<T, P>>) {
+        self.nodes = nodes.len();
+        self.root = build(&mut nodes.into_iter(), self.nodes);
+    }
+
+    /// Returns a new id, which has not been handed out by this sequence before.
+    #[inline]
+    fn generate_id(&mut self) -> NodeId {
+        self.next_id += 1;
+        NodeId::from(self.next_id - 1)
+    }
+
+    /// Records change, if the journal is enabled.
+    #[inline]
+    fn record(&mut self, change: Change<P>) {
+        if let Some(journal) = self.journal.as_mut() {
+            journal.push(change);
+        }
+    }
+
+    /// Records `(old, new)` pairs of positions as moves, if the journal is enabled.
+    fn record_moves(&mut self, changes: &[(P, P)]) {
+        for (from, to) in changes {
+            self.record(Change::Moved {
+                from: from.clone(),
+                to: to.clone(),
+            });
+        }
+    }
+}
+
+impl<T, P: Position> Default for TreeSequence<T, P> {
+    fn default() -> Self {
+        Self::with_position()
+    }
+}
+
+impl<T: Debug, P: Position> Debug for TreeSequence<T, P> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TreeSequence")
+            .field("nodes", &self.iter_with_tombstones())
+            .field("strategy", &self.strategy)
+            .field("removals", &self.removals)
+            .field("next_id", &self.next_id)
+            .field("journal", &self.journal)
+            .finish()
+    }
+}
+
+// The journal is not part of a sequence's value.
+impl<T: PartialEq, P: Position> PartialEq for TreeSequence<T, P> {
+    fn eq(&self, other: &Self) -> bool {
+        self.nodes == other.nodes
+            && self.strategy == other.strategy
+            && self.removals == other.removals
+            && self.next_id == other.next_id
+            && self.iter_with_tombstones().eq(other.iter_with_tombstones())
+    }
+}
+
+//
+// Iterator
+//
+
+/// Iterator over the nodes of a [`TreeSequence`] in order,
+/// see [`iter()`](`TreeSequence::iter()`) and [`iter_with_tombstones()`](`TreeSequence::iter_with_tombstones()`).
+pub struct TreeSequenceIter<'iterator, T: 'iterator, P: 'iterator = Pos> {
+    // Path to the next node, which is on top of the stack.
+    stack: Vec<&'iterator TreeNode<T, P>>,
+    remaining: usize,
+    tombstones: bool,
+}
+
+impl<'iterator, T: 'iterator, P: 'iterator> TreeSequenceIter<'iterator, T, P> {
+    #[inline]
+    fn new(root: Option<&'iterator TreeNode<T, P>>, remaining: usize, tombstones: bool) -> Self {
+        let mut iter = TreeSequenceIter {
+            stack: Vec::new(),
+            remaining,
+            tombstones,
+        };
+        iter.descend(root);
+        iter
+    }
+
+    // Pushes the path to the first node of the subtree rooted at link.
+    #[inline]
+    fn descend(&mut self, mut link: Option<&'iterator TreeNode<T, P>>) {
+        while let Some(tree_node) = link {
+            self.stack.push(tree_node);
+            link = tree_node.left.as_deref();
+        }
+    }
+}
+
+impl<'iterator, T: 'iterator, P: 'iterator> Iterator for TreeSequenceIter<'iterator, T, P> {
+    type Item = &'iterator Node<T, P>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let tree_node = self.stack.pop()?;
+            self.descend(tree_node.right.as_deref());
+
+            if self.tombstones || tree_node.node.is_some() {
+                self.remaining -= 1;
+                return Some(&tree_node.node);
+            }
+        }
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<'iterator, T: 'iterator, P: 'iterator> ExactSizeIterator for TreeSequenceIter<'iterator, T, P> {}
+
+impl<'iterator, T: 'iterator, P: 'iterator> Clone for TreeSequenceIter<'iterator, T, P> {
+    fn clone(&self) -> Self {
+        TreeSequenceIter {
+            stack: self.stack.clone(),
+            remaining: self.remaining,
+            tombstones: self.tombstones,
+        }
+    }
+}
+
+// Formats the remaining nodes.
+impl<'iterator, T: Debug + 'iterator, P: Debug + 'iterator> Debug for TreeSequenceIter<'iterator, T, P> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_list().entries(self.clone()).finish()
+    }
+}
+
+impl<'iterator, T: 'iterator, P: Position + 'iterator> IntoIterator for &'iterator TreeSequence<T, P> {
+    type Item = &'iterator Node<T, P>;
+    type IntoIter = TreeSequenceIter<'iterator, T, P>;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+// IntoIterator for TreeSequence<T, P>
+// - consumes the `TreeSequence`
+// - removes all empty nodes
+// - iterates over the remaining nodes in order
+impl<T, P> IntoIterator for TreeSequence<T, P> {
+    type Item = Node<T, P>;
+    type IntoIter = std::vec::IntoIter<Node<T, P>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        let mut nodes = Vec::with_capacity(self.nodes);
+        collect(self.root, &mut nodes);
+        nodes.retain(|node| node.is_some());
+        nodes.into_iter()
+    }
+}
+
+#[cfg(test)]
+#[path = "tests/tree_sequence_tests.rs"]
+mod tree_sequence_tests;